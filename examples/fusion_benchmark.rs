@@ -0,0 +1,63 @@
+//! Compare gate-by-gate application with `FusedCircuit` for several block
+//! sizes.
+//!
+//! ```sh
+//! cargo run --release --example fusion_benchmark -- 22
+//! ```
+use qurs::circuit::{Circuit, Gate};
+use qurs::fusion::FusedCircuit;
+use qurs::prelude::*;
+use qurs::StateVec;
+use std::time::Instant;
+
+/// Hardware-efficient ansatz: Ry and Rz on every qubit followed by a CNOT
+/// ladder, repeated `depth` times.
+fn ansatz(qubit_count: u32, depth: usize) -> Circuit {
+	let mut circuit = Circuit::new(qubit_count as usize);
+	for layer in 0..depth {
+		for q in 0..qubit_count {
+			let angle = 0.1 * (layer as f64 + q as f64);
+			circuit.add_gate(Gate::Ry(q, angle));
+			circuit.add_gate(Gate::Rz(q, -angle));
+		}
+		for q in 0..qubit_count - 1 {
+			circuit.add_gate(Gate::Cnot(q, q + 1));
+		}
+	}
+	circuit
+}
+
+fn main() {
+	let qubit_count = std::env::args()
+		.nth(1)
+		.and_then(|n| n.parse().ok())
+		.unwrap_or(20);
+	let circuit = ansatz(qubit_count, 10);
+	let mut state = StateVec::new(qubit_count as usize);
+
+	state.set_zero_state();
+	let start = Instant::now();
+	circuit.update_quantum_state(state.as_mut()).unwrap();
+	let baseline = start.elapsed();
+	println!(
+		"{} qubits, {} gates: gate by gate {:?}",
+		qubit_count,
+		circuit.gate_count(),
+		baseline
+	);
+
+	for max_block_qubits in 2..=5 {
+		let fused = FusedCircuit::new(&circuit, max_block_qubits);
+		state.set_zero_state();
+		let start = Instant::now();
+		fused.update_quantum_state(state.as_mut()).unwrap();
+		let elapsed = start.elapsed();
+		println!(
+			"k = {}: {} blocks {:?} (speedup {:.2}x)",
+			max_block_qubits,
+			fused.block_count(),
+			elapsed,
+			baseline.as_secs_f64() / elapsed.as_secs_f64()
+		);
+	}
+}
@@ -0,0 +1,230 @@
+use crate::binding::{qulacs, CTYPE};
+use crate::circuit::{Circuit, Gate};
+use crate::state::{StateErr, StateRef, StateVec};
+use crate::Complex;
+use std::f64::consts::PI;
+
+/// Multi-controlled Z on `qubits`, i.e. the phase -1 on |1...1>.
+fn multi_controlled_z(qubits: &[u32]) -> Gate {
+	let (target, controls) = qubits.split_last().expect("at least one qubit is required");
+	if controls.is_empty() {
+		return Gate::Z(*target);
+	}
+	Gate::MultiControlledPhase {
+		controls: controls.to_vec(),
+		target: *target,
+		angle: PI,
+	}
+}
+
+fn qft_gates(targets: &[u32], cutoff: Option<usize>) -> Vec<Gate> {
+	let n = targets.len();
+	let mut gates = Vec::new();
+	for j in (0..n).rev() {
+		gates.push(Gate::H(targets[j]));
+		for k in (0..j).rev() {
+			// rotation by 2π/2^m
+			let m = j - k + 1;
+			if matches!(cutoff, Some(c) if m > c) {
+				continue;
+			}
+			gates.push(Gate::Cp(
+				targets[k],
+				targets[j],
+				2. * PI / 2f64.powi(m as i32),
+			));
+		}
+	}
+	for i in 0..n / 2 {
+		gates.push(Gate::Swap(targets[i], targets[n - 1 - i]));
+	}
+	gates
+}
+
+/// Build the quantum Fourier transform
+/// |x> -> 2^{-n/2} Σ_y e^{2πixy/2^n} |y> on `targets`, where `targets[0]` is
+/// the least significant bit of x and y.
+/// * `qubit_count` qubit count of the returned circuit
+/// * `targets` qubits of the register
+/// * `cutoff` if given, controlled phases by 2π/2^m with m > `cutoff` are
+///   dropped (approximate QFT)
+pub fn qft(qubit_count: usize, targets: &[u32], cutoff: Option<usize>) -> Circuit {
+	let mut circuit = Circuit::new(qubit_count);
+	for gate in qft_gates(targets, cutoff) {
+		circuit.add_gate(gate);
+	}
+	circuit
+}
+
+/// Build the inverse of `qft` with the same arguments.
+pub fn inverse_qft(qubit_count: usize, targets: &[u32], cutoff: Option<usize>) -> Circuit {
+	qft(qubit_count, targets, cutoff).inverse()
+}
+
+/// Build a phase oracle which multiplies -1 to each basis state in `marked`.
+pub fn phase_oracle(qubit_count: usize, marked: &[usize]) -> Circuit {
+	let qubits: Vec<u32> = (0..qubit_count as u32).collect();
+	let mut circuit = Circuit::new(qubit_count);
+	for &m in marked {
+		let flips: Vec<u32> = qubits
+			.iter()
+			.cloned()
+			.filter(|q| (m >> q) & 1 == 0)
+			.collect();
+		for &q in &flips {
+			circuit.add_gate(Gate::X(q));
+		}
+		circuit.add_gate(multi_controlled_z(&qubits));
+		for &q in &flips {
+			circuit.add_gate(Gate::X(q));
+		}
+	}
+	circuit
+}
+
+/// Get the number of Grover iterations ⌊π/4 √(N/M)⌋ that maximizes the success
+/// probability for `marked_count` solutions among 2^`qubit_count` states.
+///
+/// # Panics
+/// Panics if `marked_count` is 0 or more than 2^`qubit_count`.
+pub fn grover_iteration_count(qubit_count: usize, marked_count: usize) -> usize {
+	assert!(
+		marked_count > 0 && marked_count <= 1 << qubit_count,
+		"{} marked states are invalid for {} qubits",
+		marked_count,
+		qubit_count
+	);
+	let ratio = (1usize << qubit_count) as f64 / marked_count as f64;
+	(PI / 4. * ratio.sqrt()).floor() as usize
+}
+
+/// Build the Grover search circuit: H on all qubits followed by `iterations`
+/// repetitions of `oracle` and the diffusion operator.
+/// * `oracle` circuit which multiplies -1 to the marked states
+///
+/// # Panics
+/// Panics if `oracle` has no qubit.
+pub fn grover(oracle: &Circuit, iterations: usize) -> Circuit {
+	let n = oracle.qubit_count();
+	let qubits: Vec<u32> = (0..n as u32).collect();
+	let mut circuit = Circuit::new(n);
+	for &q in &qubits {
+		circuit.add_gate(Gate::H(q));
+	}
+	for _ in 0..iterations {
+		circuit.merge_circuit(oracle);
+		for &q in &qubits {
+			circuit.add_gate(Gate::H(q));
+			circuit.add_gate(Gate::X(q));
+		}
+		circuit.add_gate(multi_controlled_z(&qubits));
+		for &q in &qubits {
+			circuit.add_gate(Gate::X(q));
+			circuit.add_gate(Gate::H(q));
+		}
+	}
+	circuit
+}
+
+/// Build the quantum phase estimation circuit.
+///
+/// For an eigenstate U|u> = e^{2πiφ}|u> prepared on the other qubits,
+/// measuring `evaluation_qubits` (`evaluation_qubits[0]` being the least
+/// significant bit) gives y with y/2^m ≈ φ.
+/// * `qubit_count` qubit count of the returned circuit
+/// * `evaluation_qubits` the m qubits of the evaluation register
+/// * `controlled_unitary` called as `controlled_unitary(circuit, control, k)`
+///   and must append U^(2^k) controlled by `control` to `circuit`
+pub fn phase_estimation<F>(
+	qubit_count: usize,
+	evaluation_qubits: &[u32],
+	mut controlled_unitary: F,
+) -> Circuit
+where
+	F: FnMut(&mut Circuit, u32, u32),
+{
+	let mut circuit = Circuit::new(qubit_count);
+	for &q in evaluation_qubits {
+		circuit.add_gate(Gate::H(q));
+	}
+	for (k, &q) in evaluation_qubits.iter().enumerate() {
+		controlled_unitary(&mut circuit, q, k as u32);
+	}
+	circuit.merge_circuit(&inverse_qft(qubit_count, evaluation_qubits, None));
+	circuit
+}
+
+/// Estimate the probability a = |<good|A|0>|^2 by maximum likelihood amplitude
+/// estimation (Suzuki et al., 2020).
+///
+/// For each m in `schedule`, Q^m A|0> is sampled `shots` times where Q is the
+/// Grover operator of A and the good states. The estimate maximizes the
+/// likelihood of the observed hit counts.
+/// * `state_preparation` the circuit A
+/// * `good` whether a basis index is a good state
+/// * `schedule` Grover powers to sample, e.g. `[0, 1, 2, 4, 8]`
+/// * `shots` sampling count for each power
+pub fn maximum_likelihood_amplitude_estimation<F>(
+	state_preparation: &Circuit,
+	good: F,
+	schedule: &[usize],
+	shots: u32,
+) -> Result<f64, StateErr>
+where
+	F: Fn(usize) -> bool,
+{
+	let mut prepared = StateVec::new(state_preparation.qubit_count());
+	state_preparation.update_quantum_state(prepared.as_mut())?;
+
+	let mut hits = Vec::new();
+	for &m in schedule {
+		let mut state = StateVec::new(state_preparation.qubit_count());
+		state.as_mut().copy_from_slice(prepared.as_ref());
+		for _ in 0..m {
+			grover_operator(prepared.as_ref(), &good, state.as_mut());
+		}
+		let count = state
+			.sampling(shots)
+			.into_iter()
+			.filter(|&i| good(i as usize))
+			.count();
+		hits.push((2 * m + 1, count as f64));
+	}
+
+	let log_likelihood = |theta: f64| -> f64 {
+		hits.iter()
+			.map(|&(k, h)| {
+				let p = (k as f64 * theta).sin().powi(2);
+				h * p.max(f64::MIN_POSITIVE).ln()
+					+ (shots as f64 - h) * (1. - p).max(f64::MIN_POSITIVE).ln()
+			})
+			.sum()
+	};
+	let max_k = hits.iter().map(|&(k, _)| k).max().unwrap_or(1);
+	let grid = 1000 * max_k;
+	let theta = (0..=grid)
+		.map(|i| PI / 2. * i as f64 / grid as f64)
+		.max_by(|a, b| log_likelihood(*a).total_cmp(&log_likelihood(*b)))
+		.unwrap();
+	Ok(theta.sin().powi(2))
+}
+
+/// Apply the Grover operator Q = (2|ψ><ψ| - I) S_χ, where S_χ multiplies -1
+/// to the good states and |ψ> = `prepared`.
+fn grover_operator<F>(prepared: &[Complex<f64>], good: &F, state: &mut [Complex<f64>])
+where
+	F: Fn(usize) -> bool,
+{
+	for (i, amplitude) in state.iter_mut().enumerate() {
+		if good(i) {
+			*amplitude = -*amplitude;
+		}
+	}
+	unsafe {
+		qulacs::reflection_gate(
+			prepared.as_ptr() as *const CTYPE,
+			state.as_mut_ptr() as *mut CTYPE,
+			state.len() as u64,
+		)
+	}
+}
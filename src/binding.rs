@@ -13,10 +13,8 @@ pub use qulacs::{CTYPE, UINT};
 /// Value indicate to apply the gate in controlled gate.
 /// Note: Rust requires that we implement `Copy` trait to cast `ControlValue`
 /// into `u32`.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ControlValue {
-	/// For now we don't use `Zero`.
-	#[allow(dead_code)]
 	Zero,
 	One,
 }
@@ -38,12 +36,41 @@ pub enum Gate<'a> {
 		matrix: &'a [Complex<f64>; 4],
 		gate: unsafe extern "C" fn(*const u32, *const u32, u32, u32, *const CTYPE, *mut CTYPE, u64),
 	},
+	MultiControlledMultiTarget {
+		// (control_index, control_value)
+		controls: &'a [(u32, ControlValue)],
+		target_qubit_index_list: &'a [u32],
+		/// 2^n×2^n matrix in row-major order
+		matrix: &'a [Complex<f64>],
+		gate: unsafe extern "C" fn(
+			*const u32,
+			*const u32,
+			u32,
+			*const u32,
+			u32,
+			*const CTYPE,
+			*mut CTYPE,
+			u64,
+		),
+	},
+	SingleMatrix {
+		target_qubit_index: u32,
+		/// 2×2 matrix in row-major order, or its 2 diagonal elements
+		matrix: &'a [Complex<f64>],
+		gate: unsafe extern "C" fn(u32, *const CTYPE, *mut CTYPE, u64),
+	},
 	Rotation {
 		target_qubit_index: u32,
 		/// `angle` of the rotation (radian)
 		angle: f64,
 		gate: unsafe extern "C" fn(u32, f64, *mut CTYPE, u64),
 	},
+	MultiTarget {
+		target_qubit_index_list: &'a [u32],
+		/// 2^n×2^n matrix in row-major order, or its 2^n diagonal elements
+		matrix: &'a [Complex<f64>],
+		gate: unsafe extern "C" fn(*const u32, u32, *const CTYPE, *mut CTYPE, u64),
+	},
 }
 
 /// Custom constructors.
@@ -117,6 +144,37 @@ pub fn wrap(state: &mut [Complex<f64>], gate: Gate) {
 					dim,
 				)
 			}
+			Gate::MultiControlledMultiTarget {
+				controls,
+				target_qubit_index_list,
+				matrix,
+				gate,
+			} => {
+				let (control_qubit_index_list, control_value_list): (Vec<u32>, Vec<u32>) =
+					controls.iter().map(|c| (c.0, c.1 as u32)).unzip();
+				gate(
+					control_qubit_index_list.as_ptr(),
+					control_value_list.as_ptr(),
+					controls.len() as u32,
+					target_qubit_index_list.as_ptr(),
+					target_qubit_index_list.len() as u32,
+					matrix.as_ptr() as *const CTYPE,
+					state_ptr,
+					dim,
+				);
+			}
+			Gate::SingleMatrix {
+				target_qubit_index,
+				matrix,
+				gate,
+			} => {
+				gate(
+					target_qubit_index,
+					matrix.as_ptr() as *const CTYPE,
+					state_ptr,
+					dim,
+				);
+			}
 			Gate::Rotation {
 				target_qubit_index,
 				angle,
@@ -124,6 +182,19 @@ pub fn wrap(state: &mut [Complex<f64>], gate: Gate) {
 			} => {
 				gate(target_qubit_index, angle, state_ptr, dim);
 			}
+			Gate::MultiTarget {
+				target_qubit_index_list,
+				matrix,
+				gate,
+			} => {
+				gate(
+					target_qubit_index_list.as_ptr(),
+					target_qubit_index_list.len() as u32,
+					matrix.as_ptr() as *const CTYPE,
+					state_ptr,
+					dim,
+				);
+			}
 		}
 	}
 }
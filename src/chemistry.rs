@@ -0,0 +1,620 @@
+use crate::circuit::{Circuit, Gate};
+use crate::fermion::{FermionEncoding, FermionOperator, LadderOperator};
+use crate::observable::Observable;
+use crate::pauli::PauliString;
+use crate::Complex;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::path::Path;
+
+#[non_exhaustive]
+pub enum FcidumpErr {
+	Io(std::io::Error),
+	/// The `&FCI ... &END` namelist is missing or malformed.
+	InvalidHeader(String),
+	/// A required key of the namelist is missing.
+	MissingKey(&'static str),
+	/// The integral line (1-based line number) cannot be parsed.
+	InvalidLine(usize, String),
+	/// The integral line (1-based line number) refers to an orbital out of
+	/// range.
+	InvalidOrbitalIndex(usize, usize),
+	/// Unrestricted integrals (`UHF=.TRUE.`) are not supported.
+	UnsupportedUhf,
+}
+
+impl fmt::Display for FcidumpErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			FcidumpErr::Io(e) => write!(f, "IO error: {}", e),
+			FcidumpErr::InvalidHeader(header) => write!(f, "Invalid header: {}", header),
+			FcidumpErr::MissingKey(key) => write!(f, "Missing key: {} is required", key),
+			FcidumpErr::InvalidLine(n, line) => write!(f, "Invalid line {}: {}", n, line),
+			FcidumpErr::InvalidOrbitalIndex(n, index) => write!(
+				f,
+				"Invalid orbital index: line {} refers to orbital {}",
+				n, index
+			),
+			FcidumpErr::UnsupportedUhf => write!(f, "Unsupported UHF integrals"),
+		}
+	}
+}
+
+impl fmt::Debug for FcidumpErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		<Self as fmt::Display>::fmt(self, f)
+	}
+}
+
+impl From<std::io::Error> for FcidumpErr {
+	fn from(e: std::io::Error) -> Self {
+		FcidumpErr::Io(e)
+	}
+}
+
+/// One- and two-electron integrals over real spatial orbitals read from an
+/// FCIDUMP file.
+///
+/// Spatial orbital p corresponds to the spin orbitals 2p (α) and 2p + 1 (β),
+/// which are the modes of the fermionic Hamiltonian.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fcidump {
+	orbital_count: usize,
+	electron_count: usize,
+	ms2: i64,
+	orbital_symmetries: Vec<u32>,
+	symmetry: u32,
+	core_energy: f64,
+	/// h_pq in row-major order
+	one_body: Vec<f64>,
+	/// (pq|rs) in chemists' notation, indexed by ((p n + q) n + r) n + s
+	two_body: Vec<f64>,
+}
+
+impl Fcidump {
+	/// Read an FCIDUMP file.
+	pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, FcidumpErr> {
+		Self::parse(&std::fs::read_to_string(path)?)
+	}
+
+	/// Parse the contents of an FCIDUMP file.
+	///
+	/// Each integral line `x i j k l` with 1-based orbital indices gives the
+	/// two-electron integral (ij|kl) = x, the one-electron integral h_ij = x if
+	/// k = l = 0, or the core energy x if all indices are 0. Lines `x i 0 0 0`
+	/// of orbital energies are ignored. The missing integrals are filled by
+	/// the 8-fold permutational symmetry of real orbitals. `ORBSYM` must list
+	/// one irreducible representation from 1 to 8 per orbital and `ISYM` must
+	/// be one of them too; they are kept as given and not used to check the
+	/// integrals.
+	///
+	/// ```
+	/// # use qurs::chemistry::Fcidump;
+	/// let fcidump = Fcidump::parse(
+	///     "&FCI NORB=1,NELEC=2,MS2=0,
+	///      ORBSYM=1,
+	///      ISYM=1,
+	///     &END
+	///      0.5  1 1 1 1
+	///     -1.0  1 1 0 0
+	///      0.3  0 0 0 0",
+	/// )
+	/// .unwrap();
+	/// assert_eq!(fcidump.orbital_count(), 1);
+	/// assert_eq!(fcidump.two_body_integral(0, 0, 0, 0), 0.5);
+	/// assert_eq!(fcidump.hartree_fock_energy(), 0.3 - 2. + 0.5);
+	/// ```
+	pub fn parse(text: &str) -> Result<Self, FcidumpErr> {
+		let (namelist, body_start) = split_header(text)?;
+		let number = |key: &'static str| -> Result<i64, FcidumpErr> {
+			let values = namelist.get(key).ok_or(FcidumpErr::MissingKey(key))?;
+			values
+				.first()
+				.and_then(|v| v.parse().ok())
+				.ok_or_else(|| FcidumpErr::InvalidHeader(format!("{}={:?}", key, values)))
+		};
+		let orbital_count = number("NORB")?;
+		let electron_count = number("NELEC")?;
+		if orbital_count < 0 || electron_count < 0 || electron_count > 2 * orbital_count {
+			return Err(FcidumpErr::InvalidHeader(format!(
+				"NORB={}, NELEC={}",
+				orbital_count, electron_count
+			)));
+		}
+		let (n, electron_count) = (orbital_count as usize, electron_count as usize);
+		let ms2 = if namelist.contains_key("MS2") {
+			number("MS2")?
+		} else {
+			0
+		};
+		// N_α = (N + MS2)/2 and N_β = (N - MS2)/2 must fit into the orbitals
+		let electrons = electron_count as i64;
+		if ms2.abs() > electrons
+			|| (electrons + ms2) % 2 != 0
+			|| (electrons + ms2.abs()) / 2 > orbital_count
+		{
+			return Err(FcidumpErr::InvalidHeader(format!(
+				"NELEC={}, MS2={}",
+				electron_count, ms2
+			)));
+		}
+		// irreducible representations of D2h and its subgroups are numbered 1 to 8
+		let is_irrep = |irrep: &u32| (1..=8).contains(irrep);
+		let symmetry = if namelist.contains_key("ISYM") {
+			let isym = number("ISYM")?;
+			u32::try_from(isym)
+				.ok()
+				.filter(is_irrep)
+				.ok_or_else(|| FcidumpErr::InvalidHeader(format!("ISYM={}", isym)))?
+		} else {
+			1
+		};
+		let orbital_symmetries = match namelist.get("ORBSYM") {
+			Some(values) => values
+				.iter()
+				.map(|v| v.parse::<u32>().ok().filter(is_irrep))
+				.collect::<Option<Vec<_>>>()
+				.filter(|irreps| irreps.len() == n)
+				.ok_or_else(|| FcidumpErr::InvalidHeader(format!("ORBSYM={:?}", values)))?,
+			None => vec![1; n],
+		};
+		if let Some(uhf) = namelist.get("UHF").and_then(|v| v.first()) {
+			if matches!(uhf.as_str(), ".TRUE." | "T" | "TRUE" | "1") {
+				return Err(FcidumpErr::UnsupportedUhf);
+			}
+		}
+
+		let mut fcidump = Fcidump {
+			orbital_count: n,
+			electron_count,
+			ms2,
+			orbital_symmetries,
+			symmetry,
+			core_energy: 0.,
+			one_body: vec![0.; n * n],
+			two_body: vec![0.; n * n * n * n],
+		};
+		for (line_number, line) in text.lines().enumerate().skip(body_start) {
+			let invalid = || FcidumpErr::InvalidLine(line_number + 1, line.to_string());
+			let fields: Vec<&str> = line.split_whitespace().collect();
+			if fields.is_empty() {
+				continue;
+			}
+			if fields.len() != 5 {
+				return Err(invalid());
+			}
+			// Fortran writes exponents as 1.0D-02.
+			let value: f64 = fields[0]
+				.replace(['D', 'd'], "e")
+				.parse()
+				.map_err(|_| invalid())?;
+			let mut indices = [0usize; 4];
+			for (index, field) in indices.iter_mut().zip(&fields[1..]) {
+				*index = field.parse().map_err(|_| invalid())?;
+				if *index > n {
+					return Err(FcidumpErr::InvalidOrbitalIndex(line_number + 1, *index));
+				}
+			}
+			match indices {
+				[0, 0, 0, 0] => fcidump.core_energy = value,
+				[_, 0, 0, 0] => {}
+				[i, j, 0, 0] if i > 0 && j > 0 => fcidump.set_one_body(i - 1, j - 1, value),
+				[i, j, k, l] if i > 0 && j > 0 && k > 0 && l > 0 => {
+					fcidump.set_two_body(i - 1, j - 1, k - 1, l - 1, value)
+				}
+				_ => return Err(invalid()),
+			}
+		}
+		Ok(fcidump)
+	}
+
+	fn set_one_body(&mut self, p: usize, q: usize, value: f64) {
+		let n = self.orbital_count;
+		self.one_body[p * n + q] = value;
+		self.one_body[q * n + p] = value;
+	}
+
+	fn set_two_body(&mut self, p: usize, q: usize, r: usize, s: usize, value: f64) {
+		let n = self.orbital_count;
+		for &(a, b) in &[(p, q), (q, p)] {
+			for &(c, d) in &[(r, s), (s, r)] {
+				self.two_body[((a * n + b) * n + c) * n + d] = value;
+				self.two_body[((c * n + d) * n + a) * n + b] = value;
+			}
+		}
+	}
+
+	///Get the number of spatial orbitals
+	pub fn orbital_count(&self) -> usize {
+		self.orbital_count
+	}
+
+	///Get the number of electrons
+	pub fn electron_count(&self) -> usize {
+		self.electron_count
+	}
+
+	///Get twice the total spin projection, i.e. N_α - N_β
+	pub fn ms2(&self) -> i64 {
+		self.ms2
+	}
+
+	///Get the irreducible representation of each spatial orbital
+	pub fn orbital_symmetries(&self) -> &[u32] {
+		&self.orbital_symmetries
+	}
+
+	///Get the irreducible representation of the state
+	pub fn symmetry(&self) -> u32 {
+		self.symmetry
+	}
+
+	///Get the core energy including the nuclear repulsion
+	pub fn core_energy(&self) -> f64 {
+		self.core_energy
+	}
+
+	///Get the one-electron integral h_pq (0-based)
+	pub fn one_body_integral(&self, p: usize, q: usize) -> f64 {
+		self.one_body[p * self.orbital_count + q]
+	}
+
+	///Get the two-electron integral (pq|rs) in chemists' notation (0-based)
+	pub fn two_body_integral(&self, p: usize, q: usize, r: usize, s: usize) -> f64 {
+		let n = self.orbital_count;
+		self.two_body[((p * n + q) * n + r) * n + s]
+	}
+
+	///Get the number of spin orbitals, i.e. qubits
+	pub fn spin_orbital_count(&self) -> usize {
+		2 * self.orbital_count
+	}
+
+	/// Get the electronic Hamiltonian
+	///
+	/// H = E_core + Σ h_pq a†_pσ a_qσ + 1/2 Σ (pq|rs) a†_pσ a†_rτ a_sτ a_qσ
+	///
+	/// where mode 2p + σ is spatial orbital p with spin σ.
+	pub fn fermion_operator(&self) -> FermionOperator {
+		use LadderOperator::{Annihilate, Create};
+		let n = self.orbital_count;
+		let mut h = FermionOperator::new();
+		if self.core_energy != 0. {
+			h.add_term(Complex::new(self.core_energy, 0.), &[]);
+		}
+		for p in 0..n {
+			for q in 0..n {
+				let h_pq = self.one_body_integral(p, q);
+				if h_pq == 0. {
+					continue;
+				}
+				for sigma in 0..2 {
+					h.add_term(
+						Complex::new(h_pq, 0.),
+						&[Create(2 * p + sigma), Annihilate(2 * q + sigma)],
+					);
+				}
+			}
+		}
+		for p in 0..n {
+			for q in 0..n {
+				for r in 0..n {
+					for s in 0..n {
+						let v = self.two_body_integral(p, q, r, s);
+						if v == 0. {
+							continue;
+						}
+						for sigma in 0..2 {
+							for tau in 0..2 {
+								let (ps, qs) = (2 * p + sigma, 2 * q + sigma);
+								let (rt, st) = (2 * r + tau, 2 * s + tau);
+								if ps == rt || qs == st {
+									continue;
+								}
+								h.add_term(
+									Complex::new(0.5 * v, 0.),
+									&[Create(ps), Create(rt), Annihilate(st), Annihilate(qs)],
+								);
+							}
+						}
+					}
+				}
+			}
+		}
+		h.simplify(0.);
+		h
+	}
+
+	/// Get the qubit Hamiltonian on `spin_orbital_count()` qubits.
+	pub fn observable(&self, encoding: FermionEncoding) -> Observable {
+		self.fermion_operator()
+			.encode(encoding, self.spin_orbital_count())
+	}
+
+	/// Get the occupation of the Hartree–Fock state, whose bit 2p + σ is set
+	/// for the lowest N_α spatial orbitals with spin α and the lowest N_β with
+	/// spin β.
+	pub fn hartree_fock_occupation(&self) -> usize {
+		let n = self.electron_count as i64;
+		let alpha = ((n + self.ms2) / 2) as usize;
+		let beta = ((n - self.ms2) / 2) as usize;
+		(0..alpha).map(|p| 1 << (2 * p)).sum::<usize>()
+			+ (0..beta).map(|p| 1 << (2 * p + 1)).sum::<usize>()
+	}
+
+	/// Get the computational basis index of the Hartree–Fock state under
+	/// `encoding`, to be passed to `set_computational_basis`.
+	pub fn hartree_fock_state(&self, encoding: FermionEncoding) -> usize {
+		encoding.basis_index(self.hartree_fock_occupation(), self.spin_orbital_count())
+	}
+
+	/// Get the Hartree–Fock energy
+	/// E_core + Σ_i h_ii + 1/2 Σ_ij ((ii|jj) - δ_σiσj (ij|ji)) over occupied
+	/// spin orbitals.
+	pub fn hartree_fock_energy(&self) -> f64 {
+		let occupation = self.hartree_fock_occupation();
+		let occupied: Vec<(usize, usize)> = (0..self.spin_orbital_count())
+			.filter(|i| (occupation >> i) & 1 == 1)
+			.map(|i| (i / 2, i % 2))
+			.collect();
+		let mut energy = self.core_energy;
+		for &(i, si) in &occupied {
+			energy += self.one_body_integral(i, i);
+			for &(j, sj) in &occupied {
+				energy += 0.5 * self.two_body_integral(i, i, j, j);
+				if si == sj {
+					energy -= 0.5 * self.two_body_integral(i, j, j, i);
+				}
+			}
+		}
+		energy
+	}
+}
+
+/// Parse the namelist into upper-case keys and their values, and get the
+/// index of the first line after it.
+fn split_header(text: &str) -> Result<(HashMap<String, Vec<String>>, usize), FcidumpErr> {
+	let mut namelist: HashMap<String, Vec<String>> = HashMap::new();
+	let mut key: Option<String> = None;
+	let mut started = false;
+	for (line_number, line) in text.lines().enumerate() {
+		for token in line.split(|c: char| c == ',' || c.is_whitespace()) {
+			let token = token.trim();
+			if token.is_empty() {
+				continue;
+			}
+			let upper = token.to_uppercase();
+			if !started {
+				if upper == "&FCI" || upper == "$FCI" {
+					started = true;
+					continue;
+				}
+				return Err(FcidumpErr::InvalidHeader(line.to_string()));
+			}
+			if upper == "&END" || upper == "$END" || upper == "/" || upper == "&" {
+				return Ok((namelist, line_number + 1));
+			}
+			match upper.split_once('=') {
+				Some((k, v)) => {
+					let values = namelist.entry(k.to_string()).or_default();
+					if !v.is_empty() {
+						values.push(v.to_string());
+					}
+					key = Some(k.to_string());
+				}
+				None => match &key {
+					Some(k) => namelist.get_mut(k).unwrap().push(upper),
+					None => return Err(FcidumpErr::InvalidHeader(line.to_string())),
+				},
+			}
+		}
+	}
+	Err(FcidumpErr::InvalidHeader("missing &END".to_string()))
+}
+
+/// Build the circuit preparing the Hartree–Fock state under the Jordan–Wigner
+/// encoding, i.e. X on the qubit of each spin orbital set in `occupation`,
+/// such as `Fcidump::hartree_fock_occupation`.
+///
+/// # Panics
+/// Panics if `occupation` has a spin orbital out of range.
+pub fn hartree_fock_circuit(occupation: usize, spin_orbital_count: usize) -> Circuit {
+	check_occupation(occupation, spin_orbital_count);
+	let mut circuit = Circuit::new(spin_orbital_count);
+	for q in 0..spin_orbital_count as u32 {
+		if (occupation >> q) & 1 == 1 {
+			circuit.add_gate(Gate::X(q));
+		}
+	}
+	circuit
+}
+
+fn check_occupation(occupation: usize, spin_orbital_count: usize) {
+	assert!(
+		spin_orbital_count >= usize::BITS as usize || occupation >> spin_orbital_count == 0,
+		"occupation {:#b} has spin orbitals out of range for {} spin orbitals",
+		occupation,
+		spin_orbital_count
+	);
+}
+
+/// A Trotterized unitary coupled-cluster ansatz
+///
+/// U(θ) = Π_k exp(θ_k (τ_k - τ_k†)) |HF>
+///
+/// under the Jordan–Wigner encoding, where each excitation operator τ_k has
+/// its own parameter θ_k and the product is taken in the order of
+/// `excitations`. Each exp(θ_k (τ_k - τ_k†)) is the product of the Pauli
+/// rotations of its Pauli terms, all sharing θ_k.
+#[derive(Clone, Debug)]
+pub struct CoupledClusterAnsatz {
+	occupation: usize,
+	spin_orbital_count: usize,
+	excitations: Vec<FermionOperator>,
+	/// (g, P) for each excitation such that τ - τ† = Σ i g P
+	generators: Vec<Vec<(f64, PauliString)>>,
+}
+
+impl CoupledClusterAnsatz {
+	fn new(
+		occupation: usize,
+		spin_orbital_count: usize,
+		excitations: Vec<FermionOperator>,
+	) -> Self {
+		check_occupation(occupation, spin_orbital_count);
+		let generators = excitations
+			.iter()
+			.map(|tau| {
+				let generator = (tau.clone() - tau.dagger())
+					.encode(FermionEncoding::JordanWigner, spin_orbital_count);
+				generator
+					.terms()
+					.iter()
+					.map(|(c, p)| {
+						// τ - τ† is anti-Hermitian.
+						debug_assert!(c.re.abs() < 1e-12);
+						(c.im, p.clone())
+					})
+					.collect()
+			})
+			.collect();
+		Self {
+			occupation,
+			spin_orbital_count,
+			excitations,
+			generators,
+		}
+	}
+
+	/// Build UCCSD with the excitations a†_a a_i and a†_a a†_b a_j a_i from
+	/// spin orbitals i < j set in `occupation` to unset ones a < b that
+	/// conserve the spin, singles first. Mode 2p + σ is spatial orbital p with
+	/// spin σ as in `Fcidump`, so `Fcidump::hartree_fock_occupation` gives the
+	/// reference of any MS2.
+	///
+	/// # Panics
+	/// Panics if `occupation` has a spin orbital out of range.
+	pub fn uccsd(occupation: usize, spin_orbital_count: usize) -> Self {
+		use LadderOperator::{Annihilate, Create};
+		check_occupation(occupation, spin_orbital_count);
+		let one = Complex::new(1., 0.);
+		let (occupied, virtual_orbitals): (Vec<usize>, Vec<usize>) =
+			(0..spin_orbital_count).partition(|&q| (occupation >> q) & 1 == 1);
+		let mut excitations = Vec::new();
+		for &i in &occupied {
+			for &a in &virtual_orbitals {
+				if i % 2 == a % 2 {
+					excitations.push(FermionOperator::term(one, &[Create(a), Annihilate(i)]));
+				}
+			}
+		}
+		for (k, &i) in occupied.iter().enumerate() {
+			for &j in &occupied[k + 1..] {
+				for (l, &a) in virtual_orbitals.iter().enumerate() {
+					for &b in &virtual_orbitals[l + 1..] {
+						if i % 2 + j % 2 == a % 2 + b % 2 {
+							excitations.push(FermionOperator::term(
+								one,
+								&[Create(a), Create(b), Annihilate(j), Annihilate(i)],
+							));
+						}
+					}
+				}
+			}
+		}
+		Self::new(occupation, spin_orbital_count, excitations)
+	}
+
+	/// Build k-UpCCGSD (Lee et al., 2019): `k` layers, each of the generalized
+	/// singles a†_qα a_pα + a†_qβ a_pβ with α and β sharing a parameter and
+	/// the paired doubles a†_qα a†_qβ a_pβ a_pα over all spatial orbitals
+	/// p < q. Every layer has its own parameters. The reference state sets the
+	/// spin orbitals of `occupation`.
+	///
+	/// # Panics
+	/// Panics if `occupation` has a spin orbital out of range or the number
+	/// of spin orbitals is odd.
+	pub fn k_upccgsd(k: usize, occupation: usize, spin_orbital_count: usize) -> Self {
+		use LadderOperator::{Annihilate, Create};
+		assert!(
+			spin_orbital_count & 1 == 0,
+			"the number of spin orbitals must be even, but {} was supplied",
+			spin_orbital_count
+		);
+		let one = Complex::new(1., 0.);
+		let n = spin_orbital_count / 2;
+		let mut layer = Vec::new();
+		for p in 0..n {
+			for q in p + 1..n {
+				layer.push(
+					FermionOperator::term(one, &[Create(2 * q), Annihilate(2 * p)])
+						+ FermionOperator::term(one, &[Create(2 * q + 1), Annihilate(2 * p + 1)]),
+				);
+			}
+		}
+		for p in 0..n {
+			for q in p + 1..n {
+				layer.push(FermionOperator::term(
+					one,
+					&[
+						Create(2 * q),
+						Create(2 * q + 1),
+						Annihilate(2 * p + 1),
+						Annihilate(2 * p),
+					],
+				));
+			}
+		}
+		let excitations = (0..k).flat_map(|_| layer.iter().cloned()).collect();
+		Self::new(occupation, spin_orbital_count, excitations)
+	}
+
+	///Get the number of spin orbitals, i.e. qubits
+	pub fn qubit_count(&self) -> usize {
+		self.spin_orbital_count
+	}
+
+	///Get the occupied spin orbitals of the reference state as bits
+	pub fn occupation(&self) -> usize {
+		self.occupation
+	}
+
+	///Get the number of electrons
+	pub fn electron_count(&self) -> usize {
+		self.occupation.count_ones() as usize
+	}
+
+	///Get the number of parameters
+	pub fn parameter_count(&self) -> usize {
+		self.excitations.len()
+	}
+
+	///Get the excitation operator τ_k of each parameter
+	pub fn excitations(&self) -> &[FermionOperator] {
+		&self.excitations
+	}
+
+	/// Build the circuit of the Hartree–Fock preparation followed by the
+	/// Pauli rotations of each excitation.
+	///
+	/// # Panics
+	/// Panics if the length of `parameters` is not `parameter_count()`.
+	pub fn circuit(&self, parameters: &[f64]) -> Circuit {
+		assert_eq!(
+			parameters.len(),
+			self.parameter_count(),
+			"the ansatz has {} parameters, but {} were supplied",
+			self.parameter_count(),
+			parameters.len()
+		);
+		let mut circuit = hartree_fock_circuit(self.occupation, self.spin_orbital_count);
+		for (theta, generator) in parameters.iter().zip(&self.generators) {
+			for (g, pauli) in generator {
+				// exp(iθgP) = exp(-i(-2θg)/2 P)
+				circuit.add_gate(pauli.rotation_gate(-2. * theta * g));
+			}
+		}
+		circuit
+	}
+}
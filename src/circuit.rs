@@ -0,0 +1,918 @@
+use crate::binding::{qulacs, CTYPE};
+use crate::gate::{self, ControlValue};
+use crate::pauli::PauliString;
+use crate::state::{StateErr, StateMut, StateVec};
+use crate::synthesis::pow2;
+use crate::{inner_product, Complex};
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+/// Circuits up to this qubit count are compared by their full unitary in
+/// `equivalent`. Larger ones are compared on random states.
+const EXACT_EQUIVALENCE_MAX_QUBITS: usize = 10;
+/// Number of Haar random states used for the randomized equivalence check.
+const RANDOM_STATE_COUNT: u32 = 4;
+const EQUIVALENCE_EPS: f64 = 1e-8;
+
+/// A gate stored in a `Circuit`. Each variant corresponds to a function in
+/// `qurs::gate` and takes the same qubit indexes and angles in the same
+/// order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Gate {
+	X(u32),
+	Y(u32),
+	Z(u32),
+	H(u32),
+	P0(u32),
+	P1(u32),
+	S(u32),
+	Sdag(u32),
+	T(u32),
+	Tdag(u32),
+	SqrtX(u32),
+	SqrtXdag(u32),
+	SqrtY(u32),
+	SqrtYdag(u32),
+	/// (control, target)
+	Cz(u32, u32),
+	/// (control, target)
+	Cnot(u32, u32),
+	Swap(u32, u32),
+	/// (control1, control2, target)
+	Ccnot(u32, u32, u32),
+	/// (control1, control2, target)
+	Ccz(u32, u32, u32),
+	/// (target, angle)
+	Rx(u32, f64),
+	/// (target, angle)
+	Ry(u32, f64),
+	/// (target, angle)
+	Rz(u32, f64),
+	/// (target0, target1, angle)
+	Rxx(u32, u32, f64),
+	/// (target0, target1, angle)
+	Ryy(u32, u32, f64),
+	/// (target0, target1, angle)
+	Rzz(u32, u32, f64),
+	ISwap(u32, u32),
+	SqrtISwap(u32, u32),
+	/// (target0, target1, theta, phi)
+	FSim(u32, u32, f64, f64),
+	Ecr(u32, u32),
+	/// (target0, target1, angle)
+	Givens(u32, u32, f64),
+	/// (target, lambda), also known as `u1`
+	Phase(u32, f64),
+	/// (target, phi, lambda)
+	U2(u32, f64, f64),
+	/// (target, theta, phi, lambda)
+	U3(u32, f64, f64, f64),
+	/// (control, target, lambda)
+	Cp(u32, u32, f64),
+	/// Phase e^{iλ} on the states where all `controls` and `target` are |1>.
+	MultiControlledPhase {
+		controls: Vec<u32>,
+		target: u32,
+		angle: f64,
+	},
+	/// Single-qubit `matrix` (row-major) applied to `target` when all
+	/// `controls` are |1>.
+	MultiControlled {
+		controls: Vec<u32>,
+		target: u32,
+		matrix: [Complex<f64>; 4],
+	},
+	/// Dense matrix (row-major) on `targets`, where `targets[0]` is the least
+	/// significant bit of the matrix indexes.
+	DenseMatrix {
+		targets: Vec<u32>,
+		matrix: Vec<Complex<f64>>,
+	},
+	/// Dense matrix as in `DenseMatrix`, applied when every control qubit has
+	/// its control value. Built by `Gate::controlled`.
+	Controlled {
+		controls: Vec<(u32, ControlValue)>,
+		targets: Vec<u32>,
+		matrix: Vec<Complex<f64>>,
+	},
+	/// Rotation exp(-i(θ/2)P) by the Pauli string P given as a list of target
+	/// qubits and Pauli types (0: I, 1: X, 2: Y, 3: Z).
+	PauliRotation {
+		targets: Vec<u32>,
+		paulis: Vec<u32>,
+		angle: f64,
+	},
+}
+
+impl Gate {
+	/// Get the indexes of all qubits this gate acts on, including controls.
+	pub fn qubits(&self) -> Vec<u32> {
+		use Gate::*;
+		match self {
+			X(q)
+			| Y(q)
+			| Z(q)
+			| H(q)
+			| P0(q)
+			| P1(q)
+			| S(q)
+			| Sdag(q)
+			| T(q)
+			| Tdag(q)
+			| SqrtX(q)
+			| SqrtXdag(q)
+			| SqrtY(q)
+			| SqrtYdag(q)
+			| Rx(q, _)
+			| Ry(q, _)
+			| Rz(q, _)
+			| Phase(q, _)
+			| U2(q, ..)
+			| U3(q, ..) => vec![*q],
+			Cz(a, b)
+			| Cnot(a, b)
+			| Swap(a, b)
+			| Rxx(a, b, _)
+			| Ryy(a, b, _)
+			| Rzz(a, b, _)
+			| ISwap(a, b)
+			| SqrtISwap(a, b)
+			| FSim(a, b, ..)
+			| Ecr(a, b)
+			| Givens(a, b, _)
+			| Cp(a, b, _) => vec![*a, *b],
+			Ccnot(a, b, c) | Ccz(a, b, c) => vec![*a, *b, *c],
+			MultiControlled {
+				controls, target, ..
+			}
+			| MultiControlledPhase {
+				controls, target, ..
+			} => controls.iter().chain(Some(target)).cloned().collect(),
+			DenseMatrix { targets, .. } | PauliRotation { targets, .. } => targets.clone(),
+			Controlled {
+				controls, targets, ..
+			} => controls
+				.iter()
+				.map(|&(q, _)| q)
+				.chain(targets.iter().cloned())
+				.collect(),
+		}
+	}
+
+	/// Get the name of the variant of this gate, such as `"Cnot"`.
+	pub fn name(&self) -> &'static str {
+		use Gate::*;
+		match self {
+			X(..) => "X",
+			Y(..) => "Y",
+			Z(..) => "Z",
+			H(..) => "H",
+			P0(..) => "P0",
+			P1(..) => "P1",
+			S(..) => "S",
+			Sdag(..) => "Sdag",
+			T(..) => "T",
+			Tdag(..) => "Tdag",
+			SqrtX(..) => "SqrtX",
+			SqrtXdag(..) => "SqrtXdag",
+			SqrtY(..) => "SqrtY",
+			SqrtYdag(..) => "SqrtYdag",
+			Cz(..) => "Cz",
+			Cnot(..) => "Cnot",
+			Swap(..) => "Swap",
+			Ccnot(..) => "Ccnot",
+			Ccz(..) => "Ccz",
+			Rx(..) => "Rx",
+			Ry(..) => "Ry",
+			Rz(..) => "Rz",
+			Rxx(..) => "Rxx",
+			Ryy(..) => "Ryy",
+			Rzz(..) => "Rzz",
+			ISwap(..) => "ISwap",
+			SqrtISwap(..) => "SqrtISwap",
+			FSim(..) => "FSim",
+			Ecr(..) => "Ecr",
+			Givens(..) => "Givens",
+			Phase(..) => "Phase",
+			U2(..) => "U2",
+			U3(..) => "U3",
+			Cp(..) => "Cp",
+			MultiControlledPhase { .. } => "MultiControlledPhase",
+			MultiControlled { .. } => "MultiControlled",
+			DenseMatrix { .. } => "DenseMatrix",
+			Controlled { .. } => "Controlled",
+			PauliRotation { .. } => "PauliRotation",
+		}
+	}
+
+	/// Get the same gate acting on qubit `f(q)` instead of each qubit `q`.
+	pub fn map_qubits<F>(&self, f: F) -> Gate
+	where
+		F: Fn(u32) -> u32,
+	{
+		use Gate::*;
+		match self {
+			X(q) => X(f(*q)),
+			Y(q) => Y(f(*q)),
+			Z(q) => Z(f(*q)),
+			H(q) => H(f(*q)),
+			P0(q) => P0(f(*q)),
+			P1(q) => P1(f(*q)),
+			S(q) => S(f(*q)),
+			Sdag(q) => Sdag(f(*q)),
+			T(q) => T(f(*q)),
+			Tdag(q) => Tdag(f(*q)),
+			SqrtX(q) => SqrtX(f(*q)),
+			SqrtXdag(q) => SqrtXdag(f(*q)),
+			SqrtY(q) => SqrtY(f(*q)),
+			SqrtYdag(q) => SqrtYdag(f(*q)),
+			Cz(a, b) => Cz(f(*a), f(*b)),
+			Cnot(a, b) => Cnot(f(*a), f(*b)),
+			Swap(a, b) => Swap(f(*a), f(*b)),
+			Ccnot(a, b, c) => Ccnot(f(*a), f(*b), f(*c)),
+			Ccz(a, b, c) => Ccz(f(*a), f(*b), f(*c)),
+			Rx(q, angle) => Rx(f(*q), *angle),
+			Ry(q, angle) => Ry(f(*q), *angle),
+			Rz(q, angle) => Rz(f(*q), *angle),
+			Rxx(a, b, angle) => Rxx(f(*a), f(*b), *angle),
+			Ryy(a, b, angle) => Ryy(f(*a), f(*b), *angle),
+			Rzz(a, b, angle) => Rzz(f(*a), f(*b), *angle),
+			ISwap(a, b) => ISwap(f(*a), f(*b)),
+			SqrtISwap(a, b) => SqrtISwap(f(*a), f(*b)),
+			FSim(a, b, theta, phi) => FSim(f(*a), f(*b), *theta, *phi),
+			Ecr(a, b) => Ecr(f(*a), f(*b)),
+			Givens(a, b, angle) => Givens(f(*a), f(*b), *angle),
+			Phase(q, lambda) => Phase(f(*q), *lambda),
+			U2(q, phi, lambda) => U2(f(*q), *phi, *lambda),
+			U3(q, theta, phi, lambda) => U3(f(*q), *theta, *phi, *lambda),
+			Cp(a, b, lambda) => Cp(f(*a), f(*b), *lambda),
+			MultiControlledPhase {
+				controls,
+				target,
+				angle,
+			} => MultiControlledPhase {
+				controls: controls.iter().map(|q| f(*q)).collect(),
+				target: f(*target),
+				angle: *angle,
+			},
+			MultiControlled {
+				controls,
+				target,
+				matrix,
+			} => MultiControlled {
+				controls: controls.iter().map(|q| f(*q)).collect(),
+				target: f(*target),
+				matrix: *matrix,
+			},
+			DenseMatrix { targets, matrix } => DenseMatrix {
+				targets: targets.iter().map(|q| f(*q)).collect(),
+				matrix: matrix.clone(),
+			},
+			Controlled {
+				controls,
+				targets,
+				matrix,
+			} => Controlled {
+				controls: controls.iter().map(|&(q, v)| (f(q), v)).collect(),
+				targets: targets.iter().map(|q| f(*q)).collect(),
+				matrix: matrix.clone(),
+			},
+			PauliRotation {
+				targets,
+				paulis,
+				angle,
+			} => PauliRotation {
+				targets: targets.iter().map(|q| f(*q)).collect(),
+				paulis: paulis.clone(),
+				angle: *angle,
+			},
+		}
+	}
+
+	/// Get this gate applied only when each qubit in `controls` has its
+	/// control value. Controls of an already controlled gate are kept, so
+	/// that e.g. a controlled `Cnot` becomes a Toffoli gate.
+	///
+	/// ```
+	/// # use qurs::circuit::Gate;
+	/// # use qurs::gate::ControlValue;
+	/// // H on qubit 2 when qubit 0 is |0> and qubit 1 is |1>
+	/// let gate = Gate::H(2).controlled(&[(0, ControlValue::Zero), (1, ControlValue::One)]);
+	/// assert_eq!(gate.qubits(), vec![0, 1, 2]);
+	/// ```
+	///
+	/// # Panics
+	/// Panics if a control qubit is also a qubit of this gate or given twice.
+	pub fn controlled(&self, controls: &[(u32, ControlValue)]) -> Gate {
+		let (mut all_controls, targets, matrix) = match self {
+			Gate::Controlled {
+				controls,
+				targets,
+				matrix,
+			} => (controls.clone(), targets.clone(), matrix.clone()),
+			Gate::MultiControlled {
+				controls,
+				target,
+				matrix,
+			} => (
+				controls.iter().map(|&q| (q, ControlValue::One)).collect(),
+				vec![*target],
+				matrix.to_vec(),
+			),
+			gate => (Vec::new(), gate.qubits(), gate.matrix()),
+		};
+		for &(q, value) in controls {
+			assert!(
+				!targets.contains(&q) && all_controls.iter().all(|&(c, _)| c != q),
+				"control qubit {} is already used by the gate",
+				q
+			);
+			all_controls.push((q, value));
+		}
+		Gate::Controlled {
+			controls: all_controls,
+			targets,
+			matrix,
+		}
+	}
+
+	/// Get the 2^k×2^k matrix (row-major) of this gate on its k qubits, in the
+	/// order of `qubits()` with the first one as the least significant bit.
+	pub fn matrix(&self) -> Vec<Complex<f64>> {
+		let qubits = self.qubits();
+		let local = self.map_qubits(|q| qubits.iter().position(|&p| p == q).unwrap() as u32);
+		let dim = 1usize << qubits.len();
+		let mut matrix = vec![Complex::new(0., 0.); dim * dim];
+		let mut state = StateVec::new(qubits.len());
+		for j in 0..dim {
+			state.set_computational_basis(j);
+			local.update_quantum_state(state.as_mut());
+			for (i, amplitude) in state.as_ref().iter().enumerate() {
+				matrix[i * dim + j] = *amplitude;
+			}
+		}
+		matrix
+	}
+
+	/// Get the adjoint of this gate, which is its inverse except for the
+	/// projections `P0` and `P1`.
+	///
+	/// ```
+	/// # use qurs::circuit::Gate;
+	/// assert_eq!(Gate::S(0).dagger(), Gate::Sdag(0));
+	/// assert_eq!(Gate::Rx(1, 0.5).dagger(), Gate::Rx(1, -0.5));
+	/// ```
+	pub fn dagger(&self) -> Gate {
+		use Gate::*;
+		match self {
+			S(q) => Sdag(*q),
+			Sdag(q) => S(*q),
+			T(q) => Tdag(*q),
+			Tdag(q) => T(*q),
+			SqrtX(q) => SqrtXdag(*q),
+			SqrtXdag(q) => SqrtX(*q),
+			SqrtY(q) => SqrtYdag(*q),
+			SqrtYdag(q) => SqrtY(*q),
+			Rx(q, angle) => Rx(*q, -angle),
+			Ry(q, angle) => Ry(*q, -angle),
+			Rz(q, angle) => Rz(*q, -angle),
+			Rxx(a, b, angle) => Rxx(*a, *b, -angle),
+			Ryy(a, b, angle) => Ryy(*a, *b, -angle),
+			Rzz(a, b, angle) => Rzz(*a, *b, -angle),
+			ISwap(a, b) => FSim(*a, *b, FRAC_PI_2, 0.),
+			SqrtISwap(a, b) => FSim(*a, *b, FRAC_PI_4, 0.),
+			FSim(a, b, theta, phi) => FSim(*a, *b, -theta, -phi),
+			Givens(a, b, angle) => Givens(*a, *b, -angle),
+			Phase(q, lambda) => Phase(*q, -lambda),
+			// U(θ, φ, λ)^† = U(-θ, -λ, -φ)
+			U2(q, phi, lambda) => U3(*q, -FRAC_PI_2, -lambda, -phi),
+			U3(q, theta, phi, lambda) => U3(*q, -theta, -lambda, -phi),
+			Cp(a, b, lambda) => Cp(*a, *b, -lambda),
+			MultiControlledPhase {
+				controls,
+				target,
+				angle,
+			} => MultiControlledPhase {
+				controls: controls.clone(),
+				target: *target,
+				angle: -angle,
+			},
+			PauliRotation {
+				targets,
+				paulis,
+				angle,
+			} => PauliRotation {
+				targets: targets.clone(),
+				paulis: paulis.clone(),
+				angle: -angle,
+			},
+			MultiControlled {
+				controls,
+				target,
+				matrix,
+			} => MultiControlled {
+				controls: controls.clone(),
+				target: *target,
+				matrix: [
+					matrix[0].conj(),
+					matrix[2].conj(),
+					matrix[1].conj(),
+					matrix[3].conj(),
+				],
+			},
+			DenseMatrix { targets, matrix } => DenseMatrix {
+				targets: targets.clone(),
+				matrix: conjugate_transpose(matrix),
+			},
+			Controlled {
+				controls,
+				targets,
+				matrix,
+			} => Controlled {
+				controls: controls.clone(),
+				targets: targets.clone(),
+				matrix: conjugate_transpose(matrix),
+			},
+			// Hermitian gates
+			X(_) | Y(_) | Z(_) | H(_) | P0(_) | P1(_) | Cz(..) | Cnot(..) | Swap(..) => {
+				self.clone()
+			}
+			Ccnot(..) | Ccz(..) | Ecr(..) => self.clone(),
+		}
+	}
+
+	/// Get this gate raised to the power `exponent`, or `None` if no power is
+	/// defined for a non-integer `exponent`.
+	///
+	/// Rotations and phases multiply their angle, so that e.g. `Rx(θ)^k` is
+	/// `Rx(kθ)`. Other single-qubit gates and single-target controlled gates
+	/// take the principal branch of the eigenvalues of their matrix, so that
+	/// `X^0.5` is `SqrtX`. Remaining gates only have integer powers.
+	/// Projections are their own positive powers and have the identity as
+	/// their 0th power, but no negative powers.
+	///
+	/// ```
+	/// # use qurs::circuit::{equivalent, Circuit, Gate};
+	/// let mut s = Circuit::new(1);
+	/// s.add_gate(Gate::S(0));
+	/// let mut sqrt_z = Circuit::new(1);
+	/// sqrt_z.add_gate(Gate::Z(0).pow(0.5).unwrap());
+	/// assert!(equivalent(&s, &sqrt_z, false));
+	/// assert!(Gate::Swap(0, 1).pow(0.5).is_none());
+	/// ```
+	pub fn pow(&self, exponent: f64) -> Option<Gate> {
+		use Gate::*;
+		let k = exponent;
+		let gate = match self {
+			Rx(q, angle) => Rx(*q, k * angle),
+			Ry(q, angle) => Ry(*q, k * angle),
+			Rz(q, angle) => Rz(*q, k * angle),
+			Rxx(a, b, angle) => Rxx(*a, *b, k * angle),
+			Ryy(a, b, angle) => Ryy(*a, *b, k * angle),
+			Rzz(a, b, angle) => Rzz(*a, *b, k * angle),
+			Givens(a, b, angle) => Givens(*a, *b, k * angle),
+			ISwap(a, b) => FSim(*a, *b, -k * FRAC_PI_2, 0.),
+			SqrtISwap(a, b) => FSim(*a, *b, -k * FRAC_PI_4, 0.),
+			FSim(a, b, theta, phi) => FSim(*a, *b, k * theta, k * phi),
+			PauliRotation {
+				targets,
+				paulis,
+				angle,
+			} => PauliRotation {
+				targets: targets.clone(),
+				paulis: paulis.clone(),
+				angle: k * angle,
+			},
+			Phase(q, lambda) => Phase(*q, k * lambda),
+			Z(q) => Phase(*q, k * PI),
+			S(q) => Phase(*q, k * FRAC_PI_2),
+			Sdag(q) => Phase(*q, -k * FRAC_PI_2),
+			T(q) => Phase(*q, k * FRAC_PI_4),
+			Tdag(q) => Phase(*q, -k * FRAC_PI_4),
+			Cp(a, b, lambda) => Cp(*a, *b, k * lambda),
+			Cz(a, b) => Cp(*a, *b, k * PI),
+			Ccz(a, b, c) => MultiControlledPhase {
+				controls: vec![*a, *b],
+				target: *c,
+				angle: k * PI,
+			},
+			MultiControlledPhase {
+				controls,
+				target,
+				angle,
+			} => MultiControlledPhase {
+				controls: controls.clone(),
+				target: *target,
+				angle: k * angle,
+			},
+			// projections are idempotent
+			P0(_) | P1(_) if k > 0. => self.clone(),
+			P0(q) | P1(q) if k == 0. => DenseMatrix {
+				targets: vec![*q],
+				matrix: vec![
+					Complex::new(1., 0.),
+					Complex::new(0., 0.),
+					Complex::new(0., 0.),
+					Complex::new(1., 0.),
+				],
+			},
+			P0(_) | P1(_) => return None,
+			gate => match gate.qubits()[..] {
+				[q] => {
+					let m = gate.matrix();
+					DenseMatrix {
+						targets: vec![q],
+						matrix: pow2(&[m[0], m[1], m[2], m[3]], k).to_vec(),
+					}
+				}
+				_ => match gate {
+					Cnot(c, t) => X(*t).pow(k)?.controlled(&[(*c, ControlValue::One)]),
+					Ccnot(c1, c2, t) => X(*t)
+						.pow(k)?
+						.controlled(&[(*c1, ControlValue::One), (*c2, ControlValue::One)]),
+					MultiControlled {
+						controls,
+						target,
+						matrix,
+					} => MultiControlled {
+						controls: controls.clone(),
+						target: *target,
+						matrix: pow2(matrix, k),
+					},
+					Controlled {
+						controls,
+						targets,
+						matrix,
+					} if targets.len() == 1 => Controlled {
+						controls: controls.clone(),
+						targets: targets.clone(),
+						matrix: pow2(&[matrix[0], matrix[1], matrix[2], matrix[3]], k).to_vec(),
+					},
+					gate if k.fract() == 0. => integer_power(gate, k as i64),
+					_ => return None,
+				},
+			},
+		};
+		Some(gate)
+	}
+
+	/// Apply this gate to the quantum state.
+	///
+	/// # Panics
+	/// Panics if this is a `Gate::PauliRotation` with invalid lists as in
+	/// `Circuit::add_gate`, or with a target outside of the state.
+	pub fn update_quantum_state(&self, state: &mut [Complex<f64>]) {
+		use Gate::*;
+		match self {
+			X(q) => gate::x_gate(*q, state),
+			Y(q) => gate::y_gate(*q, state),
+			Z(q) => gate::z_gate(*q, state),
+			H(q) => gate::h_gate(*q, state),
+			P0(q) => gate::p0_gate(*q, state),
+			P1(q) => gate::p1_gate(*q, state),
+			S(q) => gate::s_gate(*q, state),
+			Sdag(q) => gate::sdag_gate(*q, state),
+			T(q) => gate::t_gate(*q, state),
+			Tdag(q) => gate::tdag_gate(*q, state),
+			SqrtX(q) => gate::sqrtx_gate(*q, state),
+			SqrtXdag(q) => gate::sqrtxdag_gate(*q, state),
+			SqrtY(q) => gate::sqrty_gate(*q, state),
+			SqrtYdag(q) => gate::sqrtydag_gate(*q, state),
+			Cz(c, t) => gate::cz_gate(*c, *t, state),
+			Cnot(c, t) => gate::cnot_gate(*c, *t, state),
+			Swap(a, b) => gate::swap_gate(*a, *b, state),
+			Ccnot(c1, c2, t) => gate::ccnot_gate(*c1, *c2, *t, state),
+			Ccz(c1, c2, t) => gate::ccz_gate(*c1, *c2, *t, state),
+			Rx(q, angle) => gate::rx_gate(*q, *angle, state),
+			Ry(q, angle) => gate::ry_gate(*q, *angle, state),
+			Rz(q, angle) => gate::rz_gate(*q, *angle, state),
+			Rxx(a, b, angle) => gate::rxx_gate(*a, *b, *angle, state),
+			Ryy(a, b, angle) => gate::ryy_gate(*a, *b, *angle, state),
+			Rzz(a, b, angle) => gate::rzz_gate(*a, *b, *angle, state),
+			ISwap(a, b) => gate::iswap_gate(*a, *b, state),
+			SqrtISwap(a, b) => gate::sqrt_iswap_gate(*a, *b, state),
+			FSim(a, b, theta, phi) => gate::fsim_gate(*a, *b, *theta, *phi, state),
+			Ecr(a, b) => gate::ecr_gate(*a, *b, state),
+			Givens(a, b, angle) => gate::givens_gate(*a, *b, *angle, state),
+			Phase(q, lambda) => gate::phase_gate(*q, *lambda, state),
+			U2(q, phi, lambda) => gate::u2_gate(*q, *phi, *lambda, state),
+			U3(q, theta, phi, lambda) => gate::u3_gate(*q, *theta, *phi, *lambda, state),
+			Cp(a, b, lambda) => gate::cp_gate(*a, *b, *lambda, state),
+			MultiControlledPhase {
+				controls,
+				target,
+				angle,
+			} => gate::multi_control_phase_gate(controls, *target, *angle, state),
+			MultiControlled {
+				controls,
+				target,
+				matrix,
+			} => gate::multi_control_u_gate(controls, *target, matrix, state),
+			DenseMatrix { targets, matrix } => gate::dense_matrix_gate(targets, matrix, state),
+			Controlled {
+				controls,
+				targets,
+				matrix,
+			} => gate::multi_control_dense_matrix_gate(controls, targets, matrix, state),
+			PauliRotation {
+				targets,
+				paulis,
+				angle,
+			} => {
+				crate::assert_valid_pauli_list(
+					targets,
+					paulis,
+					state.len().trailing_zeros() as usize,
+				);
+				unsafe {
+					qulacs::multi_qubit_Pauli_rotation_gate_partial_list(
+						targets.as_ptr(),
+						paulis.as_ptr(),
+						targets.len() as u32,
+						-angle,
+						state.as_mut_ptr() as *mut CTYPE,
+						state.len() as u64,
+					)
+				}
+			}
+		}
+	}
+}
+
+/// Conjugate transpose of a square matrix in row-major order.
+fn conjugate_transpose(matrix: &[Complex<f64>]) -> Vec<Complex<f64>> {
+	let dim = (matrix.len() as f64).sqrt() as usize;
+	(0..matrix.len())
+		.map(|k| matrix[(k % dim) * dim + k / dim].conj())
+		.collect()
+}
+
+/// `gate^k` for an integer k by repeated squaring of its matrix, as a
+/// `Gate::DenseMatrix` or, for controlled gates, a `Gate::Controlled`.
+fn integer_power(gate: &Gate, k: i64) -> Gate {
+	let (controls, targets, base) = match gate {
+		Gate::Controlled {
+			controls,
+			targets,
+			matrix,
+		} => (controls.clone(), targets.clone(), matrix.clone()),
+		gate => (Vec::new(), gate.qubits(), gate.matrix()),
+	};
+	let base = if k < 0 {
+		conjugate_transpose(&base)
+	} else {
+		base
+	};
+	let dim = 1usize << targets.len();
+	let multiply = |a: &[Complex<f64>], b: &[Complex<f64>]| -> Vec<Complex<f64>> {
+		(0..dim * dim)
+			.map(|n| {
+				let (i, j) = (n / dim, n % dim);
+				(0..dim).map(|l| a[i * dim + l] * b[l * dim + j]).sum()
+			})
+			.collect()
+	};
+	let mut result: Vec<Complex<f64>> = (0..dim * dim)
+		.map(|n| {
+			if n % (dim + 1) == 0 {
+				Complex::new(1., 0.)
+			} else {
+				Complex::new(0., 0.)
+			}
+		})
+		.collect();
+	let mut square = base;
+	let mut remaining = k.unsigned_abs();
+	while remaining > 0 {
+		if remaining & 1 == 1 {
+			result = multiply(&result, &square);
+		}
+		square = multiply(&square, &square);
+		remaining >>= 1;
+	}
+	if controls.is_empty() {
+		Gate::DenseMatrix {
+			targets,
+			matrix: result,
+		}
+	} else {
+		Gate::Controlled {
+			controls,
+			targets,
+			matrix: result,
+		}
+	}
+}
+
+/// A sequence of gates acting on a fixed number of qubits.
+///
+/// ```
+/// # use qurs::circuit::{Circuit, Gate};
+/// # use qurs::prelude::*;
+/// # use qurs::StateVec;
+/// let mut circuit = Circuit::new(2);
+/// circuit.add_gate(Gate::H(0));
+/// circuit.add_gate(Gate::Cnot(0, 1));
+/// let mut state = StateVec::new(2);
+/// circuit.update_quantum_state(state.as_mut()).unwrap();
+/// assert!((state.get_zero_probability(1).unwrap() - 0.5).abs() < 1e-10);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Circuit {
+	qubit_count: usize,
+	gates: Vec<Gate>,
+}
+
+impl Circuit {
+	pub fn new(qubit_count: usize) -> Self {
+		Self {
+			qubit_count,
+			gates: Vec::new(),
+		}
+	}
+
+	///Get qubit count
+	pub fn qubit_count(&self) -> usize {
+		self.qubit_count
+	}
+
+	///Get gates in the order they are applied
+	pub fn gates(&self) -> &[Gate] {
+		&self.gates
+	}
+
+	///Get gate count
+	pub fn gate_count(&self) -> usize {
+		self.gates.len()
+	}
+
+	/// Append a gate at the end of this circuit.
+	///
+	/// # Panics
+	/// Panics if the gate acts on a qubit outside of this circuit, or if it is
+	/// a `Gate::PauliRotation` whose lists differ in length, repeat a target
+	/// or contain a Pauli type outside 0..=3.
+	pub fn add_gate(&mut self, gate: Gate) {
+		if let Gate::PauliRotation {
+			targets, paulis, ..
+		} = &gate
+		{
+			if let Err(e) = PauliString::from_type_list(targets, paulis) {
+				panic!("{}", e);
+			}
+		}
+		for q in gate.qubits() {
+			assert!(
+				(q as usize) < self.qubit_count,
+				"qubit index {} is out of range for a circuit of {} qubits",
+				q,
+				self.qubit_count
+			);
+		}
+		self.gates.push(gate);
+	}
+
+	/// Get the circuit undoing this one, i.e. the adjoint of each gate in
+	/// reverse order.
+	///
+	/// ```
+	/// # use qurs::circuit::{Circuit, Gate};
+	/// let mut circuit = Circuit::new(2);
+	/// circuit.add_gate(Gate::T(0));
+	/// circuit.add_gate(Gate::Cnot(0, 1));
+	/// let inverse = circuit.inverse();
+	/// assert_eq!(inverse.gates(), &[Gate::Cnot(0, 1), Gate::Tdag(0)]);
+	/// ```
+	pub fn inverse(&self) -> Circuit {
+		Circuit {
+			qubit_count: self.qubit_count,
+			gates: self.gates.iter().rev().map(Gate::dagger).collect(),
+		}
+	}
+
+	/// Append all gates of `other` at the end of this circuit.
+	///
+	/// # Panics
+	/// Panics if `other` has more qubits than this circuit.
+	pub fn merge_circuit(&mut self, other: &Circuit) {
+		assert!(
+			other.qubit_count <= self.qubit_count,
+			"cannot merge a circuit of {} qubits into a circuit of {} qubits",
+			other.qubit_count,
+			self.qubit_count
+		);
+		self.gates.extend(other.gates.iter().cloned());
+	}
+
+	/// Get the number of layers when each gate is placed as early as possible.
+	pub fn depth(&self) -> usize {
+		let mut layers = vec![0; self.qubit_count];
+		for gate in &self.gates {
+			let qubits = gate.qubits();
+			let layer = 1 + qubits
+				.iter()
+				.map(|&q| layers[q as usize])
+				.max()
+				.unwrap_or(0);
+			for q in qubits {
+				layers[q as usize] = layer;
+			}
+		}
+		layers.into_iter().max().unwrap_or(0)
+	}
+
+	/// Apply all gates of this circuit to the quantum state in order.
+	pub fn update_quantum_state(&self, state: &mut [Complex<f64>]) -> Result<(), StateErr> {
+		let dim = 1usize << self.qubit_count;
+		if state.len() != dim {
+			return Err(StateErr::InconsistentStateLength(dim, state.len()));
+		}
+		for gate in &self.gates {
+			gate.update_quantum_state(state);
+		}
+		Ok(())
+	}
+
+	/// Get the 2^n×2^n matrix of this circuit in row-major order, built by
+	/// applying the gates to each computational basis state. Column `j` is
+	/// the result for the basis state `j`.
+	pub fn to_unitary(&self) -> Vec<Complex<f64>> {
+		let dim = 1usize << self.qubit_count;
+		let mut matrix = vec![Complex::new(0., 0.); dim * dim];
+		let mut state = StateVec::new(self.qubit_count);
+		for j in 0..dim {
+			state.set_computational_basis(j);
+			for gate in &self.gates {
+				gate.update_quantum_state(state.as_mut());
+			}
+			for (i, amplitude) in state.as_ref().iter().enumerate() {
+				matrix[i * dim + j] = *amplitude;
+			}
+		}
+		matrix
+	}
+}
+
+/// Check whether two circuits implement the same operator.
+///
+/// Circuits of up to 10 qubits are compared by their full matrices. Larger
+/// circuits are applied to a few Haar random states and compared through
+/// `inner_product`, which detects any difference with probability 1.
+/// * `up_to_global_phase` if `true`, circuits which differ only by a global
+///   phase are considered equivalent
+pub fn equivalent(a: &Circuit, b: &Circuit, up_to_global_phase: bool) -> bool {
+	if a.qubit_count != b.qubit_count {
+		return false;
+	}
+	if a.qubit_count <= EXACT_EQUIVALENCE_MAX_QUBITS {
+		let (ua, ub) = (a.to_unitary(), b.to_unitary());
+		let phase = if up_to_global_phase {
+			// The ratio at the largest element of `ua` fixes the global phase.
+			let (k, _) = ua
+				.iter()
+				.enumerate()
+				.max_by(|x, y| x.1.norm_sqr().total_cmp(&y.1.norm_sqr()))
+				.unwrap();
+			if ub[k].norm() < EQUIVALENCE_EPS {
+				return false;
+			}
+			ub[k] / ua[k]
+		} else {
+			Complex::new(1., 0.)
+		};
+		return ua
+			.iter()
+			.zip(&ub)
+			.all(|(x, y)| (x * phase - y).norm() < EQUIVALENCE_EPS);
+	}
+
+	let mut phase = None;
+	for seed in 0..RANDOM_STATE_COUNT {
+		let mut sa = StateVec::new(a.qubit_count);
+		sa.set_haar_random_state_with_seed(seed);
+		let mut sb = StateVec::new(b.qubit_count);
+		sb.as_mut().copy_from_slice(sa.as_ref());
+		for gate in &a.gates {
+			gate.update_quantum_state(sa.as_mut());
+		}
+		for gate in &b.gates {
+			gate.update_quantum_state(sb.as_mut());
+		}
+		let overlap = inner_product(sa.as_ref(), sb.as_ref()).unwrap();
+		if !up_to_global_phase {
+			if (overlap - 1.).norm() >= EQUIVALENCE_EPS {
+				return false;
+			}
+			continue;
+		}
+		if (overlap.norm() - 1.).abs() >= EQUIVALENCE_EPS {
+			return false;
+		}
+		// The phase must be the same for every state.
+		match phase {
+			None => phase = Some(overlap),
+			Some(p) if (p - overlap).norm() >= EQUIVALENCE_EPS => return false,
+			Some(_) => {}
+		}
+	}
+	true
+}
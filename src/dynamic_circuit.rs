@@ -0,0 +1,270 @@
+use crate::binding::{qulacs, CTYPE};
+use crate::circuit::{Circuit, Gate};
+use crate::gate;
+use crate::state::StateErr;
+use crate::Complex;
+use rand::Rng;
+
+/// Measurement outcomes with at most this probability are never sampled.
+const EPS: f64 = 1e-12;
+
+/// Condition for applying a gate in a `DynamicCircuit`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+	/// The classical bit `bit` equals `value`.
+	Bit { bit: usize, value: bool },
+	/// The whole classical register, read as an integer with bit 0 as the least
+	/// significant bit, equals the value, as in QASM `if (c==k)`. Only
+	/// registers of at most 64 bits can be compared.
+	Register(u64),
+}
+
+impl Condition {
+	fn is_satisfied(&self, register: &[bool]) -> bool {
+		match self {
+			Condition::Bit { bit, value } => register[*bit] == *value,
+			Condition::Register(value) => register_value(register) == *value,
+		}
+	}
+}
+
+/// An operation in a `DynamicCircuit`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Instruction {
+	Gate(Gate),
+	/// Measure `qubit` in the Z basis and store the outcome in `bit`.
+	Measure {
+		qubit: u32,
+		bit: usize,
+	},
+	/// Reinitialize `qubit` to |0>.
+	Reset(u32),
+	/// Apply `gate` only if `condition` holds when it is reached.
+	Conditional {
+		condition: Condition,
+		gate: Gate,
+	},
+}
+
+/// A circuit with a classical register which supports mid-circuit
+/// measurement, reset and gates conditioned on measured values (feed-forward).
+///
+/// ```
+/// # use qurs::circuit::Gate;
+/// # use qurs::dynamic_circuit::{Condition, DynamicCircuit};
+/// # use qurs::StateVec;
+/// // Measure qubit 0 after X and flip qubit 1 if the outcome is 1.
+/// let mut circuit = DynamicCircuit::new(2, 1);
+/// circuit.add_gate(Gate::X(0));
+/// circuit.add_measure(0, 0);
+/// circuit.add_conditional_gate(Condition::Bit { bit: 0, value: true }, Gate::X(1));
+/// let mut state = StateVec::new(2);
+/// let register = circuit.update_quantum_state(state.as_mut()).unwrap();
+/// assert_eq!(register, [true]);
+/// assert_eq!(state.as_ref()[0b11].re, 1.);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DynamicCircuit {
+	qubit_count: usize,
+	bit_count: usize,
+	instructions: Vec<Instruction>,
+}
+
+impl DynamicCircuit {
+	/// Create a circuit with `qubit_count` qubits and a classical register of
+	/// `bit_count` bits initialized to 0.
+	pub fn new(qubit_count: usize, bit_count: usize) -> Self {
+		Self {
+			qubit_count,
+			bit_count,
+			instructions: Vec::new(),
+		}
+	}
+
+	///Get qubit count
+	pub fn qubit_count(&self) -> usize {
+		self.qubit_count
+	}
+
+	///Get the size of the classical register
+	pub fn bit_count(&self) -> usize {
+		self.bit_count
+	}
+
+	///Get instructions in the order they are applied
+	pub fn instructions(&self) -> &[Instruction] {
+		&self.instructions
+	}
+
+	/// Append a gate.
+	///
+	/// # Panics
+	/// Panics if the gate acts on a qubit outside of this circuit.
+	pub fn add_gate(&mut self, gate: Gate) {
+		self.check_gate(&gate);
+		self.instructions.push(Instruction::Gate(gate));
+	}
+
+	/// Append all gates of `circuit`.
+	///
+	/// # Panics
+	/// Panics if `circuit` has more qubits than this circuit.
+	pub fn merge_circuit(&mut self, circuit: &Circuit) {
+		assert!(
+			circuit.qubit_count() <= self.qubit_count,
+			"cannot merge a circuit of {} qubits into a circuit of {} qubits",
+			circuit.qubit_count(),
+			self.qubit_count
+		);
+		self.instructions
+			.extend(circuit.gates().iter().cloned().map(Instruction::Gate));
+	}
+
+	/// Append a Z-basis measurement of `qubit` whose outcome is stored in
+	/// `bit`.
+	///
+	/// # Panics
+	/// Panics if `qubit` or `bit` is out of range.
+	pub fn add_measure(&mut self, qubit: u32, bit: usize) {
+		self.check_qubit(qubit);
+		self.check_bit(bit);
+		self.instructions.push(Instruction::Measure { qubit, bit });
+	}
+
+	/// Append a reset of `qubit` to |0>.
+	///
+	/// # Panics
+	/// Panics if `qubit` is out of range.
+	pub fn add_reset(&mut self, qubit: u32) {
+		self.check_qubit(qubit);
+		self.instructions.push(Instruction::Reset(qubit));
+	}
+
+	/// Append `gate` applied only if `condition` holds.
+	///
+	/// # Panics
+	/// Panics if the gate or the condition refers to a qubit or bit out of
+	/// range, or if the condition is `Condition::Register` and the register
+	/// has more than 64 bits.
+	pub fn add_conditional_gate(&mut self, condition: Condition, gate: Gate) {
+		self.check_gate(&gate);
+		match condition {
+			Condition::Bit { bit, .. } => self.check_bit(bit),
+			Condition::Register(_) => assert!(
+				self.bit_count <= 64,
+				"a register of {} bits cannot be compared with a 64-bit value",
+				self.bit_count
+			),
+		}
+		self.instructions
+			.push(Instruction::Conditional { condition, gate });
+	}
+
+	/// Run this circuit on the quantum state, sampling measurement outcomes.
+	///
+	/// Returns the final classical register, where the element `i` is the bit
+	/// `i`.
+	pub fn update_quantum_state(&self, state: &mut [Complex<f64>]) -> Result<Vec<bool>, StateErr> {
+		self.update_quantum_state_with_rng(state, &mut rand::thread_rng())
+	}
+
+	/// Same as `update_quantum_state`, sampling measurement outcomes from
+	/// `rng` for reproducible runs.
+	pub fn update_quantum_state_with_rng<R: Rng>(
+		&self,
+		state: &mut [Complex<f64>],
+		rng: &mut R,
+	) -> Result<Vec<bool>, StateErr> {
+		let dim = 1usize << self.qubit_count;
+		if state.len() != dim {
+			return Err(StateErr::InconsistentStateLength(dim, state.len()));
+		}
+		let mut register = vec![false; self.bit_count];
+		for instruction in &self.instructions {
+			match instruction {
+				Instruction::Gate(gate) => gate.update_quantum_state(state),
+				Instruction::Measure { qubit, bit } => {
+					register[*bit] = measure(*qubit, state, rng);
+				}
+				Instruction::Reset(qubit) => {
+					if measure(*qubit, state, rng) {
+						gate::x_gate(*qubit, state);
+					}
+				}
+				Instruction::Conditional { condition, gate } => {
+					if condition.is_satisfied(&register) {
+						gate.update_quantum_state(state);
+					}
+				}
+			}
+		}
+		Ok(register)
+	}
+
+	fn check_qubit(&self, qubit: u32) {
+		assert!(
+			(qubit as usize) < self.qubit_count,
+			"qubit index {} is out of range for a circuit of {} qubits",
+			qubit,
+			self.qubit_count
+		);
+	}
+
+	fn check_bit(&self, bit: usize) {
+		assert!(
+			bit < self.bit_count,
+			"bit index {} is out of range for a register of {} bits",
+			bit,
+			self.bit_count
+		);
+	}
+
+	fn check_gate(&self, gate: &Gate) {
+		for q in gate.qubits() {
+			self.check_qubit(q);
+		}
+	}
+}
+
+/// Read the classical register as an integer with bit 0 as the least
+/// significant bit.
+///
+/// # Panics
+/// Panics if the register has more than 64 bits.
+pub fn register_value(register: &[bool]) -> u64 {
+	assert!(
+		register.len() <= 64,
+		"a register of {} bits does not fit in 64 bits",
+		register.len()
+	);
+	register
+		.iter()
+		.enumerate()
+		.fold(0, |acc, (i, &b)| acc | ((b as u64) << i))
+}
+
+/// Measure `qubit` in the Z basis, collapse and renormalize the state.
+///
+/// Returns `true` if the outcome is 1. An outcome whose probability is at most
+/// `EPS` is never chosen, since the state cannot be renormalized after it.
+fn measure<R: Rng>(qubit: u32, state: &mut [Complex<f64>], rng: &mut R) -> bool {
+	let dim = state.len() as u64;
+	let zero_probability =
+		unsafe { qulacs::M0_prob(qubit, state.as_ptr() as *const CTYPE, dim) }.clamp(0., 1.);
+	let outcome = if zero_probability <= EPS {
+		true
+	} else if 1. - zero_probability <= EPS {
+		false
+	} else {
+		rng.gen::<f64>() >= zero_probability
+	};
+	let probability = if outcome {
+		gate::p1_gate(qubit, state);
+		1. - zero_probability
+	} else {
+		gate::p0_gate(qubit, state);
+		zero_probability
+	};
+	unsafe { qulacs::normalize(probability, state.as_mut_ptr() as *mut CTYPE, dim) };
+	outcome
+}
@@ -0,0 +1,385 @@
+use crate::observable::Observable;
+use crate::pauli::{Pauli, PauliString};
+use crate::Complex;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// Tolerance for dropping cancelled terms.
+const EPS: f64 = 1e-12;
+
+/// A creation or annihilation operator of a fermionic mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LadderOperator {
+	/// Creation operator a†_p
+	Create(usize),
+	/// Annihilation operator a_p
+	Annihilate(usize),
+}
+
+impl LadderOperator {
+	///Get the mode the operator acts on
+	pub fn mode(self) -> usize {
+		match self {
+			LadderOperator::Create(p) | LadderOperator::Annihilate(p) => p,
+		}
+	}
+
+	///Get the Hermitian conjugate
+	pub fn dagger(self) -> Self {
+		match self {
+			LadderOperator::Create(p) => LadderOperator::Annihilate(p),
+			LadderOperator::Annihilate(p) => LadderOperator::Create(p),
+		}
+	}
+
+	/// Sort key of the normal order: creation operators first, each group in
+	/// descending order of modes.
+	fn order_key(self) -> (u8, Reverse<usize>) {
+		match self {
+			LadderOperator::Create(p) => (0, Reverse(p)),
+			LadderOperator::Annihilate(p) => (1, Reverse(p)),
+		}
+	}
+}
+
+impl fmt::Display for LadderOperator {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			LadderOperator::Create(p) => write!(f, "{}^", p),
+			LadderOperator::Annihilate(p) => write!(f, "{}", p),
+		}
+	}
+}
+
+/// Encoding of fermionic modes into qubits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FermionEncoding {
+	/// Qubit j stores the occupation of mode j.
+	JordanWigner,
+	/// Qubit j stores the parity of modes 0..=j.
+	Parity,
+	/// Qubit j stores the parity of modes (j & (j + 1))..=j, i.e. the
+	/// Fenwick tree of occupations.
+	BravyiKitaev,
+}
+
+impl FermionEncoding {
+	/// Get the computational basis index on `qubit_count` qubits representing
+	/// the occupation number state whose bit j is the occupation of mode j.
+	pub fn basis_index(self, occupation: usize, qubit_count: usize) -> usize {
+		let parity = |low: usize, high: usize| {
+			let mask = (1usize << (high + 1)) - (1usize << low);
+			(occupation & mask).count_ones() as usize % 2
+		};
+		(0..qubit_count)
+			.map(|j| {
+				let bit = match self {
+					FermionEncoding::JordanWigner => (occupation >> j) & 1,
+					FermionEncoding::Parity => parity(0, j),
+					FermionEncoding::BravyiKitaev => parity(j & (j + 1), j),
+				};
+				bit << j
+			})
+			.sum()
+	}
+
+	/// Get the update set U, parity set P and remainder set R of mode `j` so
+	/// that the Majorana operators are c_j = X_U X_j Z_P and d_j = X_U Y_j Z_R
+	/// (Seeley, Richard and Love, 2012).
+	fn majorana_sets(self, j: usize, qubit_count: usize) -> (Vec<usize>, Vec<usize>, Vec<usize>) {
+		match self {
+			FermionEncoding::JordanWigner => (Vec::new(), (0..j).collect(), (0..j).collect()),
+			FermionEncoding::Parity => {
+				let parity = if j > 0 { vec![j - 1] } else { Vec::new() };
+				((j + 1..qubit_count).collect(), parity, Vec::new())
+			}
+			FermionEncoding::BravyiKitaev => {
+				let mut update = Vec::new();
+				let mut k = j | (j + 1);
+				while k < qubit_count {
+					update.push(k);
+					k |= k + 1;
+				}
+				// qubits whose sum is the parity of modes 0..j
+				let mut parity = Vec::new();
+				let mut k = j;
+				while k > 0 {
+					let q = k - 1;
+					parity.push(q);
+					k = q & (q + 1);
+				}
+				// qubits whose sum with qubit j is the occupation of mode j
+				let low = j & (j + 1);
+				let remainder = parity.iter().cloned().filter(|&k| k < low).collect();
+				(update, parity, remainder)
+			}
+		}
+	}
+
+	/// Get the Majorana operators (c_j, d_j) with a_j = (c_j + i d_j)/2.
+	fn majorana(self, j: usize, qubit_count: usize) -> (PauliString, PauliString) {
+		let (update, parity, remainder) = self.majorana_sets(j, qubit_count);
+		let string = |middle: Pauli, zs: &[usize]| {
+			let mut targets: Vec<u32> = update.iter().map(|&k| k as u32).collect();
+			let mut paulis = vec![Pauli::X; update.len()];
+			targets.push(j as u32);
+			paulis.push(middle);
+			targets.extend(zs.iter().map(|&k| k as u32));
+			paulis.extend(zs.iter().map(|_| Pauli::Z));
+			PauliString::new(&targets, &paulis).unwrap()
+		};
+		(string(Pauli::X, &parity), string(Pauli::Y, &remainder))
+	}
+}
+
+/// A linear combination of products of fermionic creation and annihilation
+/// operators with complex coefficients.
+///
+/// ```
+/// # use qurs::fermion::{FermionOperator, LadderOperator::*};
+/// # use qurs::Complex;
+/// let one = Complex::new(1., 0.);
+/// let op = FermionOperator::term(one, &[Annihilate(0), Create(0)]);
+/// // a_0 a†_0 = 1 - a†_0 a_0
+/// let ordered = op.normal_ordered();
+/// assert_eq!(ordered.terms()[0], (one, vec![]));
+/// assert_eq!(ordered.terms()[1], (-one, vec![Create(0), Annihilate(0)]));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FermionOperator {
+	terms: Vec<(Complex<f64>, Vec<LadderOperator>)>,
+}
+
+impl FermionOperator {
+	/// Create the zero operator.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Create `coefficient` times the identity.
+	pub fn identity(coefficient: Complex<f64>) -> Self {
+		Self::term(coefficient, &[])
+	}
+
+	/// Create `coefficient` times the product of `operators`, the leftmost
+	/// acting last.
+	pub fn term(coefficient: Complex<f64>, operators: &[LadderOperator]) -> Self {
+		Self {
+			terms: vec![(coefficient, operators.to_vec())],
+		}
+	}
+
+	/// Add `coefficient` times the product of `operators`.
+	pub fn add_term(&mut self, coefficient: Complex<f64>, operators: &[LadderOperator]) {
+		self.terms.push((coefficient, operators.to_vec()));
+	}
+
+	///Get terms as (coefficient, product of ladder operators)
+	pub fn terms(&self) -> &[(Complex<f64>, Vec<LadderOperator>)] {
+		&self.terms
+	}
+
+	/// Get the number of modes, i.e. one more than the largest mode index, or 0
+	/// for a constant.
+	pub fn mode_count(&self) -> usize {
+		self.terms
+			.iter()
+			.flat_map(|(_, ops)| ops.iter().map(|o| o.mode() + 1))
+			.max()
+			.unwrap_or(0)
+	}
+
+	/// Get the Hermitian conjugate.
+	pub fn dagger(&self) -> Self {
+		Self {
+			terms: self
+				.terms
+				.iter()
+				.map(|(c, ops)| (c.conj(), ops.iter().rev().map(|o| o.dagger()).collect()))
+				.collect(),
+		}
+	}
+
+	/// Merge identical products and remove terms whose coefficient has
+	/// absolute value at most `tolerance`. Products are compared as written, so
+	/// call `normal_ordered` to merge products equal by anticommutation.
+	pub fn simplify(&mut self, tolerance: f64) {
+		let mut index: HashMap<Vec<LadderOperator>, usize> = HashMap::new();
+		let mut terms: Vec<(Complex<f64>, Vec<LadderOperator>)> = Vec::new();
+		for (c, ops) in self.terms.drain(..) {
+			match index.get(&ops) {
+				Some(&i) => terms[i].0 += c,
+				None => {
+					index.insert(ops.clone(), terms.len());
+					terms.push((c, ops));
+				}
+			}
+		}
+		terms.retain(|(c, _)| c.norm() > tolerance);
+		self.terms = terms;
+	}
+
+	/// Get the simplified normal-ordered form, in which creation operators
+	/// precede annihilation operators and each group is in descending order of
+	/// modes.
+	pub fn normal_ordered(&self) -> Self {
+		let mut terms = Vec::new();
+		for (c, ops) in &self.terms {
+			normal_order_term(*c, ops.clone(), &mut terms);
+		}
+		let mut ordered = Self { terms };
+		ordered.simplify(EPS);
+		ordered
+	}
+
+	/// Check that this operator is Hermitian up to `tolerance` in the
+	/// coefficients of the normal-ordered form.
+	pub fn is_hermitian(&self, tolerance: f64) -> bool {
+		let mut difference = (self.clone() - self.dagger()).normal_ordered();
+		difference.simplify(tolerance);
+		difference.terms.is_empty()
+	}
+
+	/// Map this operator to a Pauli-sum observable on `qubit_count` qubits.
+	///
+	/// # Panics
+	/// Panics if the operator acts on a mode not less than `qubit_count`.
+	pub fn encode(&self, encoding: FermionEncoding, qubit_count: usize) -> Observable {
+		assert!(
+			self.mode_count() <= qubit_count,
+			"{} modes cannot be encoded into {} qubits",
+			self.mode_count(),
+			qubit_count
+		);
+		let half = Complex::new(0.5, 0.);
+		let ladders: Vec<(Observable, Observable)> = (0..qubit_count)
+			.map(|j| {
+				let (c, d) = encoding.majorana(j, qubit_count);
+				let mut create = Observable::new(qubit_count);
+				create.add_term(half, c.clone());
+				create.add_term(-half * Complex::i(), d.clone());
+				let mut annihilate = Observable::new(qubit_count);
+				annihilate.add_term(half, c);
+				annihilate.add_term(half * Complex::i(), d);
+				(create, annihilate)
+			})
+			.collect();
+
+		let mut observable = Observable::new(qubit_count);
+		for (c, ops) in &self.terms {
+			let mut product = Observable::identity(qubit_count, *c);
+			for op in ops {
+				let (create, annihilate) = &ladders[op.mode()];
+				let factor = match op {
+					LadderOperator::Create(_) => create,
+					LadderOperator::Annihilate(_) => annihilate,
+				};
+				product = product * factor.clone();
+				product.simplify(EPS);
+			}
+			observable = observable + product;
+		}
+		observable.simplify(EPS);
+		observable
+	}
+}
+
+/// Bring `operators` into the normal order by insertion sort, pushing the
+/// resulting terms including the contractions a_p a†_p = 1 - a†_p a_p.
+fn normal_order_term(
+	coefficient: Complex<f64>,
+	mut operators: Vec<LadderOperator>,
+	terms: &mut Vec<(Complex<f64>, Vec<LadderOperator>)>,
+) {
+	let mut coefficient = coefficient;
+	for i in 1..operators.len() {
+		for j in (1..=i).rev() {
+			let (left, right) = (operators[j - 1], operators[j]);
+			if left.order_key() <= right.order_key() {
+				break;
+			}
+			if let (LadderOperator::Annihilate(p), LadderOperator::Create(q)) = (left, right) {
+				if p == q {
+					let mut contracted = operators[..j - 1].to_vec();
+					contracted.extend_from_slice(&operators[j + 1..]);
+					normal_order_term(coefficient, contracted, terms);
+				}
+			}
+			operators.swap(j - 1, j);
+			coefficient = -coefficient;
+		}
+	}
+	// a_p a_p = a†_p a†_p = 0
+	if operators.windows(2).all(|w| w[0] != w[1]) {
+		terms.push((coefficient, operators));
+	}
+}
+
+impl Add for FermionOperator {
+	type Output = FermionOperator;
+
+	fn add(mut self, other: FermionOperator) -> FermionOperator {
+		self.terms.extend(other.terms);
+		self
+	}
+}
+
+impl Sub for FermionOperator {
+	type Output = FermionOperator;
+
+	fn sub(self, other: FermionOperator) -> FermionOperator {
+		self + -other
+	}
+}
+
+impl Neg for FermionOperator {
+	type Output = FermionOperator;
+
+	fn neg(self) -> FermionOperator {
+		self * Complex::new(-1., 0.)
+	}
+}
+
+impl Mul<Complex<f64>> for FermionOperator {
+	type Output = FermionOperator;
+
+	fn mul(mut self, coefficient: Complex<f64>) -> FermionOperator {
+		for (c, _) in &mut self.terms {
+			*c *= coefficient;
+		}
+		self
+	}
+}
+
+impl Mul for FermionOperator {
+	type Output = FermionOperator;
+
+	/// Get the operator product. The result is not normal ordered.
+	fn mul(self, other: FermionOperator) -> FermionOperator {
+		let mut terms = Vec::with_capacity(self.terms.len() * other.terms.len());
+		for (a, p) in &self.terms {
+			for (b, q) in &other.terms {
+				terms.push((a * b, p.iter().chain(q).cloned().collect()));
+			}
+		}
+		FermionOperator { terms }
+	}
+}
+
+impl fmt::Display for FermionOperator {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.terms.is_empty() {
+			return write!(f, "0");
+		}
+		for (i, (c, ops)) in self.terms.iter().enumerate() {
+			if i > 0 {
+				write!(f, " + ")?;
+			}
+			let ops: Vec<String> = ops.iter().map(|o| o.to_string()).collect();
+			write!(f, "({}) [{}]", c, ops.join(" "))?;
+		}
+		Ok(())
+	}
+}
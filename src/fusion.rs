@@ -0,0 +1,109 @@
+use crate::circuit::{Circuit, Gate};
+use crate::state::StateErr;
+use crate::Complex;
+
+/// A circuit whose gates are grouped into blocks acting on at most
+/// `max_block_qubits` qubits, each applied as one dense matrix.
+///
+/// Every gate call is a full sweep over the state vector, so for large states
+/// the cost is dominated by memory bandwidth. Applying one 2^k×2^k matrix in a
+/// single sweep instead of many small gates reduces the number of sweeps at
+/// the price of more arithmetic per amplitude, which pays off for k of about 2
+/// to 5.
+///
+/// ```
+/// # use qurs::circuit::{Circuit, Gate};
+/// # use qurs::fusion::FusedCircuit;
+/// # use qurs::StateVec;
+/// let mut circuit = Circuit::new(3);
+/// circuit.add_gate(Gate::H(0));
+/// circuit.add_gate(Gate::Cnot(0, 1));
+/// circuit.add_gate(Gate::Cnot(1, 2));
+/// let fused = FusedCircuit::new(&circuit, 3);
+/// assert_eq!(fused.block_count(), 1);
+/// let mut state = StateVec::new(3);
+/// fused.update_quantum_state(state.as_mut()).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct FusedCircuit {
+	qubit_count: usize,
+	blocks: Vec<Gate>,
+}
+
+impl FusedCircuit {
+	/// Group the gates of `circuit` greedily in order: a gate joins the current
+	/// block as long as the block then acts on at most `max_block_qubits`
+	/// qubits, otherwise it starts a new block. Gates acting on more qubits
+	/// than `max_block_qubits` are kept as they are.
+	pub fn new(circuit: &Circuit, max_block_qubits: usize) -> Self {
+		let mut blocks = Vec::new();
+		let mut qubits: Vec<u32> = Vec::new();
+		let mut gates: Vec<Gate> = Vec::new();
+		for gate in circuit.gates() {
+			let mut merged = qubits.clone();
+			for q in gate.qubits() {
+				if !merged.contains(&q) {
+					merged.push(q);
+				}
+			}
+			if merged.len() > max_block_qubits && !gates.is_empty() {
+				blocks.push(fuse_block(&qubits, &gates));
+				gates.clear();
+				merged = gate.qubits();
+			}
+			qubits = merged;
+			gates.push(gate.clone());
+		}
+		if !gates.is_empty() {
+			blocks.push(fuse_block(&qubits, &gates));
+		}
+		Self {
+			qubit_count: circuit.qubit_count(),
+			blocks,
+		}
+	}
+
+	///Get qubit count
+	pub fn qubit_count(&self) -> usize {
+		self.qubit_count
+	}
+
+	/// Get the fused blocks in the order they are applied.
+	pub fn blocks(&self) -> &[Gate] {
+		&self.blocks
+	}
+
+	/// Get the number of fused blocks, i.e. the number of sweeps over the
+	/// state vector.
+	pub fn block_count(&self) -> usize {
+		self.blocks.len()
+	}
+
+	/// Apply all blocks to the quantum state in order.
+	pub fn update_quantum_state(&self, state: &mut [Complex<f64>]) -> Result<(), StateErr> {
+		let dim = 1usize << self.qubit_count;
+		if state.len() != dim {
+			return Err(StateErr::InconsistentStateLength(dim, state.len()));
+		}
+		for block in &self.blocks {
+			block.update_quantum_state(state);
+		}
+		Ok(())
+	}
+}
+
+/// Build one gate equivalent to `gates` on `qubits`. A block of a single gate
+/// is kept as it is since its own kernel is cheaper than a dense matrix.
+fn fuse_block(qubits: &[u32], gates: &[Gate]) -> Gate {
+	if let [gate] = gates {
+		return gate.clone();
+	}
+	let mut local = Circuit::new(qubits.len());
+	for gate in gates {
+		local.add_gate(gate.map_qubits(|q| qubits.iter().position(|&p| p == q).unwrap() as u32));
+	}
+	Gate::DenseMatrix {
+		targets: qubits.to_vec(),
+		matrix: local.to_unitary(),
+	}
+}
@@ -1,4 +1,5 @@
-use crate::binding::{qulacs, wrap, ControlValue, Gate};
+pub use crate::binding::ControlValue;
+use crate::binding::{qulacs, wrap, Gate};
 use num::{Complex, One, Zero};
 
 /// Apply the Pauli X gate to the quantum state.
@@ -194,7 +195,7 @@ pub fn ccz_gate(
 ///   `matrix`
 /// * `target_qubit_index` the target applied `matrix`
 /// * `state` quantum state
-fn multi_control_u_gate(
+pub(crate) fn multi_control_u_gate(
 	control_qubit_indexes: &[u32],
 	target_qubit_index: u32,
 	matrix: &[Complex<f64>; 4],
@@ -215,6 +216,86 @@ fn multi_control_u_gate(
 	);
 }
 
+/// Apply arbitrary dense matrix to the target qubits of the quantum state
+/// when every control qubit has its control value.
+/// * `controls` pairs of the index of a control qubit and its value
+/// * `target_qubit_index_list` indexes of the target qubits; the first one is
+///   the least significant bit of the row and column indexes of `matrix`
+/// * `matrix` 2^n×2^n matrix in row-major order where n is the number of
+///   targets
+/// * `state` quantum state
+///
+/// # Panics
+/// Panics if the size of `matrix` does not match the number of targets.
+pub fn multi_control_dense_matrix_gate(
+	controls: &[(u32, ControlValue)],
+	target_qubit_index_list: &[u32],
+	matrix: &[Complex<f64>],
+	state: &mut [Complex<f64>],
+) {
+	let dim = 1usize << target_qubit_index_list.len();
+	assert_eq!(
+		matrix.len(),
+		dim * dim,
+		"matrix for {} qubits must have {} elements",
+		target_qubit_index_list.len(),
+		dim * dim
+	);
+	if let [target_index] = *target_qubit_index_list {
+		wrap(
+			state,
+			Gate::MultiControlledSingleTarget {
+				controls,
+				target_index,
+				matrix: &[matrix[0], matrix[1], matrix[2], matrix[3]],
+				gate: qulacs::multi_qubit_control_single_qubit_dense_matrix_gate,
+			},
+		);
+		return;
+	}
+	wrap(
+		state,
+		Gate::MultiControlledMultiTarget {
+			controls,
+			target_qubit_index_list,
+			matrix,
+			gate: qulacs::multi_qubit_control_multi_qubit_dense_matrix_gate,
+		},
+	);
+}
+
+/// Apply arbitrary dense matrix to the quantum state.
+/// * `target_qubit_index_list` indexes of the target qubits; the first one is
+///   the least significant bit of the row and column indexes of `matrix`
+/// * `matrix` 2^n×2^n matrix in row-major order where n is the number of
+///   targets
+/// * `state` quantum state
+///
+/// # Panics
+/// Panics if the size of `matrix` does not match the number of targets.
+pub fn dense_matrix_gate(
+	target_qubit_index_list: &[u32],
+	matrix: &[Complex<f64>],
+	state: &mut [Complex<f64>],
+) {
+	let dim = 1usize << target_qubit_index_list.len();
+	assert_eq!(
+		matrix.len(),
+		dim * dim,
+		"matrix for {} qubits must have {} elements",
+		target_qubit_index_list.len(),
+		dim * dim
+	);
+	wrap(
+		state,
+		Gate::MultiTarget {
+			target_qubit_index_list,
+			matrix,
+			gate: qulacs::multi_qubit_dense_matrix_gate,
+		},
+	);
+}
+
 /// Apply the SWAP to the quantum state.
 /// * `target_qubit_index_0` index of the first target qubit
 /// * `target_qubit_index_1` index of the second target qubit
@@ -265,3 +346,324 @@ pub fn rz_gate(target_qubit_index: u32, angle: f64, state: &mut [Complex<f64>])
 		Gate::rotation_of(target_qubit_index, -angle, qulacs::RZ_gate),
 	);
 }
+
+/// Apply a two-qubit matrix given in row-major order, where
+/// `target_qubit_index_0` is the less significant bit of the matrix index.
+fn two_qubit_gate(
+	target_qubit_index_0: u32,
+	target_qubit_index_1: u32,
+	matrix: &[Complex<f64>; 16],
+	state: &mut [Complex<f64>],
+) {
+	dense_matrix_gate(&[target_qubit_index_0, target_qubit_index_1], matrix, state);
+}
+
+/// Apply an XX rotation gate by angle to the quantum state. The definition is
+/// given as exp(-i(θ/2)X⊗X) where θ is the angle.
+/// * `target_qubit_index_0` index of the first target qubit
+/// * `target_qubit_index_1` index of the second target qubit
+/// * `angle` angle of the rotation
+/// * `state` quantum state
+pub fn rxx_gate(
+	target_qubit_index_0: u32,
+	target_qubit_index_1: u32,
+	angle: f64,
+	state: &mut [Complex<f64>],
+) {
+	let c = Complex::new((angle / 2.).cos(), 0.);
+	let s = Complex::new(0., -(angle / 2.).sin());
+	let o = Complex::zero();
+	#[rustfmt::skip]
+	let matrix = [
+		c, o, o, s,
+		o, c, s, o,
+		o, s, c, o,
+		s, o, o, c,
+	];
+	two_qubit_gate(target_qubit_index_0, target_qubit_index_1, &matrix, state);
+}
+
+/// Apply a YY rotation gate by angle to the quantum state. The definition is
+/// given as exp(-i(θ/2)Y⊗Y) where θ is the angle.
+/// * `target_qubit_index_0` index of the first target qubit
+/// * `target_qubit_index_1` index of the second target qubit
+/// * `angle` angle of the rotation
+/// * `state` quantum state
+pub fn ryy_gate(
+	target_qubit_index_0: u32,
+	target_qubit_index_1: u32,
+	angle: f64,
+	state: &mut [Complex<f64>],
+) {
+	let c = Complex::new((angle / 2.).cos(), 0.);
+	let s = Complex::new(0., -(angle / 2.).sin());
+	let o = Complex::zero();
+	#[rustfmt::skip]
+	let matrix = [
+		c, o, o, -s,
+		o, c, s, o,
+		o, s, c, o,
+		-s, o, o, c,
+	];
+	two_qubit_gate(target_qubit_index_0, target_qubit_index_1, &matrix, state);
+}
+
+/// Apply a ZZ rotation gate by angle to the quantum state. The definition is
+/// given as exp(-i(θ/2)Z⊗Z) where θ is the angle.
+/// * `target_qubit_index_0` index of the first target qubit
+/// * `target_qubit_index_1` index of the second target qubit
+/// * `angle` angle of the rotation
+/// * `state` quantum state
+pub fn rzz_gate(
+	target_qubit_index_0: u32,
+	target_qubit_index_1: u32,
+	angle: f64,
+	state: &mut [Complex<f64>],
+) {
+	let even = Complex::from_polar(1., -angle / 2.);
+	let odd = Complex::from_polar(1., angle / 2.);
+	let o = Complex::zero();
+	#[rustfmt::skip]
+	let matrix = [
+		even, o, o, o,
+		o, odd, o, o,
+		o, o, odd, o,
+		o, o, o, even,
+	];
+	two_qubit_gate(target_qubit_index_0, target_qubit_index_1, &matrix, state);
+}
+
+/// Apply the iSWAP gate, which swaps |01> and |10> with a phase i, to the
+/// quantum state. It equals exp(i(π/4)(X⊗X + Y⊗Y)).
+/// * `target_qubit_index_0` index of the first target qubit
+/// * `target_qubit_index_1` index of the second target qubit
+/// * `state` quantum state
+pub fn iswap_gate(
+	target_qubit_index_0: u32,
+	target_qubit_index_1: u32,
+	state: &mut [Complex<f64>],
+) {
+	fsim_gate(
+		target_qubit_index_0,
+		target_qubit_index_1,
+		-std::f64::consts::FRAC_PI_2,
+		0.,
+		state,
+	);
+}
+
+/// Apply the square root of the iSWAP gate to the quantum state. It equals
+/// exp(i(π/8)(X⊗X + Y⊗Y)).
+/// * `target_qubit_index_0` index of the first target qubit
+/// * `target_qubit_index_1` index of the second target qubit
+/// * `state` quantum state
+pub fn sqrt_iswap_gate(
+	target_qubit_index_0: u32,
+	target_qubit_index_1: u32,
+	state: &mut [Complex<f64>],
+) {
+	fsim_gate(
+		target_qubit_index_0,
+		target_qubit_index_1,
+		-std::f64::consts::FRAC_PI_4,
+		0.,
+		state,
+	);
+}
+
+/// Apply the fermionic simulation gate fSim(θ, φ) to the quantum state.
+/// It rotates |01> and |10> into each other by exp(-iθ(X⊗X + Y⊗Y)/2) and
+/// multiplies |11> by e^{-iφ}, so that fSim(-π/2, 0) is iSWAP.
+/// * `target_qubit_index_0` index of the first target qubit
+/// * `target_qubit_index_1` index of the second target qubit
+/// * `theta` swap angle θ
+/// * `phi` conditional phase φ
+/// * `state` quantum state
+pub fn fsim_gate(
+	target_qubit_index_0: u32,
+	target_qubit_index_1: u32,
+	theta: f64,
+	phi: f64,
+	state: &mut [Complex<f64>],
+) {
+	let c = Complex::new(theta.cos(), 0.);
+	let s = Complex::new(0., -theta.sin());
+	let (o, l) = (Complex::zero(), Complex::one());
+	#[rustfmt::skip]
+	let matrix = [
+		l, o, o, o,
+		o, c, s, o,
+		o, s, c, o,
+		o, o, o, Complex::from_polar(1., -phi),
+	];
+	two_qubit_gate(target_qubit_index_0, target_qubit_index_1, &matrix, state);
+}
+
+/// Apply the echoed cross-resonance gate (I⊗X - X⊗Y)/√2 to the quantum
+/// state, where the right factor acts on `target_qubit_index_0`. It equals
+/// CNOT up to single-qubit gates.
+/// * `target_qubit_index_0` index of the first target qubit
+/// * `target_qubit_index_1` index of the second target qubit
+/// * `state` quantum state
+pub fn ecr_gate(target_qubit_index_0: u32, target_qubit_index_1: u32, state: &mut [Complex<f64>]) {
+	let h = std::f64::consts::FRAC_1_SQRT_2;
+	let (o, l, i) = (Complex::zero(), Complex::new(h, 0.), Complex::new(0., h));
+	#[rustfmt::skip]
+	let matrix = [
+		o, l, o, i,
+		l, o, -i, o,
+		o, i, o, l,
+		-i, o, l, o,
+	];
+	two_qubit_gate(target_qubit_index_0, target_qubit_index_1, &matrix, state);
+}
+
+/// Apply a Givens rotation by angle to the quantum state. It maps |01> to
+/// cos(θ/2)|01> + sin(θ/2)|10> and |10> to cos(θ/2)|10> - sin(θ/2)|01>,
+/// which is exp(-i(θ/2)(Y⊗X - X⊗Y)/2) where θ is the angle and the right
+/// factors act on `target_qubit_index_0`. In the Jordan-Wigner encoding it
+/// mixes the two spin orbitals without changing the particle number.
+/// * `target_qubit_index_0` index of the first target qubit
+/// * `target_qubit_index_1` index of the second target qubit
+/// * `angle` angle of the rotation
+/// * `state` quantum state
+pub fn givens_gate(
+	target_qubit_index_0: u32,
+	target_qubit_index_1: u32,
+	angle: f64,
+	state: &mut [Complex<f64>],
+) {
+	let c = Complex::new((angle / 2.).cos(), 0.);
+	let s = Complex::new((angle / 2.).sin(), 0.);
+	let (o, l) = (Complex::zero(), Complex::one());
+	#[rustfmt::skip]
+	let matrix = [
+		l, o, o, o,
+		o, c, -s, o,
+		o, s, c, o,
+		o, o, o, l,
+	];
+	two_qubit_gate(target_qubit_index_0, target_qubit_index_1, &matrix, state);
+}
+
+/// Apply the phase gate diag(1, e^{iλ}) to the quantum state. This is `p`
+/// in OpenQASM 3 and `u1` in OpenQASM 2.
+/// * `target_qubit_index` index of the qubit
+/// * `angle` phase λ
+/// * `state` quantum state
+pub fn phase_gate(target_qubit_index: u32, angle: f64, state: &mut [Complex<f64>]) {
+	let diagonal = [Complex::one(), Complex::from_polar(1., angle)];
+	wrap(
+		state,
+		Gate::SingleMatrix {
+			target_qubit_index,
+			matrix: &diagonal,
+			gate: qulacs::single_qubit_diagonal_matrix_gate,
+		},
+	);
+}
+
+/// Apply the OpenQASM `u1` gate, which is the same as `phase_gate`.
+/// * `target_qubit_index` index of the qubit
+/// * `lambda` phase λ
+/// * `state` quantum state
+pub fn u1_gate(target_qubit_index: u32, lambda: f64, state: &mut [Complex<f64>]) {
+	phase_gate(target_qubit_index, lambda, state);
+}
+
+/// Apply the OpenQASM `u2` gate U(π/2, φ, λ) to the quantum state.
+/// * `target_qubit_index` index of the qubit
+/// * `phi` angle φ
+/// * `lambda` angle λ
+/// * `state` quantum state
+pub fn u2_gate(target_qubit_index: u32, phi: f64, lambda: f64, state: &mut [Complex<f64>]) {
+	u3_gate(
+		target_qubit_index,
+		std::f64::consts::FRAC_PI_2,
+		phi,
+		lambda,
+		state,
+	);
+}
+
+/// Apply the OpenQASM `u3` gate U(θ, φ, λ) to the quantum state, given by
+/// the matrix
+/// [[cos(θ/2), -e^{iλ}sin(θ/2)], [e^{iφ}sin(θ/2), e^{i(φ+λ)}cos(θ/2)]].
+/// It equals e^{i(φ+λ)/2} Rz(φ) Ry(θ) Rz(λ).
+/// * `target_qubit_index` index of the qubit
+/// * `theta` angle θ
+/// * `phi` angle φ
+/// * `lambda` angle λ
+/// * `state` quantum state
+pub fn u3_gate(
+	target_qubit_index: u32,
+	theta: f64,
+	phi: f64,
+	lambda: f64,
+	state: &mut [Complex<f64>],
+) {
+	let (c, s) = ((theta / 2.).cos(), (theta / 2.).sin());
+	let matrix = [
+		Complex::new(c, 0.),
+		-Complex::from_polar(s, lambda),
+		Complex::from_polar(s, phi),
+		Complex::from_polar(c, phi + lambda),
+	];
+	wrap(
+		state,
+		Gate::SingleMatrix {
+			target_qubit_index,
+			matrix: &matrix,
+			gate: qulacs::single_qubit_dense_matrix_gate,
+		},
+	);
+}
+
+/// Apply the controlled phase gate diag(1, 1, 1, e^{iλ}) to the quantum
+/// state. It is symmetric in the two qubits.
+/// * `control_qubit_index` index of the control qubit
+/// * `target_qubit_index` index of the target qubit
+/// * `angle` phase λ
+/// * `state` quantum state
+pub fn cp_gate(
+	control_qubit_index: u32,
+	target_qubit_index: u32,
+	angle: f64,
+	state: &mut [Complex<f64>],
+) {
+	let diagonal = [
+		Complex::one(),
+		Complex::one(),
+		Complex::one(),
+		Complex::from_polar(1., angle),
+	];
+	wrap(
+		state,
+		Gate::MultiTarget {
+			target_qubit_index_list: &[control_qubit_index, target_qubit_index],
+			matrix: &diagonal,
+			gate: qulacs::multi_qubit_diagonal_matrix_gate,
+		},
+	);
+}
+
+/// Apply the phase e^{iλ} to the states where all of the control qubits and
+/// the target qubit are |1>.
+/// * `control_qubit_indexes` indexes of the control qubits
+/// * `target_qubit_index` index of the target qubit
+/// * `angle` phase λ
+/// * `state` quantum state
+pub fn multi_control_phase_gate(
+	control_qubit_indexes: &[u32],
+	target_qubit_index: u32,
+	angle: f64,
+	state: &mut [Complex<f64>],
+) {
+	let matrix = [
+		Complex::one(),
+		Complex::zero(),
+		Complex::zero(),
+		Complex::from_polar(1., angle),
+	];
+	multi_control_u_gate(control_qubit_indexes, target_qubit_index, &matrix, state);
+}
@@ -0,0 +1,271 @@
+//! Minimal JSON reader for calibration files.
+
+/// Maximum nesting depth of arrays and objects.
+const MAX_DEPTH: usize = 128;
+
+/// Parsed JSON value. Objects keep the order of their members.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+	Null,
+	Bool(bool),
+	Number(f64),
+	String(String),
+	Array(Vec<Value>),
+	Object(Vec<(String, Value)>),
+}
+
+impl Value {
+	/// Get the member `key` of an object.
+	pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+		match self {
+			Value::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+			_ => None,
+		}
+	}
+
+	pub(crate) fn as_f64(&self) -> Option<f64> {
+		match self {
+			Value::Number(x) => Some(*x),
+			_ => None,
+		}
+	}
+
+	pub(crate) fn as_str(&self) -> Option<&str> {
+		match self {
+			Value::String(s) => Some(s),
+			_ => None,
+		}
+	}
+
+	pub(crate) fn as_array(&self) -> Option<&[Value]> {
+		match self {
+			Value::Array(values) => Some(values),
+			_ => None,
+		}
+	}
+}
+
+/// Parse `text` as a single JSON value. Returns the byte offset of the first
+/// invalid character on failure, which is also the opening bracket of an
+/// array or object nested deeper than `MAX_DEPTH`.
+pub(crate) fn parse(text: &str) -> Result<Value, usize> {
+	let mut parser = Parser {
+		bytes: text.as_bytes(),
+		position: 0,
+	};
+	let value = parser.value(0)?;
+	parser.skip_whitespace();
+	if parser.position == parser.bytes.len() {
+		Ok(value)
+	} else {
+		Err(parser.position)
+	}
+}
+
+struct Parser<'a> {
+	bytes: &'a [u8],
+	position: usize,
+}
+
+impl<'a> Parser<'a> {
+	fn skip_whitespace(&mut self) {
+		while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+			self.position += 1;
+		}
+	}
+
+	fn peek(&self) -> Option<u8> {
+		self.bytes.get(self.position).cloned()
+	}
+
+	fn expect(&mut self, byte: u8) -> Result<(), usize> {
+		if self.peek() == Some(byte) {
+			self.position += 1;
+			Ok(())
+		} else {
+			Err(self.position)
+		}
+	}
+
+	fn literal(&mut self, word: &str, value: Value) -> Result<Value, usize> {
+		if self.bytes[self.position..].starts_with(word.as_bytes()) {
+			self.position += word.len();
+			Ok(value)
+		} else {
+			Err(self.position)
+		}
+	}
+
+	/// Parse a value inside `depth` arrays and objects.
+	fn value(&mut self, depth: usize) -> Result<Value, usize> {
+		self.skip_whitespace();
+		match self.peek() {
+			Some(b'{' | b'[') if depth == MAX_DEPTH => Err(self.position),
+			Some(b'{') => self.object(depth + 1),
+			Some(b'[') => self.array(depth + 1),
+			Some(b'"') => Ok(Value::String(self.string()?)),
+			Some(b't') => self.literal("true", Value::Bool(true)),
+			Some(b'f') => self.literal("false", Value::Bool(false)),
+			Some(b'n') => self.literal("null", Value::Null),
+			Some(b'-' | b'0'..=b'9') => self.number(),
+			_ => Err(self.position),
+		}
+	}
+
+	fn object(&mut self, depth: usize) -> Result<Value, usize> {
+		self.expect(b'{')?;
+		let mut members = Vec::new();
+		self.skip_whitespace();
+		if self.peek() == Some(b'}') {
+			self.position += 1;
+			return Ok(Value::Object(members));
+		}
+		loop {
+			self.skip_whitespace();
+			let key = self.string()?;
+			self.skip_whitespace();
+			self.expect(b':')?;
+			members.push((key, self.value(depth)?));
+			self.skip_whitespace();
+			match self.peek() {
+				Some(b',') => self.position += 1,
+				Some(b'}') => {
+					self.position += 1;
+					return Ok(Value::Object(members));
+				}
+				_ => return Err(self.position),
+			}
+		}
+	}
+
+	fn array(&mut self, depth: usize) -> Result<Value, usize> {
+		self.expect(b'[')?;
+		let mut values = Vec::new();
+		self.skip_whitespace();
+		if self.peek() == Some(b']') {
+			self.position += 1;
+			return Ok(Value::Array(values));
+		}
+		loop {
+			values.push(self.value(depth)?);
+			self.skip_whitespace();
+			match self.peek() {
+				Some(b',') => self.position += 1,
+				Some(b']') => {
+					self.position += 1;
+					return Ok(Value::Array(values));
+				}
+				_ => return Err(self.position),
+			}
+		}
+	}
+
+	fn string(&mut self) -> Result<String, usize> {
+		self.expect(b'"')?;
+		let mut bytes = Vec::new();
+		loop {
+			let start = self.position;
+			match self.peek() {
+				None => return Err(self.position),
+				Some(b'"') => {
+					self.position += 1;
+					return String::from_utf8(bytes).map_err(|_| start);
+				}
+				Some(b'\\') => {
+					self.position += 1;
+					let escaped = match self.peek() {
+						Some(b'"') => '"',
+						Some(b'\\') => '\\',
+						Some(b'/') => '/',
+						Some(b'b') => '\u{8}',
+						Some(b'f') => '\u{c}',
+						Some(b'n') => '\n',
+						Some(b'r') => '\r',
+						Some(b't') => '\t',
+						Some(b'u') => {
+							let high = self.hex_escape()?;
+							let code = if (0xd800..0xdc00).contains(&high) {
+								// a high surrogate must be followed by an escaped low surrogate
+								if !self.bytes[self.position + 1..].starts_with(b"\\u") {
+									return Err(start);
+								}
+								self.position += 2;
+								let low = self.hex_escape()?;
+								if !(0xdc00..0xe000).contains(&low) {
+									return Err(start);
+								}
+								0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+							} else {
+								high
+							};
+							char::from_u32(code).ok_or(start)?
+						}
+						_ => return Err(self.position),
+					};
+					self.position += 1;
+					let mut buffer = [0; 4];
+					bytes.extend_from_slice(escaped.encode_utf8(&mut buffer).as_bytes());
+				}
+				Some(b) if b < 0x20 => return Err(self.position),
+				Some(b) => {
+					bytes.push(b);
+					self.position += 1;
+				}
+			}
+		}
+	}
+
+	/// Read the 4 hexadecimal digits after the `u` of a `\u` escape at the
+	/// current position, leaving the position at the last digit.
+	fn hex_escape(&mut self) -> Result<u32, usize> {
+		let digits = self
+			.bytes
+			.get(self.position + 1..self.position + 5)
+			.filter(|d| d.iter().all(u8::is_ascii_hexdigit))
+			.ok_or(self.position)?;
+		self.position += 4;
+		Ok(digits
+			.iter()
+			.fold(0, |code, &d| code * 16 + (d as char).to_digit(16).unwrap()))
+	}
+
+	/// Skip one or more decimal digits.
+	fn digits(&mut self) -> Result<(), usize> {
+		if !matches!(self.peek(), Some(b'0'..=b'9')) {
+			return Err(self.position);
+		}
+		while let Some(b'0'..=b'9') = self.peek() {
+			self.position += 1;
+		}
+		Ok(())
+	}
+
+	/// Parse a number following the JSON grammar, which has no leading `+`,
+	/// leading zeros or bare decimal point.
+	fn number(&mut self) -> Result<Value, usize> {
+		let start = self.position;
+		if self.peek() == Some(b'-') {
+			self.position += 1;
+		}
+		match self.peek() {
+			Some(b'0') => self.position += 1,
+			_ => self.digits()?,
+		}
+		if self.peek() == Some(b'.') {
+			self.position += 1;
+			self.digits()?;
+		}
+		if let Some(b'e' | b'E') = self.peek() {
+			self.position += 1;
+			if let Some(b'+' | b'-') = self.peek() {
+				self.position += 1;
+			}
+			self.digits()?;
+		}
+		std::str::from_utf8(&self.bytes[start..self.position])
+			.ok()
+			.and_then(|s| s.parse().ok())
+			.map(Value::Number)
+			.ok_or(start)
+	}
+}
@@ -0,0 +1,310 @@
+use crate::fermion::{FermionEncoding, FermionOperator, LadderOperator};
+use crate::observable::Observable;
+use crate::pauli::{Pauli, PauliString};
+use crate::Complex;
+use std::fmt;
+
+#[non_exhaustive]
+pub enum LatticeErr {
+	/// The edge is a self loop, refers to a site out of range or appears
+	/// twice.
+	InvalidEdge(u32, u32),
+}
+
+impl fmt::Display for LatticeErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			LatticeErr::InvalidEdge(a, b) => write!(f, "Invalid edge: ({}, {})", a, b),
+		}
+	}
+}
+
+impl fmt::Debug for LatticeErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		<Self as fmt::Display>::fmt(self, f)
+	}
+}
+
+/// Boundary condition of a lattice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Boundary {
+	Open,
+	/// The last site of each row or column is connected to the first one.
+	/// Lengths of at most 2 get no extra edge.
+	Periodic,
+}
+
+/// Sites connected by undirected edges.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lattice {
+	site_count: usize,
+	edges: Vec<(u32, u32)>,
+}
+
+impl Lattice {
+	/// Build the lattice of `site_count` sites connected by `edges`.
+	///
+	/// Returns an error if an edge is a self loop, refers to a site out of
+	/// range or is given twice in either direction.
+	pub fn new(site_count: usize, edges: &[(u32, u32)]) -> Result<Self, LatticeErr> {
+		for (i, &(a, b)) in edges.iter().enumerate() {
+			let duplicated = edges[..i].iter().any(|&e| e == (a, b) || e == (b, a));
+			if a == b || a as usize >= site_count || b as usize >= site_count || duplicated {
+				return Err(LatticeErr::InvalidEdge(a, b));
+			}
+		}
+		Ok(Self {
+			site_count,
+			edges: edges.to_vec(),
+		})
+	}
+
+	/// Get the chain of `length` sites.
+	pub fn chain(length: usize, boundary: Boundary) -> Self {
+		Self::square(length, 1, boundary)
+	}
+
+	/// Get the ladder of two legs of `length` sites connected by rungs. Site
+	/// `2i + l` is the i-th site of leg l, and the boundary applies along the
+	/// legs.
+	pub fn ladder(length: usize, boundary: Boundary) -> Self {
+		let mut edges = Vec::new();
+		for i in 0..length as u32 {
+			edges.push((2 * i, 2 * i + 1));
+		}
+		for (a, b) in periodic_pairs(length, boundary) {
+			edges.push((2 * a, 2 * b));
+			edges.push((2 * a + 1, 2 * b + 1));
+		}
+		Self::new(2 * length, &edges).unwrap()
+	}
+
+	/// Get the `width`×`height` square lattice, numbered row by row.
+	pub fn square(width: usize, height: usize, boundary: Boundary) -> Self {
+		let mut edges = Vec::new();
+		for y in 0..height as u32 {
+			for (a, b) in periodic_pairs(width, boundary) {
+				edges.push((y * width as u32 + a, y * width as u32 + b));
+			}
+		}
+		for x in 0..width as u32 {
+			for (a, b) in periodic_pairs(height, boundary) {
+				edges.push((a * width as u32 + x, b * width as u32 + x));
+			}
+		}
+		Self::new(width * height, &edges).unwrap()
+	}
+
+	///Get site count
+	pub fn site_count(&self) -> usize {
+		self.site_count
+	}
+
+	///Get edges
+	pub fn edges(&self) -> &[(u32, u32)] {
+		&self.edges
+	}
+}
+
+/// Neighbouring pairs along a line of `length` sites.
+fn periodic_pairs(length: usize, boundary: Boundary) -> Vec<(u32, u32)> {
+	let length = length as u32;
+	let mut pairs: Vec<(u32, u32)> = (1..length).map(|i| (i - 1, i)).collect();
+	if boundary == Boundary::Periodic && length > 2 {
+		pairs.push((length - 1, 0));
+	}
+	pairs
+}
+
+/// Coefficients of the terms on every edge or every site of a lattice.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Coefficients {
+	/// The same value everywhere
+	Uniform(f64),
+	/// A value for each edge in the order of `Lattice::edges`, or for each
+	/// site
+	Individual(Vec<f64>),
+}
+
+impl Coefficients {
+	fn values(&self, count: usize, name: &str) -> Vec<f64> {
+		match self {
+			Coefficients::Uniform(v) => vec![*v; count],
+			Coefficients::Individual(values) => {
+				assert_eq!(
+					values.len(),
+					count,
+					"{} has {} values, but {} are required",
+					name,
+					values.len(),
+					count
+				);
+				values.clone()
+			}
+		}
+	}
+}
+
+impl From<f64> for Coefficients {
+	fn from(value: f64) -> Self {
+		Coefficients::Uniform(value)
+	}
+}
+
+impl From<Vec<f64>> for Coefficients {
+	fn from(values: Vec<f64>) -> Self {
+		Coefficients::Individual(values)
+	}
+}
+
+fn add_pauli_term(
+	observable: &mut Observable,
+	coefficient: f64,
+	targets: &[u32],
+	paulis: &[Pauli],
+) {
+	if coefficient != 0. {
+		observable.add_term(
+			Complex::new(coefficient, 0.),
+			PauliString::new(targets, paulis).unwrap(),
+		);
+	}
+}
+
+/// Build the Ising model
+///
+/// H = -Σ_(ij) J_ij Z_i Z_j - Σ_i h_i X_i - Σ_i g_i Z_i
+///
+/// with the `coupling` J on edges, the `transverse_field` h and the
+/// `longitudinal_field` g on sites, and site i on qubit i.
+///
+/// # Panics
+/// Panics if individual coefficients do not match the number of edges or
+/// sites.
+pub fn ising<C, H, G>(
+	lattice: &Lattice,
+	coupling: C,
+	transverse_field: H,
+	longitudinal_field: G,
+) -> Observable
+where
+	C: Into<Coefficients>,
+	H: Into<Coefficients>,
+	G: Into<Coefficients>,
+{
+	let n = lattice.site_count();
+	let coupling = coupling.into().values(lattice.edges().len(), "coupling");
+	let transverse_field = transverse_field.into().values(n, "transverse_field");
+	let longitudinal_field = longitudinal_field.into().values(n, "longitudinal_field");
+	let mut observable = Observable::new(n);
+	for (&(a, b), j) in lattice.edges().iter().zip(coupling) {
+		add_pauli_term(&mut observable, -j, &[a, b], &[Pauli::Z, Pauli::Z]);
+	}
+	for (i, (h, g)) in transverse_field
+		.into_iter()
+		.zip(longitudinal_field)
+		.enumerate()
+	{
+		add_pauli_term(&mut observable, -h, &[i as u32], &[Pauli::X]);
+		add_pauli_term(&mut observable, -g, &[i as u32], &[Pauli::Z]);
+	}
+	observable
+}
+
+/// Build the XXZ model
+///
+/// H = Σ_(ij) J_ij (X_i X_j + Y_i Y_j + Δ_ij Z_i Z_j) - Σ_i h_i Z_i
+///
+/// with the `coupling` J and the `anisotropy` Δ on edges, the `field` h on
+/// sites, and site i on qubit i.
+///
+/// # Panics
+/// Panics if individual coefficients do not match the number of edges or
+/// sites.
+pub fn xxz<C, D, H>(lattice: &Lattice, coupling: C, anisotropy: D, field: H) -> Observable
+where
+	C: Into<Coefficients>,
+	D: Into<Coefficients>,
+	H: Into<Coefficients>,
+{
+	let edge_count = lattice.edges().len();
+	let coupling = coupling.into().values(edge_count, "coupling");
+	let anisotropy = anisotropy.into().values(edge_count, "anisotropy");
+	let field = field.into().values(lattice.site_count(), "field");
+	let mut observable = Observable::new(lattice.site_count());
+	for ((&(a, b), j), delta) in lattice.edges().iter().zip(coupling).zip(anisotropy) {
+		add_pauli_term(&mut observable, j, &[a, b], &[Pauli::X, Pauli::X]);
+		add_pauli_term(&mut observable, j, &[a, b], &[Pauli::Y, Pauli::Y]);
+		add_pauli_term(&mut observable, j * delta, &[a, b], &[Pauli::Z, Pauli::Z]);
+	}
+	for (i, h) in field.into_iter().enumerate() {
+		add_pauli_term(&mut observable, -h, &[i as u32], &[Pauli::Z]);
+	}
+	observable
+}
+
+/// Build the Heisenberg model
+///
+/// H = Σ_(ij) J_ij (X_i X_j + Y_i Y_j + Z_i Z_j) - Σ_i h_i Z_i,
+///
+/// i.e. the XXZ model with Δ = 1.
+///
+/// # Panics
+/// Panics if individual coefficients do not match the number of edges or
+/// sites.
+pub fn heisenberg<C, H>(lattice: &Lattice, coupling: C, field: H) -> Observable
+where
+	C: Into<Coefficients>,
+	H: Into<Coefficients>,
+{
+	xxz(lattice, coupling, 1., field)
+}
+
+/// Build the Fermi–Hubbard model
+///
+/// H = -Σ_(ij),σ t_ij (a†_iσ a_jσ + a†_jσ a_iσ) + Σ_i U_i n_i↑ n_i↓ - Σ_iσ μ_i n_iσ
+///
+/// with the `hopping` t on edges, the `interaction` U and the
+/// `chemical_potential` μ on sites. Mode 2i + σ (σ = 0 for ↑) is mapped to a
+/// qubit by the Jordan–Wigner encoding.
+///
+/// # Panics
+/// Panics if individual coefficients do not match the number of edges or
+/// sites.
+pub fn fermi_hubbard<T, U, M>(
+	lattice: &Lattice,
+	hopping: T,
+	interaction: U,
+	chemical_potential: M,
+) -> Observable
+where
+	T: Into<Coefficients>,
+	U: Into<Coefficients>,
+	M: Into<Coefficients>,
+{
+	use LadderOperator::{Annihilate, Create};
+	let n = lattice.site_count();
+	let hopping = hopping.into().values(lattice.edges().len(), "hopping");
+	let interaction = interaction.into().values(n, "interaction");
+	let chemical_potential = chemical_potential.into().values(n, "chemical_potential");
+	let mut h = FermionOperator::new();
+	for (&(a, b), t) in lattice.edges().iter().zip(hopping) {
+		let (a, b) = (a as usize, b as usize);
+		for sigma in 0..2 {
+			let (i, j) = (2 * a + sigma, 2 * b + sigma);
+			h.add_term(Complex::new(-t, 0.), &[Create(i), Annihilate(j)]);
+			h.add_term(Complex::new(-t, 0.), &[Create(j), Annihilate(i)]);
+		}
+	}
+	for (i, (u, mu)) in interaction.into_iter().zip(chemical_potential).enumerate() {
+		let (up, down) = (2 * i, 2 * i + 1);
+		h.add_term(
+			Complex::new(u, 0.),
+			&[Create(up), Annihilate(up), Create(down), Annihilate(down)],
+		);
+		for &mode in &[up, down] {
+			h.add_term(Complex::new(-mu, 0.), &[Create(mode), Annihilate(mode)]);
+		}
+	}
+	h.encode(FermionEncoding::JordanWigner, 2 * n)
+}
@@ -1,6 +1,28 @@
+pub mod algorithm;
 mod binding;
+pub mod chemistry;
+pub mod circuit;
+pub mod dynamic_circuit;
+pub mod fermion;
+pub mod fusion;
 pub mod gate;
+mod json;
+pub mod lattice;
+pub mod measurement;
+pub mod mitigation;
+pub mod noise;
+pub mod noise_model;
+pub mod observable;
+pub mod optimizer;
+pub mod pauli;
+pub mod qaoa;
+pub mod readout;
+pub mod routing;
+pub mod shadow;
 mod state;
+pub mod synthesis;
+pub mod tomography;
+pub mod transpiler;
 pub mod prelude {
 	pub use crate::state::{StateErr, StateMut, StateRef};
 }
@@ -98,6 +120,12 @@ where
 }
 
 ///Get expectation value
+///
+/// See `pauli::PauliString` for a validated version.
+///
+/// # Panics
+/// Panics if the lists differ in length, a target is repeated or out of
+/// range, or a Pauli type is not in 0..=3.
 pub fn expectation_value_multi_qubit_pauli_operator_partial_list<T>(
 	target_qubit_index_list: &[u32],
 	pauli_operator_type_list: &[u32],
@@ -106,6 +134,11 @@ pub fn expectation_value_multi_qubit_pauli_operator_partial_list<T>(
 where
 	T: StateRef<f64> + AsRef<[Complex<f64>]>,
 {
+	assert_valid_pauli_list(
+		target_qubit_index_list,
+		pauli_operator_type_list,
+		state.qubit_count(),
+	);
 	unsafe {
 		qulacs::expectation_value_multi_qubit_Pauli_operator_partial_list_single_thread(
 			target_qubit_index_list.as_ptr() as *const u32,
@@ -117,7 +150,15 @@ where
 	}
 }
 
-///Get expectation value
+/// Apply exp(-i(θ/2)P) to the quantum state, where θ is the angle and P is
+/// the Pauli string of `pauli_operator_type_list[i]` (0: I, 1: X, 2: Y, 3: Z)
+/// on qubit `target_qubit_index_list[i]`.
+///
+/// See `pauli::PauliString` for a validated version.
+///
+/// # Panics
+/// Panics if the lists differ in length, a target is repeated or out of
+/// range, or a Pauli type is not in 0..=3.
 pub fn multi_qubit_pauli_rotation_gate_partial_list<T>(
 	target_qubit_index_list: &[u32],
 	pauli_operator_type_list: &[u32],
@@ -126,6 +167,11 @@ pub fn multi_qubit_pauli_rotation_gate_partial_list<T>(
 ) where
 	T: StateRef<f64> + AsMut<[Complex<f64>]>,
 {
+	assert_valid_pauli_list(
+		target_qubit_index_list,
+		pauli_operator_type_list,
+		state.qubit_count(),
+	);
 	unsafe {
 		qulacs::multi_qubit_Pauli_rotation_gate_partial_list(
 			target_qubit_index_list.as_ptr() as *const u32,
@@ -138,6 +184,22 @@ pub fn multi_qubit_pauli_rotation_gate_partial_list<T>(
 	}
 }
 
+/// Check the lists before they are passed to csim, which reads them without
+/// bounds checks.
+pub(crate) fn assert_valid_pauli_list(targets: &[u32], pauli_types: &[u32], qubit_count: usize) {
+	if let Err(e) = pauli::PauliString::from_type_list(targets, pauli_types) {
+		panic!("{}", e);
+	}
+	for &t in targets {
+		assert!(
+			(t as usize) < qubit_count,
+			"target qubit index {} is out of range for {} qubits",
+			t,
+			qubit_count
+		);
+	}
+}
+
 #[test]
 fn test_lib() {
 	use num::{One, Zero};
@@ -0,0 +1,508 @@
+use crate::circuit::{Circuit, Gate};
+use crate::observable::Observable;
+use crate::pauli::{Pauli, PauliString};
+use crate::state::{StateErr, StateRef, StateVec};
+use crate::Complex;
+
+/// Compatibility of Pauli terms measured in the same basis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Grouping {
+	/// Terms act with the same Pauli operator or the identity on every qubit.
+	/// The basis change consists of single-qubit gates.
+	QubitWise,
+	/// Terms commute. The basis change may contain `Cnot` and `Cz`.
+	Commuting,
+}
+
+impl Grouping {
+	/// Whether `a` and `b` can be measured in the same basis.
+	pub fn compatible(self, a: &PauliString, b: &PauliString) -> bool {
+		let differing = a
+			.targets()
+			.iter()
+			.zip(a.paulis())
+			.filter(|&(&q, &p)| {
+				let other = b.pauli_at(q);
+				p != Pauli::I && other != Pauli::I && p != other
+			})
+			.count();
+		match self {
+			Grouping::QubitWise => differing == 0,
+			Grouping::Commuting => differing % 2 == 0,
+		}
+	}
+}
+
+/// Terms of an observable measured together in the computational basis after
+/// a common basis change U.
+#[derive(Clone, Debug)]
+pub struct MeasurementGroup {
+	terms: Vec<usize>,
+	coefficients: Vec<f64>,
+	circuit: Circuit,
+	/// (sign, mask) for each term P such that U P U† = sign · Π_(q in mask) Z_q
+	readouts: Vec<(f64, u64)>,
+}
+
+impl MeasurementGroup {
+	///Get indices of the terms in `Observable::terms`
+	pub fn terms(&self) -> &[usize] {
+		&self.terms
+	}
+
+	///Get the basis change circuit applied before the measurement
+	pub fn circuit(&self) -> &Circuit {
+		&self.circuit
+	}
+
+	/// Get the value of Σ_i c_i P_i over the terms of this group for a
+	/// measurement `outcome` after the basis change.
+	pub fn value(&self, outcome: u64) -> f64 {
+		self.coefficients
+			.iter()
+			.zip(&self.readouts)
+			.map(|(c, &(sign, mask))| {
+				let parity = (outcome & mask).count_ones() % 2;
+				c * sign * if parity == 0 { 1. } else { -1. }
+			})
+			.sum()
+	}
+
+	/// Get Σ_i |c_i| over the terms of this group, which bounds the standard
+	/// deviation of a single shot.
+	pub fn coefficient_norm(&self) -> f64 {
+		self.coefficients.iter().map(|c| c.abs()).sum()
+	}
+}
+
+/// Estimated expectation value from sampling.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Estimate {
+	/// Estimated expectation value
+	pub value: f64,
+	/// Estimated variance of `value`
+	pub variance: f64,
+	/// Estimated single-shot variance of each group
+	pub group_variances: Vec<f64>,
+}
+
+impl Estimate {
+	/// Get the allocation of `total_shots` minimizing the variance of the next
+	/// estimate, based on the group variances of this one.
+	pub fn optimal_shot_allocation(&self, total_shots: u32) -> Vec<u32> {
+		let deviations: Vec<f64> = self.group_variances.iter().map(|v| v.sqrt()).collect();
+		optimal_shot_allocation(&deviations, total_shots)
+	}
+}
+
+/// Measurement groups of a Hermitian observable.
+///
+/// ```
+/// # use qurs::measurement::{Grouping, MeasurementPlan};
+/// # use qurs::lattice::{heisenberg, Boundary, Lattice};
+/// let h = heisenberg(&Lattice::chain(4, Boundary::Open), 1., 0.);
+/// // XX, YY and ZZ terms
+/// let plan = MeasurementPlan::new(&h, Grouping::QubitWise);
+/// assert_eq!(plan.groups().len(), 3);
+/// let plan = MeasurementPlan::new(&h, Grouping::Commuting);
+/// assert!(plan.groups().len() < 3);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MeasurementPlan {
+	qubit_count: usize,
+	constant: f64,
+	groups: Vec<MeasurementGroup>,
+}
+
+impl MeasurementPlan {
+	/// Group the terms of `observable` by colouring the graph whose edges
+	/// connect incompatible terms, visiting terms in descending order of
+	/// degree. The identity term is not measured. Imaginary parts of the
+	/// coefficients are ignored.
+	///
+	/// # Panics
+	/// Panics if the observable has 64 qubits or more.
+	pub fn new(observable: &Observable, grouping: Grouping) -> Self {
+		let n = observable.qubit_count();
+		assert!(n < 64, "{} qubits are too many to measure", n);
+		let mut constant = 0.;
+		let mut measured = Vec::new();
+		for (i, (c, p)) in observable.terms().iter().enumerate() {
+			if p.targets().is_empty() {
+				constant += c.re;
+			} else {
+				measured.push(i);
+			}
+		}
+
+		let terms = observable.terms();
+		let conflicts: Vec<Vec<usize>> = measured
+			.iter()
+			.map(|&i| {
+				(0..measured.len())
+					.filter(|&j| !grouping.compatible(&terms[i].1, &terms[measured[j]].1))
+					.collect()
+			})
+			.collect();
+		let mut order: Vec<usize> = (0..measured.len()).collect();
+		order.sort_by_key(|&k| std::cmp::Reverse(conflicts[k].len()));
+		let mut colours: Vec<Option<usize>> = vec![None; measured.len()];
+		let mut colour_count = 0;
+		for k in order {
+			let used: Vec<usize> = conflicts[k].iter().filter_map(|&j| colours[j]).collect();
+			let colour = (0..).find(|c| !used.contains(c)).unwrap();
+			colours[k] = Some(colour);
+			colour_count = colour_count.max(colour + 1);
+		}
+
+		let groups = (0..colour_count)
+			.map(|colour| {
+				let indices: Vec<usize> = (0..measured.len())
+					.filter(|&k| colours[k] == Some(colour))
+					.map(|k| measured[k])
+					.collect();
+				let paulis: Vec<&PauliString> = indices.iter().map(|&i| &terms[i].1).collect();
+				let circuit = match grouping {
+					Grouping::QubitWise => qubit_wise_basis_change(n, &paulis),
+					Grouping::Commuting => commuting_basis_change(n, &paulis),
+				};
+				let readouts = paulis
+					.iter()
+					.map(|p| Tableau::conjugate(n, p, &circuit))
+					.collect();
+				MeasurementGroup {
+					coefficients: indices.iter().map(|&i| terms[i].0.re).collect(),
+					terms: indices,
+					circuit,
+					readouts,
+				}
+			})
+			.collect();
+		Self {
+			qubit_count: n,
+			constant,
+			groups,
+		}
+	}
+
+	///Get the coefficient of the identity term
+	pub fn constant(&self) -> f64 {
+		self.constant
+	}
+
+	///Get the measurement groups
+	pub fn groups(&self) -> &[MeasurementGroup] {
+		&self.groups
+	}
+
+	/// Estimate the expectation value by sampling `shots[g]` times from
+	/// `state` after the basis change of each group g.
+	///
+	/// # Panics
+	/// Panics if `shots` does not have one count of at least 2 per group.
+	pub fn estimate(&self, state: &[Complex<f64>], shots: &[u32]) -> Result<Estimate, StateErr> {
+		assert_eq!(
+			shots.len(),
+			self.groups.len(),
+			"{} shot counts were supplied for {} groups",
+			shots.len(),
+			self.groups.len()
+		);
+		let mut value = self.constant;
+		let mut variance = 0.;
+		let mut group_variances = Vec::with_capacity(self.groups.len());
+		for (group, &count) in self.groups.iter().zip(shots) {
+			assert!(count >= 2, "every group needs at least 2 shots");
+			let mut rotated = StateVec::new(self.qubit_count);
+			if state.len() != rotated.as_ref().len() {
+				return Err(StateErr::InconsistentStateLength(
+					rotated.as_ref().len(),
+					state.len(),
+				));
+			}
+			rotated.as_mut().copy_from_slice(state);
+			group.circuit.update_quantum_state(rotated.as_mut())?;
+			let samples: Vec<f64> = rotated
+				.sampling(count)
+				.into_iter()
+				.map(|outcome| group.value(outcome))
+				.collect();
+			let mean = samples.iter().sum::<f64>() / count as f64;
+			let sample_variance =
+				samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (count - 1) as f64;
+			value += mean;
+			variance += sample_variance / count as f64;
+			group_variances.push(sample_variance);
+		}
+		Ok(Estimate {
+			value,
+			variance,
+			group_variances,
+		})
+	}
+
+	/// Get the allocation of `total_shots` proportional to Σ_i |c_i| of each
+	/// group, which is optimal when the variances are unknown and bounded by
+	/// (Σ_i |c_i|)^2.
+	pub fn default_shot_allocation(&self, total_shots: u32) -> Vec<u32> {
+		let norms: Vec<f64> = self.groups.iter().map(|g| g.coefficient_norm()).collect();
+		optimal_shot_allocation(&norms, total_shots)
+	}
+}
+
+/// Get the allocation of `total_shots` minimizing Σ_g σ_g^2 / N_g, i.e. N_g
+/// proportional to the single-shot standard deviation σ_g of group g, rounded
+/// by the largest remainder method. Groups get at least 2 shots when
+/// `total_shots` allows it, and shots are split evenly if all σ_g are 0.
+pub fn optimal_shot_allocation(deviations: &[f64], total_shots: u32) -> Vec<u32> {
+	let count = deviations.len();
+	if count == 0 {
+		return Vec::new();
+	}
+	let minimum = if total_shots as usize >= 2 * count {
+		2
+	} else {
+		0
+	};
+	let free = total_shots - minimum * count as u32;
+	let sum: f64 = deviations.iter().sum();
+	let weights: Vec<f64> = if sum > 0. {
+		deviations.iter().map(|d| d / sum).collect()
+	} else {
+		vec![1. / count as f64; count]
+	};
+	let exact: Vec<f64> = weights.iter().map(|w| w * free as f64).collect();
+	let mut shots: Vec<u32> = exact.iter().map(|e| e.floor() as u32).collect();
+	let mut remainders: Vec<usize> = (0..count).collect();
+	remainders.sort_by(|&a, &b| {
+		let (ra, rb) = (exact[a] - exact[a].floor(), exact[b] - exact[b].floor());
+		rb.total_cmp(&ra)
+	});
+	let assigned: u32 = shots.iter().sum();
+	for &g in remainders.iter().take((free - assigned) as usize) {
+		shots[g] += 1;
+	}
+	shots.iter().map(|s| s + minimum).collect()
+}
+
+/// H on X and S† followed by H on Y, rotating each qubit into the Z basis.
+fn qubit_wise_basis_change(qubit_count: usize, paulis: &[&PauliString]) -> Circuit {
+	let mut circuit = Circuit::new(qubit_count);
+	for q in 0..qubit_count as u32 {
+		match paulis
+			.iter()
+			.map(|p| p.pauli_at(q))
+			.find(|&p| p != Pauli::I)
+		{
+			Some(Pauli::X) => circuit.add_gate(Gate::H(q)),
+			Some(Pauli::Y) => {
+				circuit.add_gate(Gate::Sdag(q));
+				circuit.add_gate(Gate::H(q));
+			}
+			_ => {}
+		}
+	}
+	circuit
+}
+
+/// Diagonalize commuting Pauli strings by a Clifford circuit.
+///
+/// The X parts of independent generators are brought to full rank by H, to
+/// the identity on pivot qubits by `Cnot`, the symmetric Z parts on pivots
+/// are cleared by `Cz` and S†, and H on the pivots makes every generator
+/// Z-type.
+fn commuting_basis_change(qubit_count: usize, paulis: &[&PauliString]) -> Circuit {
+	let n = qubit_count;
+	let mut circuit = Circuit::new(n);
+	// binary symplectic rows (x, z) of independent generators
+	let mut rows: Vec<(Vec<bool>, Vec<bool>)> = Vec::new();
+	{
+		let mut reduced: Vec<Vec<bool>> = Vec::new();
+		for p in paulis {
+			let (x, z) = symplectic(n, p);
+			let mut v: Vec<bool> = x.iter().chain(&z).cloned().collect();
+			for r in &reduced {
+				let pivot = r.iter().position(|&b| b).unwrap();
+				if v[pivot] {
+					v.iter_mut().zip(r).for_each(|(a, &b)| *a ^= b);
+				}
+			}
+			if let Some(pivot) = v.iter().position(|&b| b) {
+				for r in reduced.iter_mut() {
+					if r[pivot] {
+						r.iter_mut().zip(&v).for_each(|(a, &b)| *a ^= b);
+					}
+				}
+				reduced.push(v);
+				rows.push((x, z));
+			}
+		}
+	}
+	let apply = |circuit: &mut Circuit, rows: &mut Vec<(Vec<bool>, Vec<bool>)>, gate: Gate| {
+		for (x, z) in rows.iter_mut() {
+			match gate {
+				Gate::H(q) => std::mem::swap(&mut x[q as usize], &mut z[q as usize]),
+				Gate::Sdag(q) => z[q as usize] ^= x[q as usize],
+				Gate::Cnot(c, t) => {
+					x[t as usize] ^= x[c as usize];
+					z[c as usize] ^= z[t as usize];
+				}
+				Gate::Cz(a, b) => {
+					z[b as usize] ^= x[a as usize];
+					z[a as usize] ^= x[b as usize];
+				}
+				_ => unreachable!(),
+			}
+		}
+		circuit.add_gate(gate);
+	};
+
+	// Gaussian elimination of the X part, moving Z to X by H where needed
+	let mut pivots: Vec<usize> = Vec::new();
+	for i in 0..rows.len() {
+		let pivot = match (0..n).find(|&q| rows[i].0[q] && !pivots.contains(&q)) {
+			Some(q) => q,
+			None => {
+				let q = (0..n)
+					.find(|&q| rows[i].1[q] && !pivots.contains(&q))
+					.expect("generators are independent");
+				apply(&mut circuit, &mut rows, Gate::H(q as u32));
+				q
+			}
+		};
+		for j in 0..rows.len() {
+			if j != i && rows[j].0[pivot] {
+				let (x, z) = rows[i].clone();
+				rows[j].0.iter_mut().zip(&x).for_each(|(a, &b)| *a ^= b);
+				rows[j].1.iter_mut().zip(&z).for_each(|(a, &b)| *a ^= b);
+			}
+		}
+		pivots.push(pivot);
+	}
+	// Row i has X on pivots[i] only after removing the others by CNOT.
+	for (i, &pivot) in pivots.iter().enumerate() {
+		for q in 0..n {
+			if q != pivot && rows[i].0[q] {
+				apply(&mut circuit, &mut rows, Gate::Cnot(pivot as u32, q as u32));
+			}
+		}
+	}
+	for (i, &a) in pivots.iter().enumerate() {
+		for &b in &pivots[i + 1..] {
+			if rows[i].1[b] {
+				apply(&mut circuit, &mut rows, Gate::Cz(a as u32, b as u32));
+			}
+		}
+	}
+	for (i, &pivot) in pivots.iter().enumerate() {
+		if rows[i].1[pivot] {
+			apply(&mut circuit, &mut rows, Gate::Sdag(pivot as u32));
+		}
+	}
+	for &pivot in &pivots {
+		apply(&mut circuit, &mut rows, Gate::H(pivot as u32));
+	}
+	circuit
+}
+
+fn symplectic(qubit_count: usize, pauli: &PauliString) -> (Vec<bool>, Vec<bool>) {
+	let mut x = vec![false; qubit_count];
+	let mut z = vec![false; qubit_count];
+	for (&q, &p) in pauli.targets().iter().zip(pauli.paulis()) {
+		x[q as usize] = matches!(p, Pauli::X | Pauli::Y);
+		z[q as usize] = matches!(p, Pauli::Z | Pauli::Y);
+	}
+	(x, z)
+}
+
+/// A Pauli string with a sign conjugated by Clifford gates (Aaronson and
+/// Gottesman, 2004).
+pub(crate) struct Tableau {
+	x: Vec<bool>,
+	z: Vec<bool>,
+	negative: bool,
+}
+
+impl Tableau {
+	pub(crate) fn new(qubit_count: usize, pauli: &PauliString) -> Self {
+		let (x, z) = symplectic(qubit_count, pauli);
+		Tableau {
+			x,
+			z,
+			negative: false,
+		}
+	}
+
+	/// Get (sign, mask) with U P U† = sign · Π_(q in mask) Z_q for the
+	/// circuit U.
+	///
+	/// # Panics
+	/// Panics if U P U† is not a product of Z.
+	fn conjugate(qubit_count: usize, pauli: &PauliString, circuit: &Circuit) -> (f64, u64) {
+		let mut t = Tableau::new(qubit_count, pauli);
+		for gate in circuit.gates() {
+			t.apply(gate);
+		}
+		t.z_type()
+			.expect("the basis change must diagonalize the term")
+	}
+
+	/// Replace P with G P G† for the Clifford gate G.
+	///
+	/// # Panics
+	/// Panics if the gate is not one of the Pauli gates, H, S, S†, `Cnot` and
+	/// `Cz`.
+	pub(crate) fn apply(&mut self, gate: &Gate) {
+		match *gate {
+			Gate::X(q) => self.negative ^= self.z[q as usize],
+			Gate::Y(q) => self.negative ^= self.x[q as usize] != self.z[q as usize],
+			Gate::Z(q) => self.negative ^= self.x[q as usize],
+			Gate::H(q) => self.h(q as usize),
+			Gate::S(q) => self.s(q as usize),
+			Gate::Sdag(q) => {
+				for _ in 0..3 {
+					self.s(q as usize);
+				}
+			}
+			Gate::Cnot(c, t) => self.cnot(c as usize, t as usize),
+			Gate::Cz(a, b) => {
+				self.h(b as usize);
+				self.cnot(a as usize, b as usize);
+				self.h(b as usize);
+			}
+			_ => panic!("{:?} is not supported in a tableau", gate),
+		}
+	}
+
+	/// Get (sign, mask) with P = sign · Π_(q in mask) Z_q, or `None` if P has
+	/// X or Y.
+	pub(crate) fn z_type(&self) -> Option<(f64, u64)> {
+		if self.x.iter().any(|&b| b) {
+			return None;
+		}
+		let mask = self
+			.z
+			.iter()
+			.enumerate()
+			.filter(|&(_, &b)| b)
+			.map(|(q, _)| 1u64 << q)
+			.sum();
+		Some((if self.negative { -1. } else { 1. }, mask))
+	}
+
+	fn h(&mut self, q: usize) {
+		self.negative ^= self.x[q] && self.z[q];
+		std::mem::swap(&mut self.x[q], &mut self.z[q]);
+	}
+
+	fn s(&mut self, q: usize) {
+		self.negative ^= self.x[q] && self.z[q];
+		self.z[q] ^= self.x[q];
+	}
+
+	fn cnot(&mut self, c: usize, t: usize) {
+		self.negative ^= self.x[c] && self.z[t] && (self.x[t] == self.z[c]);
+		self.x[t] ^= self.x[c];
+		self.z[c] ^= self.z[t];
+	}
+}
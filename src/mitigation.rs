@@ -0,0 +1,467 @@
+use crate::circuit::{Circuit, Gate};
+use crate::noise::{Channel, NoisyCircuit, Operation};
+use crate::pauli::{pauli_list, Pauli};
+use std::fmt;
+
+/// Eigenvalues of noise channels below this are treated as 0 in
+/// `ProbabilisticErrorCancellation`.
+const EPS: f64 = 1e-10;
+
+#[non_exhaustive]
+pub enum MitigationErr {
+	/// Scale factors must be at least 1 and distinct.
+	InvalidScaleFactor(f64),
+	/// The extrapolation needs more distinct scale factors.
+	TooFewScaleFactors(usize),
+	/// Number of scale factors and number of values
+	InconsistentDataLength(usize, usize),
+	/// Values for the exponential fit must lie on one side of the asymptote.
+	InvalidExponentialData(f64),
+	/// The channel at the index of `NoisyCircuit::operations` is not a Pauli
+	/// channel.
+	NonPauliChannel(usize),
+	/// The channel at the index of `NoisyCircuit::operations` cannot be
+	/// inverted.
+	NonInvertibleChannel(usize),
+}
+
+impl fmt::Display for MitigationErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			MitigationErr::InvalidScaleFactor(s) => write!(
+				f,
+				"Invalid scale factor: scale factors must be distinct and at least 1, but {} was supplied",
+				s
+			),
+			MitigationErr::TooFewScaleFactors(count) => write!(
+				f,
+				"Too few scale factors: {} scale factors are not enough for the extrapolation",
+				count
+			),
+			MitigationErr::InconsistentDataLength(scales, values) => write!(
+				f,
+				"Inconsistent data length: {} scale factors, but {} values",
+				scales, values
+			),
+			MitigationErr::InvalidExponentialData(value) => write!(
+				f,
+				"Invalid exponential data: {} is not on the same side of the asymptote as the other values",
+				value
+			),
+			MitigationErr::NonPauliChannel(i) => {
+				write!(
+					f,
+					"Non-Pauli channel: operation {} is not a Pauli channel",
+					i
+				)
+			}
+			MitigationErr::NonInvertibleChannel(i) => {
+				write!(
+					f,
+					"Non-invertible channel: operation {} cannot be inverted",
+					i
+				)
+			}
+		}
+	}
+}
+
+impl fmt::Debug for MitigationErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		<Self as fmt::Display>::fmt(self, f)
+	}
+}
+
+/// Unitary folding G ↦ G (G† G)^k, which scales the noise of a circuit
+/// without changing its unitary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Folding {
+	/// Fold the whole circuit, then the last gates for the remaining scale.
+	Global,
+	/// Fold every gate, then the first gates once more for the remaining
+	/// scale.
+	Local,
+}
+
+/// Fold `circuit` so that its gate count is about `scale_factor` times the
+/// original, rounding the number of partially folded gates to the nearest
+/// integer.
+///
+/// ```
+/// # use qurs::circuit::{Circuit, Gate};
+/// # use qurs::mitigation::{fold, Folding};
+/// let mut circuit = Circuit::new(1);
+/// circuit.add_gate(Gate::H(0));
+/// circuit.add_gate(Gate::S(0));
+/// let folded = fold(&circuit, 3., Folding::Global).unwrap();
+/// assert_eq!(
+///     folded.gates(),
+///     &[Gate::H(0), Gate::S(0), Gate::Sdag(0), Gate::H(0), Gate::H(0), Gate::S(0)]
+/// );
+/// ```
+pub fn fold(
+	circuit: &Circuit,
+	scale_factor: f64,
+	folding: Folding,
+) -> Result<Circuit, MitigationErr> {
+	if scale_factor.is_nan() || scale_factor < 1. {
+		return Err(MitigationErr::InvalidScaleFactor(scale_factor));
+	}
+	let gates = circuit.gates();
+	let full = ((scale_factor - 1.) / 2.).floor() as usize;
+	let partial = (((scale_factor - 1. - 2. * full as f64) * gates.len() as f64 / 2.).round()
+		as usize)
+		.min(gates.len());
+	let mut folded = Circuit::new(circuit.qubit_count());
+	match folding {
+		Folding::Global => {
+			folded.merge_circuit(circuit);
+			for _ in 0..full {
+				folded.merge_circuit(&circuit.inverse());
+				folded.merge_circuit(circuit);
+			}
+			let tail = &gates[gates.len() - partial..];
+			for gate in tail.iter().rev() {
+				folded.add_gate(gate.dagger());
+			}
+			for gate in tail {
+				folded.add_gate(gate.clone());
+			}
+		}
+		Folding::Local => {
+			for (i, gate) in gates.iter().enumerate() {
+				folded.add_gate(gate.clone());
+				let count = if i < partial { full + 1 } else { full };
+				for _ in 0..count {
+					folded.add_gate(gate.dagger());
+					folded.add_gate(gate.clone());
+				}
+			}
+		}
+	}
+	Ok(folded)
+}
+
+/// Extrapolation of values at scaled noise to the zero-noise limit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Extrapolation {
+	/// Polynomial of degree m - 1 through all m points
+	Richardson,
+	/// Least-squares line
+	Linear,
+	/// Least-squares fit of `asymptote` + a e^(bλ), linear in log|y -
+	/// `asymptote`|
+	Exponential { asymptote: f64 },
+}
+
+impl Extrapolation {
+	/// Get the value at the scale factor 0 of the curve fitted to `values`
+	/// at `scale_factors`.
+	///
+	/// ```
+	/// # use qurs::mitigation::Extrapolation;
+	/// let value = Extrapolation::Richardson
+	///     .extrapolate(&[1., 2., 3.], &[0.5, 0.3, 0.2])
+	///     .unwrap();
+	/// assert!((value - 0.8).abs() < 1e-12);
+	/// ```
+	pub fn extrapolate(&self, scale_factors: &[f64], values: &[f64]) -> Result<f64, MitigationErr> {
+		if scale_factors.len() != values.len() {
+			return Err(MitigationErr::InconsistentDataLength(
+				scale_factors.len(),
+				values.len(),
+			));
+		}
+		for (i, &s) in scale_factors.iter().enumerate() {
+			if !s.is_finite() || scale_factors[..i].contains(&s) {
+				return Err(MitigationErr::InvalidScaleFactor(s));
+			}
+		}
+		if scale_factors.len() < 2 {
+			return Err(MitigationErr::TooFewScaleFactors(scale_factors.len()));
+		}
+		match *self {
+			Extrapolation::Richardson => Ok(values
+				.iter()
+				.enumerate()
+				.map(|(i, y)| {
+					let weight: f64 = scale_factors
+						.iter()
+						.enumerate()
+						.filter(|&(j, _)| j != i)
+						.map(|(_, &x)| x / (x - scale_factors[i]))
+						.product();
+					weight * y
+				})
+				.sum()),
+			Extrapolation::Linear => Ok(linear_fit(scale_factors, values).0),
+			Extrapolation::Exponential { asymptote } => {
+				let sign = (values[0] - asymptote).signum();
+				let mut logs = Vec::with_capacity(values.len());
+				for &y in values {
+					let d = (y - asymptote) * sign;
+					if d.is_nan() || d <= 0. {
+						return Err(MitigationErr::InvalidExponentialData(y));
+					}
+					logs.push(d.ln());
+				}
+				Ok(asymptote + sign * linear_fit(scale_factors, &logs).0.exp())
+			}
+		}
+	}
+}
+
+/// Zero-noise extrapolation: run the circuit folded at several scale factors
+/// and extrapolate the results to zero noise.
+///
+/// ```
+/// # use qurs::circuit::{Circuit, Gate};
+/// # use qurs::mitigation::{Extrapolation, Folding, ZeroNoiseExtrapolation};
+/// let zne = ZeroNoiseExtrapolation::new(&[1., 3.], Folding::Global, Extrapolation::Linear).unwrap();
+/// let mut circuit = Circuit::new(1);
+/// circuit.add_gate(Gate::X(0));
+/// // each gate loses 10% of the signal
+/// let value = zne.estimate(&circuit, |c| 1. - 0.1 * c.gate_count() as f64).unwrap();
+/// assert!((value - 1.).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZeroNoiseExtrapolation {
+	scale_factors: Vec<f64>,
+	folding: Folding,
+	extrapolation: Extrapolation,
+}
+
+impl ZeroNoiseExtrapolation {
+	pub fn new(
+		scale_factors: &[f64],
+		folding: Folding,
+		extrapolation: Extrapolation,
+	) -> Result<Self, MitigationErr> {
+		for (i, &s) in scale_factors.iter().enumerate() {
+			if s.is_nan() || s < 1. || scale_factors[..i].contains(&s) {
+				return Err(MitigationErr::InvalidScaleFactor(s));
+			}
+		}
+		if scale_factors.len() < 2 {
+			return Err(MitigationErr::TooFewScaleFactors(scale_factors.len()));
+		}
+		Ok(Self {
+			scale_factors: scale_factors.to_vec(),
+			folding,
+			extrapolation,
+		})
+	}
+
+	///Get requested scale factors
+	pub fn scale_factors(&self) -> &[f64] {
+		&self.scale_factors
+	}
+
+	/// Get `circuit` folded at each scale factor.
+	pub fn circuits(&self, circuit: &Circuit) -> Vec<Circuit> {
+		self.scale_factors
+			.iter()
+			.map(|&s| fold(circuit, s, self.folding).unwrap())
+			.collect()
+	}
+
+	/// Extrapolate `executor(c)` for each of `circuits(circuit)`. The values
+	/// are extrapolated at the actual scale factors, the ratios of the gate
+	/// counts of the folded circuits to that of `circuit`.
+	pub fn estimate<F>(&self, circuit: &Circuit, mut executor: F) -> Result<f64, MitigationErr>
+	where
+		F: FnMut(&Circuit) -> f64,
+	{
+		let circuits = self.circuits(circuit);
+		let scale_factors: Vec<f64> = if circuit.gate_count() == 0 {
+			self.scale_factors.clone()
+		} else {
+			circuits
+				.iter()
+				.map(|c| c.gate_count() as f64 / circuit.gate_count() as f64)
+				.collect()
+		};
+		let values: Vec<f64> = circuits.iter().map(&mut executor).collect();
+		self.extrapolation.extrapolate(&scale_factors, &values)
+	}
+}
+
+/// Estimate by `ProbabilisticErrorCancellation`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PecEstimate {
+	pub value: f64,
+	/// Standard error of the mean over the samples
+	pub standard_error: f64,
+}
+
+/// Probabilistic error cancellation of the Pauli channels of a
+/// `NoisyCircuit`.
+///
+/// The inverse of each Pauli channel N is the quasi-probability
+/// decomposition N^-1 = Σ_P q_P P(·)P over Pauli strings P, with
+/// γ = Σ_P |q_P| ≥ 1. Each sample inserts P after N with probability
+/// |q_P|/γ and weights the result by γ sign(q_P), so the mean over samples
+/// is an unbiased estimate of the noiseless value. Its variance grows as the
+/// square of the overhead `gamma()`, the product of γ over the channels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProbabilisticErrorCancellation {
+	circuit: NoisyCircuit,
+	/// (index of the operation, targets, quasi-probabilities) of each channel
+	inverses: Vec<(usize, Vec<u32>, Vec<f64>)>,
+}
+
+impl ProbabilisticErrorCancellation {
+	/// Build the decompositions of the inverses of the channels of
+	/// `circuit`. Every channel must be a Pauli channel.
+	pub fn new(circuit: &NoisyCircuit) -> Result<Self, MitigationErr> {
+		let mut inverses = Vec::new();
+		for (i, operation) in circuit.operations().iter().enumerate() {
+			if let Operation::Channel(channel) = operation {
+				inverses.push((
+					i,
+					channel.targets().to_vec(),
+					inverse_quasi_probabilities(i, channel)?,
+				));
+			}
+		}
+		Ok(Self {
+			circuit: circuit.clone(),
+			inverses,
+		})
+	}
+
+	/// Get the overhead γ, the product of Σ_P |q_P| over the channels. The
+	/// number of samples for a given precision grows as γ².
+	pub fn gamma(&self) -> f64 {
+		self.inverses
+			.iter()
+			.map(|(_, _, q)| q.iter().map(|x| x.abs()).sum::<f64>())
+			.product()
+	}
+
+	/// Sample a circuit with Pauli corrections after the channels. Returns
+	/// the circuit and its weight ±γ.
+	pub fn sample(&self) -> (NoisyCircuit, f64) {
+		let mut corrections = Vec::with_capacity(self.inverses.len());
+		let mut weight = 1.;
+		for (_, targets, q) in &self.inverses {
+			let norm: f64 = q.iter().map(|x| x.abs()).sum();
+			let mut r = rand::random::<f64>() * norm;
+			let index = q
+				.iter()
+				.position(|x| {
+					r -= x.abs();
+					r < 0.
+				})
+				.unwrap_or(q.len() - 1);
+			weight *= norm * q[index].signum();
+			corrections.push((targets, index));
+		}
+		let mut sampled = NoisyCircuit::new(self.circuit.qubit_count());
+		let mut corrections = corrections.into_iter().peekable();
+		for operation in self.circuit.operations() {
+			match operation {
+				Operation::Gate(gate) => sampled.add_gate(gate.clone()),
+				Operation::Channel(channel) => {
+					sampled.add_channel(channel.clone());
+					let (targets, index) = corrections.next().unwrap();
+					for (&q, p) in targets.iter().zip(pauli_list(targets.len(), index)) {
+						match p {
+							Pauli::I => {}
+							Pauli::X => sampled.add_gate(Gate::X(q)),
+							Pauli::Y => sampled.add_gate(Gate::Y(q)),
+							Pauli::Z => sampled.add_gate(Gate::Z(q)),
+						}
+					}
+				}
+			}
+		}
+		(sampled, weight)
+	}
+
+	/// Estimate the noiseless value of `executor` from `sample_count`
+	/// samples.
+	///
+	/// # Panics
+	/// Panics if `sample_count` is less than 2.
+	pub fn estimate<F>(&self, sample_count: usize, mut executor: F) -> PecEstimate
+	where
+		F: FnMut(&NoisyCircuit) -> f64,
+	{
+		assert!(sample_count >= 2, "at least 2 samples are needed");
+		let values: Vec<f64> = (0..sample_count)
+			.map(|_| {
+				let (circuit, weight) = self.sample();
+				weight * executor(&circuit)
+			})
+			.collect();
+		let mean = values.iter().sum::<f64>() / sample_count as f64;
+		let variance =
+			values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (sample_count - 1) as f64;
+		PecEstimate {
+			value: mean,
+			standard_error: (variance / sample_count as f64).sqrt(),
+		}
+	}
+}
+
+/// Get the quasi-probabilities q_P = 4^-k Σ_Q s(P, Q)/λ_Q of the inverse of
+/// the Pauli channel, where λ_Q = Σ_P p_P s(P, Q) are its eigenvalues and
+/// s(P, Q) is 1 if P and Q commute and -1 otherwise.
+fn inverse_quasi_probabilities(index: usize, channel: &Channel) -> Result<Vec<f64>, MitigationErr> {
+	let probabilities = channel
+		.pauli_probabilities()
+		.ok_or(MitigationErr::NonPauliChannel(index))?;
+	let k = channel.targets().len();
+	let count = probabilities.len();
+	let sign = |a: usize, b: usize| -> f64 {
+		let anticommuting = (0..k)
+			.filter(|q| {
+				let (s, t) = ((a >> (2 * q)) & 3, (b >> (2 * q)) & 3);
+				s != 0 && t != 0 && s != t
+			})
+			.count();
+		if anticommuting & 1 == 0 {
+			1.
+		} else {
+			-1.
+		}
+	};
+	let mut inverse_eigenvalues = Vec::with_capacity(count);
+	for q in 0..count {
+		let lambda: f64 = probabilities
+			.iter()
+			.enumerate()
+			.map(|(p, x)| x * sign(p, q))
+			.sum();
+		if lambda.abs() < EPS {
+			return Err(MitigationErr::NonInvertibleChannel(index));
+		}
+		inverse_eigenvalues.push(1. / lambda);
+	}
+	Ok((0..count)
+		.map(|p| {
+			inverse_eigenvalues
+				.iter()
+				.enumerate()
+				.map(|(q, l)| sign(p, q) * l)
+				.sum::<f64>()
+				/ count as f64
+		})
+		.collect())
+}
+
+/// Get the (intercept, slope) of the least-squares line through the points.
+fn linear_fit(x: &[f64], y: &[f64]) -> (f64, f64) {
+	let n = x.len() as f64;
+	let mean_x = x.iter().sum::<f64>() / n;
+	let mean_y = y.iter().sum::<f64>() / n;
+	let covariance: f64 = x
+		.iter()
+		.zip(y)
+		.map(|(a, b)| (a - mean_x) * (b - mean_y))
+		.sum();
+	let variance: f64 = x.iter().map(|a| (a - mean_x).powi(2)).sum();
+	let slope = covariance / variance;
+	(mean_y - slope * mean_x, slope)
+}
@@ -0,0 +1,614 @@
+use crate::circuit::{Circuit, Gate};
+use crate::observable::Observable;
+use crate::pauli::{pauli_list, pauli_matrix_element, Pauli};
+use crate::state::StateErr;
+use crate::Complex;
+use std::fmt;
+
+/// Tolerance of the completeness of Kraus operators.
+const EPS: f64 = 1e-8;
+
+#[non_exhaustive]
+pub enum NoiseErr {
+	/// The targets are empty or have duplicates.
+	InvalidTargetList(Vec<u32>),
+	/// The Kraus operators have the wrong size or do not satisfy Σ K†K = I.
+	InvalidKrausOperators,
+	/// The probability or rate is out of range.
+	InvalidProbability(f64),
+}
+
+impl fmt::Display for NoiseErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			NoiseErr::InvalidTargetList(list) => write!(f, "Invalid target list: {:?}", list),
+			NoiseErr::InvalidKrausOperators => write!(
+				f,
+				"Invalid Kraus operators: the operators must be 2^k×2^k matrices with Σ K†K = I"
+			),
+			NoiseErr::InvalidProbability(p) => {
+				write!(f, "Invalid probability: {} is out of range", p)
+			}
+		}
+	}
+}
+
+impl fmt::Debug for NoiseErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		<Self as fmt::Display>::fmt(self, f)
+	}
+}
+
+/// Quantum channel ρ ↦ Σ_i K_i ρ K_i† given by Kraus operators K_i on
+/// `targets`. Each operator is a row-major 2^k×2^k matrix where `targets[0]`
+/// is the least significant bit of the matrix indexes, as in
+/// `Gate::DenseMatrix`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Channel {
+	targets: Vec<u32>,
+	kraus: Vec<Vec<Complex<f64>>>,
+}
+
+impl Channel {
+	/// Build the channel of the Kraus operators `kraus` on `targets`.
+	pub fn new(targets: &[u32], kraus: Vec<Vec<Complex<f64>>>) -> Result<Self, NoiseErr> {
+		check_targets(targets)?;
+		let dim = 1 << targets.len();
+		if kraus.is_empty() || kraus.iter().any(|k| k.len() != dim * dim) {
+			return Err(NoiseErr::InvalidKrausOperators);
+		}
+		for r in 0..dim {
+			for c in 0..dim {
+				let sum: Complex<f64> = kraus
+					.iter()
+					.map(|k| {
+						(0..dim)
+							.map(|i| k[i * dim + r].conj() * k[i * dim + c])
+							.sum::<Complex<f64>>()
+					})
+					.sum();
+				let identity = if r == c { 1. } else { 0. };
+				if (sum - identity).norm() > EPS {
+					return Err(NoiseErr::InvalidKrausOperators);
+				}
+			}
+		}
+		Ok(Self {
+			targets: targets.to_vec(),
+			kraus,
+		})
+	}
+
+	/// Pauli channel applying the Pauli string P on `targets` with probability
+	/// `probabilities[P]`. Pauli strings are indexed by Σ_q t_q 4^q for the
+	/// Pauli type t_q on `targets[q]`.
+	pub fn pauli(targets: &[u32], probabilities: &[f64]) -> Result<Self, NoiseErr> {
+		check_targets(targets)?;
+		if probabilities.len() != 1 << (2 * targets.len()) {
+			return Err(NoiseErr::InvalidKrausOperators);
+		}
+		if let Some(&p) = probabilities.iter().find(|&&p| !(0. ..=1.).contains(&p)) {
+			return Err(NoiseErr::InvalidProbability(p));
+		}
+		let kraus = probabilities
+			.iter()
+			.enumerate()
+			.filter(|(_, &p)| p > 0.)
+			.map(|(index, &p)| {
+				let paulis = pauli_list(targets.len(), index);
+				let dim = 1 << targets.len();
+				(0..dim * dim)
+					.map(|i| pauli_matrix_element(&paulis, i / dim, i % dim) * p.sqrt())
+					.collect()
+			})
+			.collect();
+		Self::new(targets, kraus)
+	}
+
+	/// Depolarizing channel applying each of the 4^k - 1 non-identity Pauli
+	/// strings on `targets` with probability p / (4^k - 1).
+	pub fn depolarizing(targets: &[u32], p: f64) -> Result<Self, NoiseErr> {
+		let count = 1 << (2 * targets.len());
+		let mut probabilities = vec![p / (count - 1) as f64; count];
+		probabilities[0] = 1. - p;
+		Self::pauli(targets, &probabilities)
+	}
+
+	/// Apply X on `target` with probability `p`.
+	pub fn bit_flip(target: u32, p: f64) -> Result<Self, NoiseErr> {
+		Self::pauli(&[target], &[1. - p, p, 0., 0.])
+	}
+
+	/// Apply Z on `target` with probability `p`.
+	pub fn phase_flip(target: u32, p: f64) -> Result<Self, NoiseErr> {
+		Self::pauli(&[target], &[1. - p, 0., 0., p])
+	}
+
+	/// Decay of |1> to |0> on `target` with probability `gamma`.
+	pub fn amplitude_damping(target: u32, gamma: f64) -> Result<Self, NoiseErr> {
+		if !(0. ..=1.).contains(&gamma) {
+			return Err(NoiseErr::InvalidProbability(gamma));
+		}
+		let zero = Complex::new(0., 0.);
+		let one = Complex::new(1., 0.);
+		Self::new(
+			&[target],
+			vec![
+				vec![one, zero, zero, Complex::new((1. - gamma).sqrt(), 0.)],
+				vec![zero, Complex::new(gamma.sqrt(), 0.), zero, zero],
+			],
+		)
+	}
+
+	/// Loss of coherence on `target` scaling the off-diagonal elements by
+	/// sqrt(1 - `lambda`).
+	pub fn phase_damping(target: u32, lambda: f64) -> Result<Self, NoiseErr> {
+		if !(0. ..=1.).contains(&lambda) {
+			return Err(NoiseErr::InvalidProbability(lambda));
+		}
+		let zero = Complex::new(0., 0.);
+		let one = Complex::new(1., 0.);
+		Self::new(
+			&[target],
+			vec![
+				vec![one, zero, zero, Complex::new((1. - lambda).sqrt(), 0.)],
+				vec![zero, zero, zero, Complex::new(lambda.sqrt(), 0.)],
+			],
+		)
+	}
+
+	/// Relaxation of `target` with the times `t1` and `t2` for the duration
+	/// `time`: amplitude damping with γ = 1 - e^(-t/T1) followed by phase
+	/// damping, so that the off-diagonal elements decay as e^(-t/T2).
+	/// Requires T2 ≤ 2 T1.
+	pub fn thermal_relaxation(target: u32, t1: f64, t2: f64, time: f64) -> Result<Self, NoiseErr> {
+		if t1.is_nan() || t1 <= 0. {
+			return Err(NoiseErr::InvalidProbability(t1));
+		}
+		if t2.is_nan() || t2 <= 0. || t2 > 2. * t1 {
+			return Err(NoiseErr::InvalidProbability(t2));
+		}
+		if time.is_nan() || time < 0. {
+			return Err(NoiseErr::InvalidProbability(time));
+		}
+		let damping = Self::amplitude_damping(target, 1. - (-time / t1).exp())?;
+		// 1/T_φ = 1/T2 - 1/(2 T1), and phase damping scales the off-diagonal
+		// elements by sqrt(1 - λ) = e^(-t/T_φ)
+		let dephasing_rate = (1. / t2 - 0.5 / t1).max(0.);
+		let dephasing = Self::phase_damping(target, 1. - (-2. * time * dephasing_rate).exp())?;
+		Ok(damping.then(&dephasing))
+	}
+
+	/// Get the channel applying `self` and then `other`, which must have the
+	/// same targets.
+	///
+	/// # Panics
+	/// Panics if the targets differ.
+	pub fn then(&self, other: &Channel) -> Channel {
+		assert_eq!(
+			self.targets, other.targets,
+			"channels must have the same targets"
+		);
+		let dim = 1 << self.targets.len();
+		let mut kraus = Vec::with_capacity(self.kraus.len() * other.kraus.len());
+		for b in &other.kraus {
+			for a in &self.kraus {
+				let mut product = vec![Complex::new(0., 0.); dim * dim];
+				for i in 0..dim {
+					for k in 0..dim {
+						for j in 0..dim {
+							product[i * dim + j] += b[i * dim + k] * a[k * dim + j];
+						}
+					}
+				}
+				if product.iter().any(|x| x.norm_sqr() > 0.) {
+					kraus.push(product);
+				}
+			}
+		}
+		Channel {
+			targets: self.targets.clone(),
+			kraus,
+		}
+	}
+
+	/// Get the same channel acting on qubit `f(q)` instead of each target `q`.
+	pub fn map_targets<F>(&self, f: F) -> Channel
+	where
+		F: Fn(u32) -> u32,
+	{
+		Channel {
+			targets: self.targets.iter().map(|&q| f(q)).collect(),
+			kraus: self.kraus.clone(),
+		}
+	}
+
+	///Get target qubits
+	pub fn targets(&self) -> &[u32] {
+		&self.targets
+	}
+
+	///Get Kraus operators
+	pub fn kraus_operators(&self) -> &[Vec<Complex<f64>>] {
+		&self.kraus
+	}
+
+	/// Get the probabilities of the Pauli strings, indexed as in `pauli`, if
+	/// this channel is a Pauli channel.
+	pub fn pauli_probabilities(&self) -> Option<Vec<f64>> {
+		let k = self.targets.len();
+		let dim = 1 << k;
+		let paulis: Vec<Vec<Pauli>> = (0..1 << (2 * k))
+			.map(|index| pauli_list(k, index))
+			.collect();
+		// coefficients c_P = tr(P K)/2^k of K = Σ_P c_P P
+		let coefficients: Vec<Vec<Complex<f64>>> =
+			self.kraus
+				.iter()
+				.map(|kraus| {
+					paulis
+						.iter()
+						.map(|p| {
+							(0..dim * dim)
+								.map(|i| pauli_matrix_element(p, i % dim, i / dim) * kraus[i])
+								.sum::<Complex<f64>>() / dim as f64
+						})
+						.collect()
+				})
+				.collect();
+		// the channel is a Pauli channel iff its χ matrix Σ_K c_P c_Q* is diagonal
+		for a in 0..paulis.len() {
+			for b in 0..a {
+				let chi: Complex<f64> = coefficients.iter().map(|c| c[a] * c[b].conj()).sum();
+				if chi.norm() > EPS {
+					return None;
+				}
+			}
+		}
+		Some(
+			(0..paulis.len())
+				.map(|a| coefficients.iter().map(|c| c[a].norm_sqr()).sum())
+				.collect(),
+		)
+	}
+
+	/// Apply one randomly chosen Kraus operator K_i to the normalized `state`
+	/// with probability ||K_i ψ||² and normalize the result. If rounding
+	/// leaves no operator chosen, the last one with a nonzero probability is
+	/// applied.
+	pub fn update_quantum_state(&self, state: &mut [Complex<f64>]) {
+		let mut r = rand::random::<f64>();
+		let mut chosen = None;
+		for kraus in &self.kraus {
+			let mut candidate = state.to_vec();
+			self.kraus_gate(kraus, 0)
+				.update_quantum_state(&mut candidate);
+			let probability: f64 = candidate.iter().map(|a| a.norm_sqr()).sum();
+			if probability > 0. {
+				chosen = Some((candidate, probability));
+				r -= probability;
+				if r < 0. {
+					break;
+				}
+			}
+		}
+		if let Some((candidate, probability)) = chosen {
+			let norm = probability.sqrt();
+			for (s, c) in state.iter_mut().zip(&candidate) {
+				*s = c / norm;
+			}
+		}
+	}
+
+	/// Get `kraus` as a gate on the targets shifted by `offset`.
+	fn kraus_gate(&self, kraus: &[Complex<f64>], offset: u32) -> Gate {
+		Gate::DenseMatrix {
+			targets: self.targets.iter().map(|q| q + offset).collect(),
+			matrix: kraus.to_vec(),
+		}
+	}
+}
+
+/// Mixed state of `qubit_count` qubits stored as a row-major 2^n×2^n matrix.
+///
+/// ```
+/// # use qurs::circuit::Gate;
+/// # use qurs::noise::{Channel, DensityMatrix};
+/// let mut rho = DensityMatrix::new(1);
+/// rho.apply_gate(&Gate::X(0));
+/// rho.apply_channel(&Channel::amplitude_damping(0, 0.25).unwrap());
+/// assert!((rho.probabilities()[0] - 0.25).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DensityMatrix {
+	qubit_count: usize,
+	data: Vec<Complex<f64>>,
+}
+
+impl DensityMatrix {
+	/// |0...0><0...0|
+	pub fn new(qubit_count: usize) -> Self {
+		let mut data = vec![Complex::new(0., 0.); 1 << (2 * qubit_count)];
+		data[0] = Complex::new(1., 0.);
+		Self { qubit_count, data }
+	}
+
+	/// |ψ><ψ| of the pure `state`
+	///
+	/// # Panics
+	/// Panics if the length of `state` is not a power of 2.
+	pub fn from_state(state: &[Complex<f64>]) -> Self {
+		assert!(
+			state.len().is_power_of_two(),
+			"the state length must be a power of 2, but is {}",
+			state.len()
+		);
+		let qubit_count = state.len().trailing_zeros() as usize;
+		let data = state
+			.iter()
+			.flat_map(|a| state.iter().map(move |b| a * b.conj()))
+			.collect();
+		Self { qubit_count, data }
+	}
+
+	///Get qubit count
+	pub fn qubit_count(&self) -> usize {
+		self.qubit_count
+	}
+
+	///Get the row-major matrix
+	pub fn matrix(&self) -> &[Complex<f64>] {
+		&self.data
+	}
+
+	/// ρ ↦ UρU† for the matrix U of `gate`
+	pub fn apply_gate(&mut self, gate: &Gate) {
+		// with the row index in the upper qubits of the vectorized matrix,
+		// ρ ↦ UρU† is U on the upper qubits and U* on the lower ones
+		let n = self.qubit_count as u32;
+		gate.map_qubits(|q| q + n)
+			.update_quantum_state(&mut self.data);
+		Gate::DenseMatrix {
+			targets: gate.qubits(),
+			matrix: gate.matrix().iter().map(|x| x.conj()).collect(),
+		}
+		.update_quantum_state(&mut self.data);
+	}
+
+	/// ρ ↦ Σ_i K_i ρ K_i†
+	pub fn apply_channel(&mut self, channel: &Channel) {
+		let n = self.qubit_count as u32;
+		let mut result = vec![Complex::new(0., 0.); self.data.len()];
+		for kraus in &channel.kraus {
+			let mut term = self.data.clone();
+			channel.kraus_gate(kraus, n).update_quantum_state(&mut term);
+			let conjugate: Vec<Complex<f64>> = kraus.iter().map(|x| x.conj()).collect();
+			channel
+				.kraus_gate(&conjugate, 0)
+				.update_quantum_state(&mut term);
+			for (r, t) in result.iter_mut().zip(&term) {
+				*r += t;
+			}
+		}
+		self.data = result;
+	}
+
+	/// Apply the gates of `circuit`.
+	pub fn apply_circuit(&mut self, circuit: &Circuit) -> Result<(), StateErr> {
+		if circuit.qubit_count() != self.qubit_count {
+			return Err(StateErr::InconsistentStateLength(
+				1 << circuit.qubit_count(),
+				1 << self.qubit_count,
+			));
+		}
+		for gate in circuit.gates() {
+			self.apply_gate(gate);
+		}
+		Ok(())
+	}
+
+	/// tr(ρ)
+	pub fn trace(&self) -> f64 {
+		let dim = 1 << self.qubit_count;
+		(0..dim).map(|i| self.data[i * dim + i].re).sum()
+	}
+
+	/// tr(ρ²)
+	pub fn purity(&self) -> f64 {
+		self.data.iter().map(|x| x.norm_sqr()).sum()
+	}
+
+	/// <ψ|ρ|ψ> for the pure `state`
+	pub fn fidelity(&self, state: &[Complex<f64>]) -> Result<f64, StateErr> {
+		let dim = 1 << self.qubit_count;
+		if state.len() != dim {
+			return Err(StateErr::InconsistentStateLength(dim, state.len()));
+		}
+		let mut sum = Complex::new(0., 0.);
+		for r in 0..dim {
+			for c in 0..dim {
+				sum += state[r].conj() * self.data[r * dim + c] * state[c];
+			}
+		}
+		Ok(sum.re)
+	}
+
+	/// Get the probability of each computational basis state.
+	pub fn probabilities(&self) -> Vec<f64> {
+		let dim = 1 << self.qubit_count;
+		(0..dim).map(|i| self.data[i * dim + i].re).collect()
+	}
+
+	/// Sample computational basis states `sampling_count` times.
+	pub fn sampling(&self, sampling_count: u32) -> Vec<u64> {
+		let probabilities = self.probabilities();
+		(0..sampling_count)
+			.map(|_| {
+				let mut r = rand::random::<f64>();
+				probabilities
+					.iter()
+					.position(|p| {
+						r -= p;
+						r < 0.
+					})
+					.unwrap_or(probabilities.len() - 1) as u64
+			})
+			.collect()
+	}
+
+	/// tr(Oρ) for the observable O
+	pub fn expectation_value(&self, observable: &Observable) -> Result<Complex<f64>, StateErr> {
+		if observable.qubit_count() > self.qubit_count {
+			return Err(StateErr::InvalidTargetQubitIndex(
+				observable.qubit_count() - 1,
+			));
+		}
+		let dim = 1 << self.qubit_count;
+		let mut value = Complex::new(0., 0.);
+		for (coefficient, pauli) in observable.terms() {
+			let flip = pauli
+				.targets()
+				.iter()
+				.zip(pauli.paulis())
+				.filter(|(_, &p)| p == Pauli::X || p == Pauli::Y)
+				.fold(0, |mask, (&q, _)| mask | 1 << q);
+			// tr(Pρ) = Σ_c <c ⊕ x|P|c> ρ[c, c ⊕ x]
+			let mut sum = Complex::new(0., 0.);
+			for c in 0..dim {
+				let r = c ^ flip;
+				let element: Complex<f64> = pauli
+					.targets()
+					.iter()
+					.zip(pauli.paulis())
+					.map(|(&q, p)| p.matrix()[((r >> q) & 1) * 2 + ((c >> q) & 1)])
+					.product();
+				sum += element * self.data[c * dim + r];
+			}
+			value += coefficient * sum;
+		}
+		Ok(value)
+	}
+}
+
+/// Element of a `NoisyCircuit`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+	Gate(Gate),
+	Channel(Channel),
+}
+
+/// Circuit of gates and noise channels, simulated exactly on a
+/// `DensityMatrix` or by sampling a trajectory on a state vector.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoisyCircuit {
+	qubit_count: usize,
+	operations: Vec<Operation>,
+}
+
+impl NoisyCircuit {
+	pub fn new(qubit_count: usize) -> Self {
+		Self {
+			qubit_count,
+			operations: Vec::new(),
+		}
+	}
+
+	/// Build the noisy circuit of the gates of `circuit` with the channels
+	/// `noise(gate)` following each gate.
+	pub fn with_gate_noise<F>(circuit: &Circuit, mut noise: F) -> Self
+	where
+		F: FnMut(&Gate) -> Vec<Channel>,
+	{
+		let mut noisy = Self::new(circuit.qubit_count());
+		for gate in circuit.gates() {
+			noisy.add_gate(gate.clone());
+			for channel in noise(gate) {
+				noisy.add_channel(channel);
+			}
+		}
+		noisy
+	}
+
+	///Get qubit count
+	pub fn qubit_count(&self) -> usize {
+		self.qubit_count
+	}
+
+	///Get operations
+	pub fn operations(&self) -> &[Operation] {
+		&self.operations
+	}
+
+	/// # Panics
+	/// Panics if the gate acts on a qubit out of range.
+	pub fn add_gate(&mut self, gate: Gate) {
+		self.check_qubits(&gate.qubits());
+		self.operations.push(Operation::Gate(gate));
+	}
+
+	/// # Panics
+	/// Panics if the channel acts on a qubit out of range.
+	pub fn add_channel(&mut self, channel: Channel) {
+		self.check_qubits(&channel.targets);
+		self.operations.push(Operation::Channel(channel));
+	}
+
+	/// Apply the operations exactly to `rho`.
+	pub fn update_density_matrix(&self, rho: &mut DensityMatrix) -> Result<(), StateErr> {
+		if rho.qubit_count != self.qubit_count {
+			return Err(StateErr::InconsistentStateLength(
+				1 << self.qubit_count,
+				1 << rho.qubit_count,
+			));
+		}
+		for operation in &self.operations {
+			match operation {
+				Operation::Gate(gate) => rho.apply_gate(gate),
+				Operation::Channel(channel) => rho.apply_channel(channel),
+			}
+		}
+		Ok(())
+	}
+
+	/// Apply the operations to the normalized `state`, sampling one Kraus
+	/// operator of each channel. The average of the resulting |ψ><ψ| over
+	/// trajectories is the density matrix of `update_density_matrix`.
+	pub fn update_quantum_state(&self, state: &mut [Complex<f64>]) -> Result<(), StateErr> {
+		if state.len() != 1 << self.qubit_count {
+			return Err(StateErr::InconsistentStateLength(
+				1 << self.qubit_count,
+				state.len(),
+			));
+		}
+		for operation in &self.operations {
+			match operation {
+				Operation::Gate(gate) => gate.update_quantum_state(state),
+				Operation::Channel(channel) => channel.update_quantum_state(state),
+			}
+		}
+		Ok(())
+	}
+
+	fn check_qubits(&self, qubits: &[u32]) {
+		for &q in qubits {
+			assert!(
+				(q as usize) < self.qubit_count,
+				"qubit index {} is out of range for a circuit of {} qubits",
+				q,
+				self.qubit_count
+			);
+		}
+	}
+}
+
+fn check_targets(targets: &[u32]) -> Result<(), NoiseErr> {
+	let duplicate = targets
+		.iter()
+		.enumerate()
+		.any(|(i, q)| targets[..i].contains(q));
+	if targets.is_empty() || duplicate {
+		Err(NoiseErr::InvalidTargetList(targets.to_vec()))
+	} else {
+		Ok(())
+	}
+}
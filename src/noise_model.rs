@@ -0,0 +1,508 @@
+use crate::circuit::{Circuit, Gate};
+use crate::json::{self, Value};
+use crate::noise::{Channel, DensityMatrix, NoiseErr, NoisyCircuit};
+use crate::readout::{ReadoutErr, ReadoutNoise};
+use crate::state::StateErr;
+use crate::Complex;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+#[non_exhaustive]
+pub enum NoiseModelErr {
+	Io(io::Error),
+	/// Byte offset of the invalid JSON
+	InvalidJson(usize),
+	/// The calibration has a missing or invalid entry.
+	InvalidCalibration(String),
+	/// The qubit index is out of range.
+	InvalidQubit(u32),
+	Noise(NoiseErr),
+	Readout(ReadoutErr),
+}
+
+impl fmt::Display for NoiseModelErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			NoiseModelErr::Io(e) => write!(f, "IO error: {}", e),
+			NoiseModelErr::InvalidJson(position) => {
+				write!(f, "Invalid JSON: unexpected input at byte {}", position)
+			}
+			NoiseModelErr::InvalidCalibration(entry) => {
+				write!(f, "Invalid calibration: {}", entry)
+			}
+			NoiseModelErr::InvalidQubit(q) => write!(f, "Invalid qubit: {} is out of range", q),
+			NoiseModelErr::Noise(e) => write!(f, "{}", e),
+			NoiseModelErr::Readout(e) => write!(f, "{}", e),
+		}
+	}
+}
+
+impl fmt::Debug for NoiseModelErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		<Self as fmt::Display>::fmt(self, f)
+	}
+}
+
+impl From<io::Error> for NoiseModelErr {
+	fn from(e: io::Error) -> Self {
+		NoiseModelErr::Io(e)
+	}
+}
+
+impl From<NoiseErr> for NoiseModelErr {
+	fn from(e: NoiseErr) -> Self {
+		NoiseModelErr::Noise(e)
+	}
+}
+
+impl From<ReadoutErr> for NoiseModelErr {
+	fn from(e: ReadoutErr) -> Self {
+		NoiseModelErr::Readout(e)
+	}
+}
+
+/// Gates matched by name, as returned by `Gate::name`, and optionally by
+/// their qubits in the order of `Gate::qubits`.
+#[derive(Clone, Debug, PartialEq)]
+struct GateRule<T> {
+	name: String,
+	qubits: Option<Vec<u32>>,
+	value: T,
+}
+
+/// Noise of a device: channels following gates, relaxation of idle qubits
+/// and readout errors.
+///
+/// A circuit is run layer by layer, placing each gate in the first layer
+/// after the previous gates on its qubits as in `Circuit::depth`. A layer
+/// lasts as long as its longest gate, and each qubit with relaxation times
+/// undergoes `Channel::thermal_relaxation` for the part of the layer it is
+/// idle.
+///
+/// ```
+/// # use qurs::circuit::{Circuit, Gate};
+/// # use qurs::noise::{Channel, DensityMatrix};
+/// # use qurs::noise_model::NoiseModel;
+/// let mut model = NoiseModel::new(2);
+/// model.add_gate_noise("X", None, Channel::bit_flip(0, 0.1).unwrap()).unwrap();
+/// let mut circuit = Circuit::new(2);
+/// circuit.add_gate(Gate::X(1));
+/// let mut rho = DensityMatrix::new(2);
+/// model.update_density_matrix(&circuit, &mut rho).unwrap();
+/// assert!((rho.probabilities()[0] - 0.1).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoiseModel {
+	qubit_count: usize,
+	gate_errors: Vec<GateRule<Channel>>,
+	gate_noise: Vec<GateRule<Channel>>,
+	gate_times: Vec<GateRule<f64>>,
+	relaxation: Vec<Option<(f64, f64)>>,
+	readout: ReadoutNoise,
+}
+
+impl NoiseModel {
+	/// Noiseless model of `qubit_count` qubits
+	pub fn new(qubit_count: usize) -> Self {
+		Self {
+			qubit_count,
+			gate_errors: Vec::new(),
+			gate_noise: Vec::new(),
+			gate_times: Vec::new(),
+			relaxation: vec![None; qubit_count],
+			readout: ReadoutNoise::new(qubit_count),
+		}
+	}
+
+	/// Read a calibration file. See `parse` for the format.
+	pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, NoiseModelErr> {
+		Self::parse(&std::fs::read_to_string(path)?)
+	}
+
+	/// Parse a JSON calibration of the form
+	///
+	/// ```json
+	/// {
+	///     "qubits": [
+	///         {"t1": 80.0, "t2": 60.0, "readout_fidelity": 0.98},
+	///         {"t1": 70.0, "t2": 90.0, "prob_meas1_prep0": 0.01, "prob_meas0_prep1": 0.03}
+	///     ],
+	///     "gates": [
+	///         {"gate": "SqrtX", "error": 0.0003, "time": 0.035},
+	///         {"gate": "Cnot", "qubits": [0, 1], "error": 0.008, "time": 0.3}
+	///     ]
+	/// }
+	/// ```
+	///
+	/// The qubit count is the length of `qubits`. Every key of a qubit is
+	/// optional: `t1` and `t2` must be given together, and a
+	/// `readout_fidelity` f gives the symmetric readout error 1 - f. Gate
+	/// entries without `qubits` apply to the gate on any qubits. The `error`
+	/// of a gate on k qubits is its average gate infidelity r, modeled as
+	/// `Channel::depolarizing` with p = r (2^k + 1)/2^k. An entry with `qubits`
+	/// replaces the `error` and `time` of the entry without them on those
+	/// qubits. Times are in any unit shared by `t1`, `t2` and `time`.
+	pub fn parse(text: &str) -> Result<Self, NoiseModelErr> {
+		let root = json::parse(text).map_err(NoiseModelErr::InvalidJson)?;
+		let invalid = |entry: String| NoiseModelErr::InvalidCalibration(entry);
+		let qubits = root
+			.get("qubits")
+			.and_then(Value::as_array)
+			.ok_or_else(|| invalid("\"qubits\" must be an array".to_string()))?;
+		let mut model = Self::new(qubits.len());
+		for (q, qubit) in qubits.iter().enumerate() {
+			let number = |key: &str| -> Result<Option<f64>, NoiseModelErr> {
+				match qubit.get(key) {
+					None => Ok(None),
+					Some(value) => value.as_f64().map(Some).ok_or_else(|| {
+						invalid(format!("\"{}\" of qubit {} must be a number", key, q))
+					}),
+				}
+			};
+			let q = q as u32;
+			match (number("t1")?, number("t2")?) {
+				(Some(t1), Some(t2)) => model.set_relaxation(q, t1, t2)?,
+				(None, None) => {}
+				_ => {
+					return Err(invalid(format!(
+						"qubit {} must have both \"t1\" and \"t2\"",
+						q
+					)))
+				}
+			}
+			match (
+				number("readout_fidelity")?,
+				number("prob_meas1_prep0")?,
+				number("prob_meas0_prep1")?,
+			) {
+				(Some(f), None, None) => model.set_readout_error(q, 1. - f, 1. - f)?,
+				(None, Some(p10), Some(p01)) => model.set_readout_error(q, p10, p01)?,
+				(None, None, None) => {}
+				_ => {
+					return Err(invalid(format!(
+						"qubit {} must have either \"readout_fidelity\" or both \"prob_meas1_prep0\" and \"prob_meas0_prep1\"",
+						q
+					)))
+				}
+			}
+		}
+		let empty = Vec::new();
+		let gates = match root.get("gates") {
+			None => &empty[..],
+			Some(gates) => gates
+				.as_array()
+				.ok_or_else(|| invalid("\"gates\" must be an array".to_string()))?,
+		};
+		for (i, gate) in gates.iter().enumerate() {
+			let name = gate
+				.get("gate")
+				.and_then(Value::as_str)
+				.ok_or_else(|| invalid(format!("gate {} must have a \"gate\" name", i)))?;
+			let qubits = match gate.get("qubits") {
+				None => None,
+				Some(value) => Some(
+					value
+						.as_array()
+						.and_then(|qubits| {
+							qubits
+								.iter()
+								.map(|q| {
+									q.as_f64()
+										.filter(|q| q.fract() == 0. && *q >= 0.)
+										.map(|q| q as u32)
+								})
+								.collect::<Option<Vec<u32>>>()
+						})
+						.ok_or_else(|| {
+							invalid(format!("\"qubits\" of gate {} must be qubit indexes", i))
+						})?,
+				),
+			};
+			let number = |key: &str| -> Result<Option<f64>, NoiseModelErr> {
+				match gate.get(key) {
+					None => Ok(None),
+					Some(value) => value.as_f64().map(Some).ok_or_else(|| {
+						invalid(format!("\"{}\" of gate {} must be a number", key, i))
+					}),
+				}
+			};
+			if let Some(time) = number("time")? {
+				model.set_gate_time(name, qubits.as_deref(), time)?;
+			}
+			if let Some(error) = number("error")? {
+				if qubits.is_none() && gate_arity(name).is_none() {
+					return Err(invalid(format!(
+						"gate {} needs \"qubits\" for \"{}\"",
+						i, name
+					)));
+				}
+				model.set_gate_error(name, qubits.as_deref(), error)?;
+			}
+		}
+		Ok(model)
+	}
+
+	///Get qubit count
+	pub fn qubit_count(&self) -> usize {
+		self.qubit_count
+	}
+
+	/// Apply `channel` after every gate named `name`, or only after those on
+	/// `qubits` if given. Target q of the channel is the q-th qubit of the
+	/// gate in the order of `Gate::qubits`. The channel is skipped for gates
+	/// with fewer qubits than its targets.
+	pub fn add_gate_noise(
+		&mut self,
+		name: &str,
+		qubits: Option<&[u32]>,
+		channel: Channel,
+	) -> Result<(), NoiseModelErr> {
+		self.check_qubits(qubits.unwrap_or(&[]))?;
+		self.gate_noise.push(GateRule {
+			name: name.to_string(),
+			qubits: qubits.map(|q| q.to_vec()),
+			value: channel,
+		});
+		Ok(())
+	}
+
+	/// Set the average gate infidelity of the gates named `name`, or only of
+	/// those on `qubits` if given, applied as a depolarizing channel after the
+	/// gate as described in `parse`. A rule for specific qubits takes
+	/// precedence, and the channels of `add_gate_noise` follow it.
+	///
+	/// Returns an error if `qubits` is not given and `name` is not a gate of
+	/// fixed size, or if the error is too large for a depolarizing channel.
+	pub fn set_gate_error(
+		&mut self,
+		name: &str,
+		qubits: Option<&[u32]>,
+		error: f64,
+	) -> Result<(), NoiseModelErr> {
+		self.check_qubits(qubits.unwrap_or(&[]))?;
+		let arity = match qubits {
+			Some(qubits) => qubits.len(),
+			None => gate_arity(name).ok_or_else(|| {
+				NoiseModelErr::InvalidCalibration(format!("\"{}\" needs qubits", name))
+			})?,
+		};
+		let dim = (1 << arity) as f64;
+		let local: Vec<u32> = (0..arity as u32).collect();
+		let channel = Channel::depolarizing(&local, error * (dim + 1.) / dim)?;
+		self.gate_errors
+			.retain(|rule| rule.name != name || rule.qubits.as_deref() != qubits);
+		self.gate_errors.push(GateRule {
+			name: name.to_string(),
+			qubits: qubits.map(|q| q.to_vec()),
+			value: channel,
+		});
+		Ok(())
+	}
+
+	/// Set the duration of the gates named `name`, or only of those on
+	/// `qubits` if given. A rule for specific qubits takes precedence, and
+	/// gates without a duration take no time.
+	pub fn set_gate_time(
+		&mut self,
+		name: &str,
+		qubits: Option<&[u32]>,
+		time: f64,
+	) -> Result<(), NoiseModelErr> {
+		self.check_qubits(qubits.unwrap_or(&[]))?;
+		if time.is_nan() || time < 0. {
+			return Err(NoiseErr::InvalidProbability(time).into());
+		}
+		self.gate_times
+			.retain(|rule| rule.name != name || rule.qubits.as_deref() != qubits);
+		self.gate_times.push(GateRule {
+			name: name.to_string(),
+			qubits: qubits.map(|q| q.to_vec()),
+			value: time,
+		});
+		Ok(())
+	}
+
+	/// Set the relaxation times of qubit `q` while it is idle.
+	pub fn set_relaxation(&mut self, q: u32, t1: f64, t2: f64) -> Result<(), NoiseModelErr> {
+		self.check_qubits(&[q])?;
+		// validate the times
+		Channel::thermal_relaxation(q, t1, t2, 0.)?;
+		self.relaxation[q as usize] = Some((t1, t2));
+		Ok(())
+	}
+
+	/// Set the readout error of qubit `q`. See `ReadoutNoise::add_qubit_error`.
+	pub fn set_readout_error(
+		&mut self,
+		q: u32,
+		prob_1_given_0: f64,
+		prob_0_given_1: f64,
+	) -> Result<(), NoiseModelErr> {
+		self.readout
+			.add_qubit_error(q, prob_1_given_0, prob_0_given_1)?;
+		Ok(())
+	}
+
+	///Get readout noise
+	pub fn readout(&self) -> &ReadoutNoise {
+		&self.readout
+	}
+
+	/// Get the duration of `gate`.
+	pub fn gate_time(&self, gate: &Gate) -> f64 {
+		find_rule(&self.gate_times, gate).copied().unwrap_or(0.)
+	}
+
+	/// Get the channels following `gate`: the depolarizing channel of
+	/// `set_gate_error`, then those of `add_gate_noise`.
+	pub fn gate_noise(&self, gate: &Gate) -> Vec<Channel> {
+		let qubits = gate.qubits();
+		find_rule(&self.gate_errors, gate)
+			.into_iter()
+			.chain(
+				self.gate_noise
+					.iter()
+					.filter(|rule| {
+						rule.name == gate.name() && rule.qubits.iter().all(|q| *q == qubits)
+					})
+					.map(|rule| &rule.value),
+			)
+			.filter(|channel| {
+				channel
+					.targets()
+					.iter()
+					.all(|&t| (t as usize) < qubits.len())
+			})
+			.map(|channel| channel.map_targets(|t| qubits[t as usize]))
+			.collect()
+	}
+
+	/// Get `circuit` with the gate noise and the idle relaxation of this
+	/// model.
+	///
+	/// # Panics
+	/// Panics if `circuit` has more qubits than this model.
+	pub fn noisy_circuit(&self, circuit: &Circuit) -> NoisyCircuit {
+		assert!(
+			circuit.qubit_count() <= self.qubit_count,
+			"the circuit has {} qubits but the model has {}",
+			circuit.qubit_count(),
+			self.qubit_count
+		);
+		let mut last = vec![0; self.qubit_count];
+		let mut layers: Vec<Vec<&Gate>> = Vec::new();
+		for gate in circuit.gates() {
+			let qubits = gate.qubits();
+			let layer = qubits.iter().map(|&q| last[q as usize]).max().unwrap_or(0);
+			for &q in &qubits {
+				last[q as usize] = layer + 1;
+			}
+			if layers.len() <= layer {
+				layers.push(Vec::new());
+			}
+			layers[layer].push(gate);
+		}
+		let mut noisy = NoisyCircuit::new(self.qubit_count);
+		for layer in layers {
+			let mut busy = vec![0.; self.qubit_count];
+			for gate in &layer {
+				noisy.add_gate((*gate).clone());
+				for channel in self.gate_noise(gate) {
+					noisy.add_channel(channel);
+				}
+				let time = self.gate_time(gate);
+				for q in gate.qubits() {
+					busy[q as usize] = time;
+				}
+			}
+			let duration = busy.iter().cloned().fold(0., f64::max);
+			for (q, relaxation) in self.relaxation.iter().enumerate() {
+				if let Some((t1, t2)) = relaxation {
+					let idle = duration - busy[q];
+					if idle > 0. {
+						noisy.add_channel(
+							Channel::thermal_relaxation(q as u32, *t1, *t2, idle).unwrap(),
+						);
+					}
+				}
+			}
+		}
+		noisy
+	}
+
+	/// Run `circuit` with this noise on `rho`.
+	pub fn update_density_matrix(
+		&self,
+		circuit: &Circuit,
+		rho: &mut DensityMatrix,
+	) -> Result<(), StateErr> {
+		self.check_circuit(circuit)?;
+		self.noisy_circuit(circuit).update_density_matrix(rho)
+	}
+
+	/// Run `circuit` with this noise on `state`, sampling one trajectory.
+	pub fn update_quantum_state(
+		&self,
+		circuit: &Circuit,
+		state: &mut [Complex<f64>],
+	) -> Result<(), StateErr> {
+		self.check_circuit(circuit)?;
+		self.noisy_circuit(circuit).update_quantum_state(state)
+	}
+
+	/// Run `circuit` on |0...0> with this noise and sample the outcomes
+	/// `sampling_count` times with the readout error.
+	pub fn sampling(&self, circuit: &Circuit, sampling_count: u32) -> Result<Vec<u64>, StateErr> {
+		let mut rho = DensityMatrix::new(self.qubit_count);
+		self.update_density_matrix(circuit, &mut rho)?;
+		Ok(self.readout.apply(&rho.sampling(sampling_count)))
+	}
+
+	fn check_circuit(&self, circuit: &Circuit) -> Result<(), StateErr> {
+		if circuit.qubit_count() > self.qubit_count {
+			Err(StateErr::InconsistentStateLength(
+				1 << self.qubit_count,
+				1 << circuit.qubit_count(),
+			))
+		} else {
+			Ok(())
+		}
+	}
+
+	fn check_qubits(&self, qubits: &[u32]) -> Result<(), NoiseModelErr> {
+		match qubits.iter().find(|&&q| q as usize >= self.qubit_count) {
+			Some(&q) => Err(NoiseModelErr::InvalidQubit(q)),
+			None => Ok(()),
+		}
+	}
+}
+
+/// Get the value of the rule for `gate` on its qubits, or else of the rule
+/// for any qubits.
+fn find_rule<'a, T>(rules: &'a [GateRule<T>], gate: &Gate) -> Option<&'a T> {
+	let qubits = gate.qubits();
+	let mut value = None;
+	for rule in rules {
+		if rule.name == gate.name() {
+			match &rule.qubits {
+				Some(q) if *q == qubits => return Some(&rule.value),
+				None => value = Some(&rule.value),
+				_ => {}
+			}
+		}
+	}
+	value
+}
+
+/// Get the number of qubits of the gate named `name` if it is fixed.
+fn gate_arity(name: &str) -> Option<usize> {
+	match name {
+		"X" | "Y" | "Z" | "H" | "P0" | "P1" | "S" | "Sdag" | "T" | "Tdag" | "SqrtX"
+		| "SqrtXdag" | "SqrtY" | "SqrtYdag" | "Rx" | "Ry" | "Rz" | "Phase" | "U2" | "U3" => Some(1),
+		"Cz" | "Cnot" | "Swap" | "Rxx" | "Ryy" | "Rzz" | "ISwap" | "SqrtISwap" | "FSim" | "Ecr"
+		| "Givens" | "Cp" => Some(2),
+		"Ccnot" | "Ccz" => Some(3),
+		_ => None,
+	}
+}
@@ -0,0 +1,220 @@
+use crate::pauli::PauliString;
+use crate::state::{StateErr, StateMut, StateRef, StateVec};
+use crate::Complex;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// A linear combination Σ_k c_k P_k of Pauli strings P_k with complex
+/// coefficients c_k on `qubit_count` qubits.
+///
+/// ```
+/// # use qurs::observable::Observable;
+/// # use qurs::pauli::{Pauli, PauliString};
+/// # use qurs::prelude::*;
+/// # use qurs::{Complex, StateVec};
+/// let mut h = Observable::new(2);
+/// h.add_term(Complex::new(0.5, 0.), PauliString::new(&[0], &[Pauli::Z]).unwrap());
+/// h.add_term(Complex::new(2., 0.), PauliString::new(&[1], &[Pauli::Z]).unwrap());
+/// let mut state = StateVec::new(2);
+/// state.set_computational_basis(0b01);
+/// assert_eq!(h.expectation_value(&state).unwrap().re, 1.5);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Observable {
+	qubit_count: usize,
+	terms: Vec<(Complex<f64>, PauliString)>,
+}
+
+impl Observable {
+	/// Create the zero observable on `qubit_count` qubits.
+	pub fn new(qubit_count: usize) -> Self {
+		Self {
+			qubit_count,
+			terms: Vec::new(),
+		}
+	}
+
+	/// Create `coefficient` times the identity on `qubit_count` qubits.
+	pub fn identity(qubit_count: usize, coefficient: Complex<f64>) -> Self {
+		let mut observable = Self::new(qubit_count);
+		observable.add_term(coefficient, PauliString::default());
+		observable
+	}
+
+	///Get qubit count
+	pub fn qubit_count(&self) -> usize {
+		self.qubit_count
+	}
+
+	///Get terms as (coefficient, Pauli string)
+	pub fn terms(&self) -> &[(Complex<f64>, PauliString)] {
+		&self.terms
+	}
+
+	///Get the number of terms
+	pub fn term_count(&self) -> usize {
+		self.terms.len()
+	}
+
+	/// Add `coefficient` times `pauli` to this observable. Like terms are not
+	/// merged until `simplify` is called.
+	///
+	/// # Panics
+	/// Panics if `pauli` acts on a qubit out of range.
+	pub fn add_term(&mut self, coefficient: Complex<f64>, pauli: PauliString) {
+		for &q in pauli.targets() {
+			assert!(
+				(q as usize) < self.qubit_count,
+				"qubit index {} is out of range for an observable of {} qubits",
+				q,
+				self.qubit_count
+			);
+		}
+		self.terms.push((coefficient, pauli.normalized()));
+	}
+
+	/// Merge terms with the same Pauli string and remove terms whose
+	/// coefficient has absolute value at most `tolerance`. The first occurrence
+	/// of each Pauli string determines the order of terms.
+	pub fn simplify(&mut self, tolerance: f64) {
+		let mut index: HashMap<PauliString, usize> = HashMap::new();
+		let mut terms: Vec<(Complex<f64>, PauliString)> = Vec::new();
+		for (c, p) in self.terms.drain(..) {
+			match index.get(&p) {
+				Some(&i) => terms[i].0 += c,
+				None => {
+					index.insert(p.clone(), terms.len());
+					terms.push((c, p));
+				}
+			}
+		}
+		terms.retain(|(c, _)| c.norm() > tolerance);
+		self.terms = terms;
+	}
+
+	/// Get the Hermitian conjugate.
+	pub fn dagger(&self) -> Self {
+		Self {
+			qubit_count: self.qubit_count,
+			terms: self
+				.terms
+				.iter()
+				.map(|(c, p)| (c.conj(), p.clone()))
+				.collect(),
+		}
+	}
+
+	/// Check that this observable is Hermitian, i.e. every coefficient of the
+	/// simplified observable is real up to `tolerance`.
+	pub fn is_hermitian(&self, tolerance: f64) -> bool {
+		let mut simplified = self.clone();
+		simplified.simplify(0.);
+		simplified
+			.terms
+			.iter()
+			.all(|(c, _)| c.im.abs() <= tolerance)
+	}
+
+	/// Get the expectation value <ψ|O|ψ>, which is real if this observable is
+	/// Hermitian.
+	pub fn expectation_value<T>(&self, state: &T) -> Result<Complex<f64>, StateErr>
+	where
+		T: StateRef<f64> + AsRef<[Complex<f64>]>,
+	{
+		let mut value = Complex::new(0., 0.);
+		for (c, p) in &self.terms {
+			value += c * p.expectation_value(state)?;
+		}
+		Ok(value)
+	}
+
+	/// Get the 2^n×2^n matrix in row-major order, where qubit 0 is the least
+	/// significant bit of the row and column indices.
+	pub fn matrix(&self) -> Vec<Complex<f64>> {
+		let dim = 1 << self.qubit_count;
+		let mut matrix = vec![Complex::new(0., 0.); dim * dim];
+		for (c, p) in &self.terms {
+			for column in 0..dim {
+				let mut state = StateVec::new(self.qubit_count);
+				state.set_computational_basis(column);
+				p.apply(&mut state).unwrap();
+				for (row, amplitude) in state.as_ref().iter().enumerate() {
+					matrix[row * dim + column] += c * amplitude;
+				}
+			}
+		}
+		matrix
+	}
+}
+
+impl Add for Observable {
+	type Output = Observable;
+
+	fn add(mut self, other: Observable) -> Observable {
+		self.qubit_count = self.qubit_count.max(other.qubit_count);
+		self.terms.extend(other.terms);
+		self
+	}
+}
+
+impl Sub for Observable {
+	type Output = Observable;
+
+	fn sub(self, other: Observable) -> Observable {
+		self + -other
+	}
+}
+
+impl Neg for Observable {
+	type Output = Observable;
+
+	fn neg(self) -> Observable {
+		self * Complex::new(-1., 0.)
+	}
+}
+
+impl Mul<Complex<f64>> for Observable {
+	type Output = Observable;
+
+	fn mul(mut self, coefficient: Complex<f64>) -> Observable {
+		for (c, _) in &mut self.terms {
+			*c *= coefficient;
+		}
+		self
+	}
+}
+
+impl Mul for Observable {
+	type Output = Observable;
+
+	/// Get the operator product. Like terms of the result are not merged.
+	fn mul(self, other: Observable) -> Observable {
+		let mut terms = Vec::with_capacity(self.terms.len() * other.terms.len());
+		for (a, p) in &self.terms {
+			for (b, q) in &other.terms {
+				let (phase, pq) = p.product(q);
+				terms.push((a * b * phase, pq));
+			}
+		}
+		Observable {
+			qubit_count: self.qubit_count.max(other.qubit_count),
+			terms,
+		}
+	}
+}
+
+impl fmt::Display for Observable {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.terms.is_empty() {
+			return write!(f, "0");
+		}
+		for (i, (c, p)) in self.terms.iter().enumerate() {
+			if i > 0 {
+				write!(f, " + ")?;
+			}
+			write!(f, "({}) {}", c, p)?;
+		}
+		Ok(())
+	}
+}
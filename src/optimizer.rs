@@ -0,0 +1,367 @@
+use crate::circuit::{Circuit, Gate};
+use crate::Complex;
+use std::f64::consts::PI;
+use std::fmt;
+
+/// Rotation angles closer than this to a multiple of 4π are treated as the
+/// identity.
+const ANGLE_EPS: f64 = 1e-12;
+
+/// A transformation of a circuit which preserves its operator.
+pub trait Pass {
+	/// Name shown in `PassReport`.
+	fn name(&self) -> &str;
+
+	/// Get the transformed circuit.
+	fn run(&self, circuit: &Circuit) -> Circuit;
+}
+
+/// Gate count and depth of a circuit before and after a pass.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PassReport {
+	pub name: String,
+	pub gate_count_before: usize,
+	pub gate_count_after: usize,
+	pub depth_before: usize,
+	pub depth_after: usize,
+}
+
+impl fmt::Display for PassReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{}: gates {} -> {}, depth {} -> {}",
+			self.name,
+			self.gate_count_before,
+			self.gate_count_after,
+			self.depth_before,
+			self.depth_after
+		)
+	}
+}
+
+/// Runs a sequence of passes.
+///
+/// ```
+/// # use qurs::circuit::{Circuit, Gate};
+/// # use qurs::optimizer::{CancelInversePairs, MergeRotations, PassManager};
+/// let mut circuit = Circuit::new(1);
+/// circuit.add_gate(Gate::H(0));
+/// circuit.add_gate(Gate::Rz(0, 0.2));
+/// circuit.add_gate(Gate::Rz(0, -0.2));
+/// circuit.add_gate(Gate::H(0));
+/// let mut manager = PassManager::new();
+/// manager.add_pass(MergeRotations);
+/// manager.add_pass(CancelInversePairs);
+/// let (optimized, reports) = manager.run(&circuit);
+/// assert_eq!(optimized.gate_count(), 0);
+/// assert_eq!(reports[1].gate_count_before, 2);
+/// ```
+#[derive(Default)]
+pub struct PassManager {
+	passes: Vec<Box<dyn Pass>>,
+}
+
+impl PassManager {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Get a manager running all passes of this module in an order where
+	/// each pass exposes opportunities for the next one.
+	pub fn with_default_passes() -> Self {
+		let mut manager = Self::new();
+		manager.add_pass(CommuteDiagonal);
+		manager.add_pass(MergeRotations);
+		manager.add_pass(CancelInversePairs);
+		manager.add_pass(FuseSingleQubitGates);
+		manager
+	}
+
+	/// Append a pass to run after the already added ones.
+	pub fn add_pass<P: Pass + 'static>(&mut self, pass: P) {
+		self.passes.push(Box::new(pass));
+	}
+
+	/// Run all passes in order.
+	///
+	/// Returns the optimized circuit and a report for each pass.
+	pub fn run(&self, circuit: &Circuit) -> (Circuit, Vec<PassReport>) {
+		let mut circuit = circuit.clone();
+		let mut reports = Vec::new();
+		for pass in &self.passes {
+			let optimized = pass.run(&circuit);
+			reports.push(PassReport {
+				name: pass.name().to_string(),
+				gate_count_before: circuit.gate_count(),
+				gate_count_after: optimized.gate_count(),
+				depth_before: circuit.depth(),
+				depth_after: optimized.depth(),
+			});
+			circuit = optimized;
+		}
+		(circuit, reports)
+	}
+}
+
+/// Index of the last gate in `gates` sharing a qubit with `gate`.
+fn last_overlapping(gates: &[Gate], gate: &Gate) -> Option<usize> {
+	let qubits = gate.qubits();
+	gates
+		.iter()
+		.rposition(|g| g.qubits().iter().any(|q| qubits.contains(q)))
+}
+
+fn same_qubit_set(a: &Gate, b: &Gate) -> bool {
+	let (mut qa, mut qb) = (a.qubits(), b.qubits());
+	qa.sort_unstable();
+	qb.sort_unstable();
+	qa == qb
+}
+
+/// Whether `b` is the inverse of `a`, given that both act on the same qubits.
+fn is_inverse_pair(a: &Gate, b: &Gate) -> bool {
+	use Gate::*;
+	match (a, b) {
+		(X(_), X(_)) | (Y(_), Y(_)) | (Z(_), Z(_)) | (H(_), H(_)) => true,
+		(S(_), Sdag(_)) | (Sdag(_), S(_)) | (T(_), Tdag(_)) | (Tdag(_), T(_)) => true,
+		(SqrtX(_), SqrtXdag(_)) | (SqrtXdag(_), SqrtX(_)) => true,
+		(SqrtY(_), SqrtYdag(_)) | (SqrtYdag(_), SqrtY(_)) => true,
+		// CZ, SWAP and CCZ are symmetric in their qubits.
+		(Cz(..), Cz(..)) | (Swap(..), Swap(..)) | (Ccz(..), Ccz(..)) => true,
+		(Cnot(_, t1), Cnot(_, t2)) | (Ccnot(_, _, t1), Ccnot(_, _, t2)) => t1 == t2,
+		(Rx(_, x), Rx(_, y)) | (Ry(_, x), Ry(_, y)) | (Rz(_, x), Rz(_, y)) => {
+			is_identity_angle(x + y)
+		}
+		_ => false,
+	}
+}
+
+/// Whether the rotation exp(-i(θ/2)P) is exactly the identity.
+fn is_identity_angle(angle: f64) -> bool {
+	let r = angle.rem_euclid(4. * PI);
+	r < ANGLE_EPS || 4. * PI - r < ANGLE_EPS
+}
+
+/// Removes pairs of adjacent gates which are inverse to each other, such as
+/// `H H`, `S Sdag` or `CNOT CNOT`. Gates are adjacent when no gate between them
+/// acts on their qubits, and nested pairs such as `H X X H` are removed
+/// together.
+pub struct CancelInversePairs;
+
+impl Pass for CancelInversePairs {
+	fn name(&self) -> &str {
+		"CancelInversePairs"
+	}
+
+	fn run(&self, circuit: &Circuit) -> Circuit {
+		let mut gates: Vec<Gate> = Vec::new();
+		for gate in circuit.gates() {
+			if let Some(i) = last_overlapping(&gates, gate) {
+				if same_qubit_set(&gates[i], gate) && is_inverse_pair(&gates[i], gate) {
+					gates.remove(i);
+					continue;
+				}
+			}
+			gates.push(gate.clone());
+		}
+		rebuild(circuit, gates)
+	}
+}
+
+/// Merges adjacent rotations around the same axis on the same qubits, such as
+/// `Rz(a) Rz(b)` into `Rz(a + b)`, and removes rotations which are the
+/// identity.
+pub struct MergeRotations;
+
+impl Pass for MergeRotations {
+	fn name(&self) -> &str {
+		"MergeRotations"
+	}
+
+	fn run(&self, circuit: &Circuit) -> Circuit {
+		use Gate::*;
+		let mut gates: Vec<Gate> = Vec::new();
+		for gate in circuit.gates() {
+			if let Some(i) = last_overlapping(&gates, gate) {
+				let merged = match (&gates[i], gate) {
+					(Rx(p, x), Rx(q, y)) if p == q => Some(Rx(*p, x + y)),
+					(Ry(p, x), Ry(q, y)) if p == q => Some(Ry(*p, x + y)),
+					(Rz(p, x), Rz(q, y)) if p == q => Some(Rz(*p, x + y)),
+					(Phase(p, x), Phase(q, y)) if p == q => Some(Phase(*p, x + y)),
+					(
+						PauliRotation {
+							targets,
+							paulis,
+							angle: x,
+						},
+						PauliRotation {
+							targets: t,
+							paulis: p,
+							angle: y,
+						},
+					) if targets == t && paulis == p => Some(PauliRotation {
+						targets: targets.clone(),
+						paulis: paulis.clone(),
+						angle: x + y,
+					}),
+					_ => None,
+				};
+				if let Some(merged) = merged {
+					gates[i] = merged;
+					continue;
+				}
+			}
+			gates.push(gate.clone());
+		}
+		gates.retain(|g| match g {
+			Rx(_, angle) | Ry(_, angle) | Rz(_, angle) | PauliRotation { angle, .. } => {
+				!is_identity_angle(*angle)
+			}
+			// e^{iλ} is 1 for λ ≡ 0 (mod 2π)
+			Phase(_, angle) => !is_identity_angle(2. * angle),
+			_ => true,
+		});
+		rebuild(circuit, gates)
+	}
+}
+
+/// Whether `gate` is a single-qubit gate diagonal in the Z basis.
+fn diagonal_qubit(gate: &Gate) -> Option<u32> {
+	use Gate::*;
+	match gate {
+		Z(q) | S(q) | Sdag(q) | T(q) | Tdag(q) | Rz(q, _) | Phase(q, _) | P0(q) | P1(q) => Some(*q),
+		_ => None,
+	}
+}
+
+/// Whether a Z-diagonal gate on `qubit` commutes with `gate`.
+fn commutes_with_diagonal(qubit: u32, gate: &Gate) -> bool {
+	use Gate::*;
+	if !gate.qubits().contains(&qubit) {
+		return true;
+	}
+	match gate {
+		Cz(..) | Ccz(..) | Rzz(..) | Cp(..) | MultiControlledPhase { .. } => true,
+		Cnot(c, _) => *c == qubit,
+		Ccnot(c1, c2, _) => *c1 == qubit || *c2 == qubit,
+		MultiControlled {
+			controls,
+			target,
+			matrix,
+		} => {
+			controls.contains(&qubit)
+				|| (*target == qubit
+					&& matrix[1] == Complex::new(0., 0.)
+					&& matrix[2] == Complex::new(0., 0.))
+		}
+		Controlled { controls, .. } if controls.iter().any(|&(c, _)| c == qubit) => true,
+		PauliRotation {
+			targets, paulis, ..
+		} => targets
+			.iter()
+			.zip(paulis)
+			.all(|(&t, &p)| t != qubit || p == 0 || p == 3),
+		gate => diagonal_qubit(gate).is_some(),
+	}
+}
+
+/// Moves each Z-diagonal single-qubit gate (`Z`, `S`, `T`, `Rz`, ...) towards
+/// the beginning of the circuit through gates it commutes with, such as the
+/// control of a CNOT. It stops right after the previous single-qubit gate on
+/// the same qubit so that later passes can cancel, merge or fuse them.
+pub struct CommuteDiagonal;
+
+impl Pass for CommuteDiagonal {
+	fn name(&self) -> &str {
+		"CommuteDiagonal"
+	}
+
+	fn run(&self, circuit: &Circuit) -> Circuit {
+		let mut gates: Vec<Gate> = Vec::new();
+		for gate in circuit.gates() {
+			gates.push(gate.clone());
+			let qubit = match diagonal_qubit(gate) {
+				Some(q) => q,
+				None => continue,
+			};
+			let mut i = gates.len() - 1;
+			while i > 0 {
+				let previous = &gates[i - 1];
+				if previous.qubits() == [qubit] || !commutes_with_diagonal(qubit, previous) {
+					break;
+				}
+				gates.swap(i - 1, i);
+				i -= 1;
+			}
+		}
+		rebuild(circuit, gates)
+	}
+}
+
+/// Fuses each run of two or more consecutive single-qubit gates on a qubit
+/// into one `Gate::DenseMatrix`.
+pub struct FuseSingleQubitGates;
+
+impl Pass for FuseSingleQubitGates {
+	fn name(&self) -> &str {
+		"FuseSingleQubitGates"
+	}
+
+	fn run(&self, circuit: &Circuit) -> Circuit {
+		let mut pending: Vec<Vec<Gate>> = vec![Vec::new(); circuit.qubit_count()];
+		let mut gates = Vec::new();
+		for gate in circuit.gates() {
+			let qubits = gate.qubits();
+			if let [q] = qubits[..] {
+				pending[q as usize].push(gate.clone());
+				continue;
+			}
+			for q in qubits {
+				flush_single_qubit_run(q, &mut pending[q as usize], &mut gates);
+			}
+			gates.push(gate.clone());
+		}
+		for (q, run) in pending.iter_mut().enumerate() {
+			flush_single_qubit_run(q as u32, run, &mut gates);
+		}
+		rebuild(circuit, gates)
+	}
+}
+
+fn flush_single_qubit_run(qubit: u32, run: &mut Vec<Gate>, gates: &mut Vec<Gate>) {
+	if run.len() == 1 {
+		gates.push(run.remove(0));
+		return;
+	}
+	if run.is_empty() {
+		return;
+	}
+	let mut matrix = vec![
+		Complex::new(1., 0.),
+		Complex::new(0., 0.),
+		Complex::new(0., 0.),
+		Complex::new(1., 0.),
+	];
+	for gate in run.drain(..) {
+		let m = gate.matrix();
+		matrix = (0..4)
+			.map(|k| {
+				let (i, j) = (k / 2, k % 2);
+				m[i * 2] * matrix[j] + m[i * 2 + 1] * matrix[2 + j]
+			})
+			.collect();
+	}
+	gates.push(Gate::DenseMatrix {
+		targets: vec![qubit],
+		matrix,
+	});
+}
+
+fn rebuild(circuit: &Circuit, gates: Vec<Gate>) -> Circuit {
+	let mut optimized = Circuit::new(circuit.qubit_count());
+	for gate in gates {
+		optimized.add_gate(gate);
+	}
+	optimized
+}
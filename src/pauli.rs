@@ -0,0 +1,397 @@
+use crate::binding::{qulacs, CTYPE};
+use crate::circuit::Gate;
+use crate::state::{StateErr, StateRef};
+use crate::Complex;
+use std::fmt;
+
+/// A single-qubit Pauli operator. The discriminant is the Pauli type used by
+/// csim.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Pauli {
+	I = 0,
+	X = 1,
+	Y = 2,
+	Z = 3,
+}
+
+impl Pauli {
+	/// Get the Pauli operator of a csim Pauli type.
+	pub fn from_type(pauli_type: u32) -> Result<Self, StateErr> {
+		match pauli_type {
+			0 => Ok(Pauli::I),
+			1 => Ok(Pauli::X),
+			2 => Ok(Pauli::Y),
+			3 => Ok(Pauli::Z),
+			t => Err(StateErr::InvalidPauliType(t)),
+		}
+	}
+
+	/// Get the csim Pauli type.
+	pub fn pauli_type(self) -> u32 {
+		self as u32
+	}
+
+	/// Get the 2×2 matrix in row-major order.
+	pub fn matrix(self) -> [Complex<f64>; 4] {
+		let (o, l, i) = (
+			Complex::new(0., 0.),
+			Complex::new(1., 0.),
+			Complex::new(0., 1.),
+		);
+		match self {
+			Pauli::I => [l, o, o, l],
+			Pauli::X => [o, l, l, o],
+			Pauli::Y => [o, -i, i, o],
+			Pauli::Z => [l, o, o, -l],
+		}
+	}
+}
+
+impl Pauli {
+	/// Get the product of two Pauli operators as (phase, Pauli), e.g. XY = iZ.
+	pub fn product(self, other: Pauli) -> (Complex<f64>, Pauli) {
+		let one = Complex::new(1., 0.);
+		match (self, other) {
+			(a, b) if a == b => (one, Pauli::I),
+			(Pauli::I, b) => (one, b),
+			(a, Pauli::I) => (one, a),
+			(a, b) => {
+				// the remaining one of X, Y and Z
+				let c = Pauli::from_type(6 - a.pauli_type() - b.pauli_type()).unwrap();
+				let cyclic = matches!(
+					(a, b),
+					(Pauli::X, Pauli::Y) | (Pauli::Y, Pauli::Z) | (Pauli::Z, Pauli::X)
+				);
+				let phase = if cyclic {
+					Complex::new(0., 1.)
+				} else {
+					Complex::new(0., -1.)
+				};
+				(phase, c)
+			}
+		}
+	}
+}
+
+impl fmt::Display for Pauli {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			Pauli::I => "I",
+			Pauli::X => "X",
+			Pauli::Y => "Y",
+			Pauli::Z => "Z",
+		};
+		write!(f, "{}", name)
+	}
+}
+
+/// A tensor product of Pauli operators on distinct qubits. Qubits not listed
+/// are acted on by the identity.
+///
+/// ```
+/// # use qurs::pauli::{Pauli, PauliString};
+/// # use qurs::prelude::*;
+/// # use qurs::StateVec;
+/// let zz = PauliString::new(&[0, 2], &[Pauli::Z, Pauli::Z]).unwrap();
+/// let mut state = StateVec::new(3);
+/// state.set_computational_basis(0b001);
+/// assert_eq!(zz.expectation_value(&state).unwrap(), -1.);
+/// assert_eq!(zz.to_string(), "Z0 Z2");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PauliString {
+	targets: Vec<u32>,
+	paulis: Vec<Pauli>,
+}
+
+impl PauliString {
+	/// Build the product of `paulis[i]` on qubit `targets[i]`.
+	///
+	/// Returns an error if the lists differ in length or a qubit is given
+	/// twice.
+	pub fn new(targets: &[u32], paulis: &[Pauli]) -> Result<Self, StateErr> {
+		if targets.len() != paulis.len() {
+			return Err(StateErr::InconsistentListLength(
+				targets.len(),
+				paulis.len(),
+			));
+		}
+		for (i, t) in targets.iter().enumerate() {
+			if targets[..i].contains(t) {
+				return Err(StateErr::InvalidTargetList(targets.to_vec()));
+			}
+		}
+		Ok(Self {
+			targets: targets.to_vec(),
+			paulis: paulis.to_vec(),
+		})
+	}
+
+	/// Build a Pauli string from csim style lists of target qubits and Pauli
+	/// types (0: I, 1: X, 2: Y, 3: Z).
+	pub fn from_type_list(targets: &[u32], pauli_types: &[u32]) -> Result<Self, StateErr> {
+		let paulis = pauli_types
+			.iter()
+			.map(|&t| Pauli::from_type(t))
+			.collect::<Result<Vec<_>, _>>()?;
+		Self::new(targets, &paulis)
+	}
+
+	/// Build a Pauli string acting with `paulis[q]` on each qubit `q`.
+	pub fn from_whole_list(paulis: &[Pauli]) -> Self {
+		Self {
+			targets: (0..paulis.len() as u32).collect(),
+			paulis: paulis.to_vec(),
+		}
+	}
+
+	///Get target qubits
+	pub fn targets(&self) -> &[u32] {
+		&self.targets
+	}
+
+	///Get Pauli operators for each target qubit
+	pub fn paulis(&self) -> &[Pauli] {
+		&self.paulis
+	}
+
+	/// Get the csim Pauli types for each target qubit.
+	pub fn pauli_types(&self) -> Vec<u32> {
+		self.paulis.iter().map(|p| p.pauli_type()).collect()
+	}
+
+	/// Get the Pauli operator acting on `qubit`.
+	pub fn pauli_at(&self, qubit: u32) -> Pauli {
+		self.targets
+			.iter()
+			.position(|&t| t == qubit)
+			.map_or(Pauli::I, |i| self.paulis[i])
+	}
+
+	/// Get the same Pauli string with targets in ascending order and identities
+	/// removed.
+	pub fn normalized(&self) -> PauliString {
+		let mut pairs: Vec<(u32, Pauli)> = self
+			.targets
+			.iter()
+			.cloned()
+			.zip(self.paulis.iter().cloned())
+			.filter(|&(_, p)| p != Pauli::I)
+			.collect();
+		pairs.sort_unstable();
+		let (targets, paulis) = pairs.into_iter().unzip();
+		PauliString { targets, paulis }
+	}
+
+	/// Get the product of two Pauli strings as (phase, normalized Pauli
+	/// string).
+	pub fn product(&self, other: &PauliString) -> (Complex<f64>, PauliString) {
+		let mut qubits: Vec<u32> = self.targets.iter().chain(&other.targets).cloned().collect();
+		qubits.sort_unstable();
+		qubits.dedup();
+		let mut phase = Complex::new(1., 0.);
+		let mut targets = Vec::new();
+		let mut paulis = Vec::new();
+		for q in qubits {
+			let (p, pauli) = self.pauli_at(q).product(other.pauli_at(q));
+			phase *= p;
+			if pauli != Pauli::I {
+				targets.push(q);
+				paulis.push(pauli);
+			}
+		}
+		(phase, PauliString { targets, paulis })
+	}
+
+	/// Get the gate exp(-i(θ/2)P) for this Pauli string P.
+	pub fn rotation_gate(&self, angle: f64) -> Gate {
+		Gate::PauliRotation {
+			targets: self.targets.clone(),
+			paulis: self.pauli_types(),
+			angle,
+		}
+	}
+
+	fn check_targets(&self, qubit_count: usize) -> Result<(), StateErr> {
+		match self.targets.iter().find(|&&t| t as usize >= qubit_count) {
+			Some(&t) => Err(StateErr::InvalidTargetQubitIndex(t as usize)),
+			None => Ok(()),
+		}
+	}
+
+	/// Apply this Pauli string to the quantum state.
+	pub fn apply<T>(&self, state: &mut T) -> Result<(), StateErr>
+	where
+		T: StateRef<f64> + AsMut<[Complex<f64>]>,
+	{
+		self.check_targets(state.qubit_count())?;
+		let pauli_types = self.pauli_types();
+		let state = state.as_mut();
+		unsafe {
+			qulacs::multi_qubit_Pauli_gate_partial_list(
+				self.targets.as_ptr(),
+				pauli_types.as_ptr(),
+				self.targets.len() as u32,
+				state.as_mut_ptr() as *mut CTYPE,
+				state.len() as u64,
+			)
+		}
+		Ok(())
+	}
+
+	/// Apply exp(-i(θ/2)P) to the quantum state, where θ is the angle and P is
+	/// this Pauli string.
+	pub fn rotate<T>(&self, angle: f64, state: &mut T) -> Result<(), StateErr>
+	where
+		T: StateRef<f64> + AsMut<[Complex<f64>]>,
+	{
+		self.check_targets(state.qubit_count())?;
+		let pauli_types = self.pauli_types();
+		let state = state.as_mut();
+		unsafe {
+			qulacs::multi_qubit_Pauli_rotation_gate_partial_list(
+				self.targets.as_ptr(),
+				pauli_types.as_ptr(),
+				self.targets.len() as u32,
+				-angle,
+				state.as_mut_ptr() as *mut CTYPE,
+				state.len() as u64,
+			)
+		}
+		Ok(())
+	}
+
+	/// Get the expectation value <ψ|P|ψ> of this Pauli string.
+	pub fn expectation_value<T>(&self, state: &T) -> Result<f64, StateErr>
+	where
+		T: StateRef<f64> + AsRef<[Complex<f64>]>,
+	{
+		self.check_targets(state.qubit_count())?;
+		let pauli_types = self.pauli_types();
+		let state = state.as_ref();
+		unsafe {
+			Ok(
+				qulacs::expectation_value_multi_qubit_Pauli_operator_partial_list(
+					self.targets.as_ptr(),
+					pauli_types.as_ptr(),
+					self.targets.len() as u32,
+					state.as_ptr() as *const CTYPE,
+					state.len() as u64,
+				),
+			)
+		}
+	}
+}
+
+impl fmt::Display for PauliString {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut terms = self
+			.targets
+			.iter()
+			.zip(&self.paulis)
+			.filter(|(_, &p)| p != Pauli::I)
+			.peekable();
+		if terms.peek().is_none() {
+			return write!(f, "I");
+		}
+		for (i, (t, p)) in terms.enumerate() {
+			if i > 0 {
+				write!(f, " ")?;
+			}
+			write!(f, "{}{}", p, t)?;
+		}
+		Ok(())
+	}
+}
+
+fn check_whole_list(paulis: &[Pauli], qubit_count: usize) -> Result<Vec<u32>, StateErr> {
+	if paulis.len() != qubit_count {
+		return Err(StateErr::InconsistentListLength(qubit_count, paulis.len()));
+	}
+	Ok(paulis.iter().map(|p| p.pauli_type()).collect())
+}
+
+/// Get the Pauli string of `qubit_count` qubits with the index Σ_q t_q 4^q,
+/// where t_q is the Pauli type on qubit q.
+pub(crate) fn pauli_list(qubit_count: usize, index: usize) -> Vec<Pauli> {
+	(0..qubit_count)
+		.map(|q| Pauli::from_type(((index >> (2 * q)) & 3) as u32).unwrap())
+		.collect()
+}
+
+/// Get the element at (`row`, `col`) of ⊗_q `paulis[q]`.
+pub(crate) fn pauli_matrix_element(paulis: &[Pauli], row: usize, col: usize) -> Complex<f64> {
+	paulis
+		.iter()
+		.enumerate()
+		.map(|(q, p)| p.matrix()[((row >> q) & 1) * 2 + ((col >> q) & 1)])
+		.product()
+}
+
+/// Apply `paulis[q]` to every qubit `q` of the quantum state.
+///
+/// Returns an error unless there is exactly one Pauli operator per qubit.
+pub fn pauli_gate_whole_list<T>(paulis: &[Pauli], state: &mut T) -> Result<(), StateErr>
+where
+	T: StateRef<f64> + AsMut<[Complex<f64>]>,
+{
+	let pauli_types = check_whole_list(paulis, state.qubit_count())?;
+	let state = state.as_mut();
+	unsafe {
+		qulacs::multi_qubit_Pauli_gate_whole_list(
+			pauli_types.as_ptr(),
+			pauli_types.len() as u32,
+			state.as_mut_ptr() as *mut CTYPE,
+			state.len() as u64,
+		)
+	}
+	Ok(())
+}
+
+/// Apply exp(-i(θ/2)P) to the quantum state, where θ is the angle and P has
+/// `paulis[q]` on every qubit `q`.
+///
+/// Returns an error unless there is exactly one Pauli operator per qubit.
+pub fn pauli_rotation_gate_whole_list<T>(
+	paulis: &[Pauli],
+	angle: f64,
+	state: &mut T,
+) -> Result<(), StateErr>
+where
+	T: StateRef<f64> + AsMut<[Complex<f64>]>,
+{
+	let pauli_types = check_whole_list(paulis, state.qubit_count())?;
+	let state = state.as_mut();
+	unsafe {
+		qulacs::multi_qubit_Pauli_rotation_gate_whole_list(
+			pauli_types.as_ptr(),
+			pauli_types.len() as u32,
+			-angle,
+			state.as_mut_ptr() as *mut CTYPE,
+			state.len() as u64,
+		)
+	}
+	Ok(())
+}
+
+/// Get the expectation value of the Pauli operator with `paulis[q]` on every
+/// qubit `q`.
+///
+/// Returns an error unless there is exactly one Pauli operator per qubit.
+pub fn expectation_value_pauli_whole_list<T>(paulis: &[Pauli], state: &T) -> Result<f64, StateErr>
+where
+	T: StateRef<f64> + AsRef<[Complex<f64>]>,
+{
+	let pauli_types = check_whole_list(paulis, state.qubit_count())?;
+	let state = state.as_ref();
+	unsafe {
+		Ok(
+			qulacs::expectation_value_multi_qubit_Pauli_operator_whole_list(
+				pauli_types.as_ptr(),
+				pauli_types.len() as u32,
+				state.as_ptr() as *const CTYPE,
+				state.len() as u64,
+			),
+		)
+	}
+}
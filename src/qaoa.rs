@@ -0,0 +1,213 @@
+use crate::circuit::{Circuit, Gate};
+use crate::state::StateErr;
+use crate::Complex;
+
+/// Coefficients whose absolute value is below this are dropped when a cost
+/// function is expanded into Z terms.
+const COEF_EPS: f64 = 1e-12;
+
+/// A QAOA problem given by a cost function C(z) that is diagonal in the
+/// computational basis. The ansatz maximizes C.
+///
+/// The cost is kept both as a table of C(z) over all basis indexes (for fast
+/// expectation values) and as a sum of Z-string terms Σ_S a_S Π_{i∈S} Z_i (for
+/// building the phase separator).
+///
+/// ```
+/// # use qurs::qaoa::Qaoa;
+/// # use qurs::StateVec;
+/// let qaoa = Qaoa::maxcut(3, &[(0, 1, 1.), (1, 2, 1.), (0, 2, 1.)]);
+/// let circuit = qaoa.circuit(&[(0.5, 0.3)]);
+/// let mut state = StateVec::new(3);
+/// circuit.update_quantum_state(state.as_mut()).unwrap();
+/// let ratio = qaoa.approximation_ratio(state.as_ref()).unwrap();
+/// assert!(ratio > 0. && ratio <= 1.);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Qaoa {
+	qubit_count: usize,
+	cost_table: Vec<f64>,
+	/// (qubits, coefficient) for each non-constant Z-string term
+	terms: Vec<(Vec<u32>, f64)>,
+}
+
+impl Qaoa {
+	/// Build the MaxCut problem C(z) = Σ_(i,j) w_ij (1 - z_i z_j) / 2 for a
+	/// weighted graph.
+	/// * `qubit_count` number of vertices
+	/// * `edges` list of `(vertex, vertex, weight)`
+	///
+	/// # Panics
+	/// Panics if an edge refers to a vertex outside of `qubit_count` or is a
+	/// self loop.
+	pub fn maxcut(qubit_count: usize, edges: &[(u32, u32, f64)]) -> Self {
+		for &(i, j, _) in edges {
+			assert!(
+				(i as usize) < qubit_count && (j as usize) < qubit_count && i != j,
+				"invalid edge ({}, {}) for a graph of {} vertices",
+				i,
+				j,
+				qubit_count
+			);
+		}
+		let cost_table = (0..1usize << qubit_count)
+			.map(|z| {
+				edges
+					.iter()
+					.filter(|(i, j, _)| (z >> i) & 1 != (z >> j) & 1)
+					.map(|(_, _, w)| w)
+					.sum()
+			})
+			.collect();
+		let terms = edges
+			.iter()
+			.map(|&(i, j, w)| (vec![i, j], -w / 2.))
+			.collect();
+		Self {
+			qubit_count,
+			cost_table,
+			terms,
+		}
+	}
+
+	/// Build a problem from an arbitrary cost function over basis indexes.
+	/// The bit `i` of the index is the value of qubit `i`.
+	///
+	/// The function is evaluated on all 2^n bitstrings and expanded into Z
+	/// terms by a Walsh-Hadamard transform.
+	pub fn from_cost_fn<F>(qubit_count: usize, cost: F) -> Self
+	where
+		F: Fn(usize) -> f64,
+	{
+		let cost_table: Vec<f64> = (0..1usize << qubit_count).map(cost).collect();
+		let mut coefs = cost_table.clone();
+		walsh_hadamard_transform(&mut coefs);
+		let terms = coefs
+			.iter()
+			.enumerate()
+			.skip(1)
+			.filter(|(_, c)| c.abs() > COEF_EPS)
+			.map(|(s, &c)| {
+				let qubits = (0..qubit_count as u32)
+					.filter(|i| (s >> i) & 1 == 1)
+					.collect();
+				(qubits, c)
+			})
+			.collect();
+		Self {
+			qubit_count,
+			cost_table,
+			terms,
+		}
+	}
+
+	///Get qubit count
+	pub fn qubit_count(&self) -> usize {
+		self.qubit_count
+	}
+
+	/// Get the value of the cost function for each basis index.
+	pub fn cost_table(&self) -> &[f64] {
+		&self.cost_table
+	}
+
+	/// Build the p-layer QAOA circuit
+	/// H^n Π_k exp(-iβ_k Σ X_i) exp(-iγ_k C) acting on |0...0>.
+	/// * `layers` list of `(gamma, beta)`, one per layer
+	pub fn circuit(&self, layers: &[(f64, f64)]) -> Circuit {
+		let mut circuit = Circuit::new(self.qubit_count);
+		for q in 0..self.qubit_count as u32 {
+			circuit.add_gate(Gate::H(q));
+		}
+		for &(gamma, beta) in layers {
+			for (qubits, coef) in &self.terms {
+				circuit.add_gate(Gate::PauliRotation {
+					targets: qubits.clone(),
+					paulis: vec![3; qubits.len()],
+					angle: 2. * gamma * coef,
+				});
+			}
+			for q in 0..self.qubit_count as u32 {
+				circuit.add_gate(Gate::Rx(q, 2. * beta));
+			}
+		}
+		circuit
+	}
+
+	/// Compute <C> directly from the amplitudes of the quantum state.
+	pub fn expectation_value(&self, state: &[Complex<f64>]) -> Result<f64, StateErr> {
+		if state.len() != self.cost_table.len() {
+			return Err(StateErr::InconsistentStateLength(
+				self.cost_table.len(),
+				state.len(),
+			));
+		}
+		Ok(diagonal_expectation_value(state, |z| self.cost_table[z]))
+	}
+
+	/// Find the maximum of the cost function by brute force.
+	///
+	/// Returns `(basis index, cost)`.
+	pub fn optimum(&self) -> (usize, f64) {
+		self.cost_table
+			.iter()
+			.cloned()
+			.enumerate()
+			.fold((0, f64::NEG_INFINITY), |best, (z, c)| {
+				if c > best.1 {
+					(z, c)
+				} else {
+					best
+				}
+			})
+	}
+
+	/// Get <C> / C_max for the quantum state, where C_max is the brute-force
+	/// optimum. This is meaningful for non-negative cost functions such as
+	/// MaxCut.
+	///
+	/// The ratio is undefined and NaN is returned if C_max is 0, as for
+	/// MaxCut on a graph without edges.
+	pub fn approximation_ratio(&self, state: &[Complex<f64>]) -> Result<f64, StateErr> {
+		let expectation = self.expectation_value(state)?;
+		let optimum = self.optimum().1;
+		if optimum == 0. {
+			return Ok(f64::NAN);
+		}
+		Ok(expectation / optimum)
+	}
+}
+
+/// Compute the expectation value Σ_z |ψ_z|^2 C(z) of an operator that is
+/// diagonal in the computational basis.
+/// * `state` quantum state
+/// * `cost` diagonal element for each basis index
+pub fn diagonal_expectation_value<F>(state: &[Complex<f64>], cost: F) -> f64
+where
+	F: Fn(usize) -> f64,
+{
+	state
+		.iter()
+		.enumerate()
+		.map(|(z, amp)| amp.norm_sqr() * cost(z))
+		.sum()
+}
+
+/// Transform f(z) into the coefficients a_S of f(z) = Σ_S a_S (-1)^{|z∧S|}.
+fn walsh_hadamard_transform(values: &mut [f64]) {
+	let n = values.len();
+	let mut h = 1;
+	while h < n {
+		for i in (0..n).step_by(h * 2) {
+			for j in i..i + h {
+				let (a, b) = (values[j], values[j + h]);
+				values[j] = a + b;
+				values[j + h] = a - b;
+			}
+		}
+		h *= 2;
+	}
+	for v in values.iter_mut() {
+		*v /= n as f64;
+	}
+}
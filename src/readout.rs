@@ -0,0 +1,503 @@
+use crate::circuit::{Circuit, Gate};
+use crate::state::StateRef;
+use std::fmt;
+
+/// Tolerance of column sums of confusion matrices and of pivots.
+const EPS: f64 = 1e-10;
+
+#[non_exhaustive]
+pub enum ReadoutErr {
+	/// The qubit index is out of range.
+	InvalidQubit(u32),
+	/// The qubit already has a confusion matrix.
+	DuplicateQubit(u32),
+	/// The matrix has the wrong size or is not column-stochastic.
+	InvalidConfusionMatrix(Vec<f64>),
+	/// The confusion matrix cannot be inverted.
+	SingularConfusionMatrix,
+	/// Number of calibration circuits and number of sample lists
+	InconsistentDataLength(usize, usize),
+	/// The sample list of the circuit is empty.
+	NoSamples(usize),
+	/// The outcome does not fit in the qubits.
+	InvalidOutcome(u64),
+}
+
+impl fmt::Display for ReadoutErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ReadoutErr::InvalidQubit(q) => write!(f, "Invalid qubit: {} is out of range", q),
+			ReadoutErr::DuplicateQubit(q) => {
+				write!(f, "Duplicate qubit: {} already has a confusion matrix", q)
+			}
+			ReadoutErr::InvalidConfusionMatrix(m) => write!(
+				f,
+				"Invalid confusion matrix: {:?} is not a column-stochastic matrix",
+				m
+			),
+			ReadoutErr::SingularConfusionMatrix => {
+				write!(
+					f,
+					"Singular confusion matrix: the matrix cannot be inverted"
+				)
+			}
+			ReadoutErr::InconsistentDataLength(circuits, samples) => write!(
+				f,
+				"Inconsistent data length: {} circuits were measured, but {} sample lists were supplied",
+				circuits, samples
+			),
+			ReadoutErr::NoSamples(i) => write!(f, "No samples: circuit {} has no samples", i),
+			ReadoutErr::InvalidOutcome(outcome) => {
+				write!(f, "Invalid outcome: {} is out of range", outcome)
+			}
+		}
+	}
+}
+
+impl fmt::Debug for ReadoutErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		<Self as fmt::Display>::fmt(self, f)
+	}
+}
+
+/// Confusion matrices of disjoint groups of qubits. The matrix of a group of
+/// k qubits is a row-major 2^k × 2^k column-stochastic matrix whose element at
+/// (m, p) is the probability of measuring m when p is prepared, with bit i of
+/// the indices for the i-th qubit of the group.
+type Groups = Vec<(Vec<u32>, Vec<f64>)>;
+
+/// Readout noise flipping sampled outcomes. Qubits without a confusion matrix
+/// are read perfectly.
+///
+/// ```
+/// # use qurs::readout::ReadoutNoise;
+/// let mut noise = ReadoutNoise::new(2);
+/// noise.add_qubit_error(0, 1., 0.).unwrap();
+/// assert_eq!(noise.apply(&[0b00, 0b10]), vec![0b01, 0b11]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadoutNoise {
+	qubit_count: usize,
+	groups: Groups,
+}
+
+impl ReadoutNoise {
+	/// Perfect readout of `qubit_count` qubits
+	pub fn new(qubit_count: usize) -> Self {
+		Self {
+			qubit_count,
+			groups: Vec::new(),
+		}
+	}
+
+	/// Add the independent error of qubit `q`, reading 1 from |0> with
+	/// probability `prob_1_given_0` and 0 from |1> with `prob_0_given_1`.
+	pub fn add_qubit_error(
+		&mut self,
+		q: u32,
+		prob_1_given_0: f64,
+		prob_0_given_1: f64,
+	) -> Result<(), ReadoutErr> {
+		self.add_correlated_error(
+			&[q],
+			vec![
+				1. - prob_1_given_0,
+				prob_0_given_1,
+				prob_1_given_0,
+				1. - prob_0_given_1,
+			],
+		)
+	}
+
+	/// Add the joint confusion matrix `matrix` of `qubits`. See `groups` for
+	/// the layout of the matrix.
+	pub fn add_correlated_error(
+		&mut self,
+		qubits: &[u32],
+		matrix: Vec<f64>,
+	) -> Result<(), ReadoutErr> {
+		check_qubits(self.qubit_count, &self.groups, qubits)?;
+		check_confusion_matrix(qubits.len(), &matrix)?;
+		self.groups.push((qubits.to_vec(), matrix));
+		Ok(())
+	}
+
+	///Get qubit count
+	pub fn qubit_count(&self) -> usize {
+		self.qubit_count
+	}
+
+	/// Get the qubits and the confusion matrix of each group. The element at
+	/// (m, p) of the row-major 2^k × 2^k matrix of a group of k qubits is the
+	/// probability of measuring m when p is prepared, with bit i of the
+	/// indices for the i-th qubit of the group.
+	pub fn groups(&self) -> &[(Vec<u32>, Vec<f64>)] {
+		&self.groups
+	}
+
+	/// Get the full row-major 2^n × 2^n confusion matrix.
+	pub fn matrix(&self) -> Vec<f64> {
+		full_matrix(self.qubit_count, &self.groups)
+	}
+
+	/// Get the noisy outcomes of ideal `samples` such as those of
+	/// `StateRef::sampling`.
+	pub fn apply(&self, samples: &[u64]) -> Vec<u64> {
+		samples
+			.iter()
+			.map(|&sample| {
+				let mut outcome = sample;
+				for (qubits, matrix) in &self.groups {
+					let dim = 1 << qubits.len();
+					let prepared = gather(sample, qubits);
+					let mut r = rand::random::<f64>();
+					let mut measured = dim - 1;
+					for m in 0..dim {
+						r -= matrix[m * dim + prepared];
+						if r < 0. {
+							measured = m;
+							break;
+						}
+					}
+					outcome = scatter(outcome, qubits, measured);
+				}
+				outcome
+			})
+			.collect()
+	}
+
+	/// Sample `state` `sampling_count` times with the readout noise.
+	pub fn sampling<T>(&self, state: &T, sampling_count: u32) -> Vec<u64>
+	where
+		T: StateRef<f64>,
+	{
+		self.apply(&state.sampling(sampling_count))
+	}
+}
+
+/// Calibration of the confusion matrix from measurements of basis states.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Calibration {
+	/// Prepare each of the 2^n basis states and measure the full confusion
+	/// matrix, including correlations.
+	Full,
+	/// Prepare |0...0> and |1...1> and measure the confusion matrix of each
+	/// qubit, neglecting correlations.
+	Tensored,
+}
+
+impl Calibration {
+	/// Get the circuits preparing the basis states to be measured.
+	pub fn circuits(self, qubit_count: usize) -> Vec<Circuit> {
+		let states: Vec<usize> = match self {
+			Calibration::Full => (0..1 << qubit_count).collect(),
+			Calibration::Tensored => vec![0, (1 << qubit_count) - 1],
+		};
+		states
+			.into_iter()
+			.map(|state| {
+				let mut circuit = Circuit::new(qubit_count);
+				for q in 0..qubit_count {
+					if (state >> q) & 1 == 1 {
+						circuit.add_gate(Gate::X(q as u32));
+					}
+				}
+				circuit
+			})
+			.collect()
+	}
+}
+
+/// Measurement error mitigation by the confusion matrix A, estimating the
+/// ideal distribution p from the measured distribution m = A p.
+///
+/// ```
+/// # use qurs::readout::{ReadoutMitigation, ReadoutNoise};
+/// let mut noise = ReadoutNoise::new(1);
+/// noise.add_qubit_error(0, 0.1, 0.2).unwrap();
+/// let mitigation = ReadoutMitigation::from_noise(&noise).unwrap();
+/// let samples = vec![0; 90].into_iter().chain(vec![1; 10]).collect::<Vec<u64>>();
+/// let p = mitigation.invert(&samples).unwrap();
+/// assert!((p[0] - 1.).abs() < 1e-10);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadoutMitigation {
+	qubit_count: usize,
+	groups: Groups,
+	inverses: Groups,
+}
+
+impl ReadoutMitigation {
+	/// Build the mitigation of the known `noise`.
+	pub fn from_noise(noise: &ReadoutNoise) -> Result<Self, ReadoutErr> {
+		Self::from_groups(noise.qubit_count, noise.groups.clone())
+	}
+
+	/// Build the mitigation from `samples` of each of `calibration.circuits(qubit_count)`.
+	pub fn from_calibration(
+		calibration: Calibration,
+		qubit_count: usize,
+		samples: &[Vec<u64>],
+	) -> Result<Self, ReadoutErr> {
+		let circuit_count = match calibration {
+			Calibration::Full => 1 << qubit_count,
+			Calibration::Tensored => 2,
+		};
+		check_samples(circuit_count, samples)?;
+		let frequencies = samples
+			.iter()
+			.map(|s| distribution(qubit_count, s))
+			.collect::<Result<Vec<_>, _>>()?;
+		let groups = match calibration {
+			Calibration::Full => {
+				let dim = 1 << qubit_count;
+				let mut matrix = vec![0.; dim * dim];
+				for (prepared, frequency) in frequencies.iter().enumerate() {
+					for (measured, f) in frequency.iter().enumerate() {
+						matrix[measured * dim + prepared] = *f;
+					}
+				}
+				vec![((0..qubit_count as u32).collect(), matrix)]
+			}
+			Calibration::Tensored => (0..qubit_count)
+				.map(|q| {
+					let one = |frequency: &[f64]| -> f64 {
+						frequency
+							.iter()
+							.enumerate()
+							.filter(|(m, _)| (m >> q) & 1 == 1)
+							.map(|(_, f)| f)
+							.sum()
+					};
+					let p10 = one(&frequencies[0]);
+					let p11 = one(&frequencies[1]);
+					(vec![q as u32], vec![1. - p10, 1. - p11, p10, p11])
+				})
+				.collect(),
+		};
+		Self::from_groups(qubit_count, groups)
+	}
+
+	fn from_groups(qubit_count: usize, groups: Groups) -> Result<Self, ReadoutErr> {
+		let inverses = groups
+			.iter()
+			.map(|(qubits, matrix)| Ok((qubits.clone(), inverse(1 << qubits.len(), matrix)?)))
+			.collect::<Result<_, _>>()?;
+		Ok(Self {
+			qubit_count,
+			groups,
+			inverses,
+		})
+	}
+
+	///Get qubit count
+	pub fn qubit_count(&self) -> usize {
+		self.qubit_count
+	}
+
+	/// Get the full row-major 2^n × 2^n confusion matrix.
+	pub fn matrix(&self) -> Vec<f64> {
+		full_matrix(self.qubit_count, &self.groups)
+	}
+
+	/// Estimate the ideal distribution of outcomes by A^-1 m from the noisy
+	/// `samples`. The result sums to 1 but may have negative entries.
+	pub fn invert(&self, samples: &[u64]) -> Result<Vec<f64>, ReadoutErr> {
+		Ok(apply_groups(
+			&self.inverses,
+			&distribution(self.qubit_count, samples)?,
+		))
+	}
+
+	/// Estimate the ideal distribution of outcomes from the noisy `samples`
+	/// by iterative Bayesian unfolding, p_(k+1)(j) = Σ_i m_i A_ij p_k(j) /
+	/// (A p_k)_i, starting from the uniform distribution. Stops after
+	/// `max_iterations` iterations or when no entry changes by more than
+	/// `tolerance`.
+	///
+	/// The result is a probability distribution.
+	pub fn iterative_bayesian_unfolding(
+		&self,
+		samples: &[u64],
+		max_iterations: usize,
+		tolerance: f64,
+	) -> Result<Vec<f64>, ReadoutErr> {
+		let measured = distribution(self.qubit_count, samples)?;
+		let dim = 1 << self.qubit_count;
+		let transposed: Groups = self
+			.groups
+			.iter()
+			.map(|(qubits, matrix)| {
+				let d = 1 << qubits.len();
+				let t = (0..d * d).map(|i| matrix[(i % d) * d + i / d]).collect();
+				(qubits.clone(), t)
+			})
+			.collect();
+		let mut p = vec![1. / dim as f64; dim];
+		for _ in 0..max_iterations {
+			let predicted = apply_groups(&self.groups, &p);
+			let ratio: Vec<f64> = measured
+				.iter()
+				.zip(&predicted)
+				.map(|(m, a)| if *m == 0. { 0. } else { m / a.max(EPS * EPS) })
+				.collect();
+			let next: Vec<f64> = apply_groups(&transposed, &ratio)
+				.iter()
+				.zip(&p)
+				.map(|(r, p)| r * p)
+				.collect();
+			let change = next
+				.iter()
+				.zip(&p)
+				.map(|(a, b)| (a - b).abs())
+				.fold(0., f64::max);
+			p = next;
+			if change <= tolerance {
+				break;
+			}
+		}
+		Ok(p)
+	}
+}
+
+fn check_qubits(qubit_count: usize, groups: &Groups, qubits: &[u32]) -> Result<(), ReadoutErr> {
+	for (i, &q) in qubits.iter().enumerate() {
+		if q as usize >= qubit_count {
+			return Err(ReadoutErr::InvalidQubit(q));
+		}
+		if qubits[..i].contains(&q) || groups.iter().any(|(g, _)| g.contains(&q)) {
+			return Err(ReadoutErr::DuplicateQubit(q));
+		}
+	}
+	Ok(())
+}
+
+fn check_confusion_matrix(qubit_count: usize, matrix: &[f64]) -> Result<(), ReadoutErr> {
+	let dim = 1 << qubit_count;
+	let valid = matrix.len() == dim * dim
+		&& matrix.iter().all(|&p| (0. ..=1.).contains(&p))
+		&& (0..dim).all(|c| ((0..dim).map(|r| matrix[r * dim + c]).sum::<f64>() - 1.).abs() < EPS);
+	if valid {
+		Ok(())
+	} else {
+		Err(ReadoutErr::InvalidConfusionMatrix(matrix.to_vec()))
+	}
+}
+
+fn check_samples(circuit_count: usize, samples: &[Vec<u64>]) -> Result<(), ReadoutErr> {
+	if samples.len() != circuit_count {
+		return Err(ReadoutErr::InconsistentDataLength(
+			circuit_count,
+			samples.len(),
+		));
+	}
+	match samples.iter().position(|s| s.is_empty()) {
+		Some(i) => Err(ReadoutErr::NoSamples(i)),
+		None => Ok(()),
+	}
+}
+
+/// Get the relative frequency of each outcome of `samples`.
+fn distribution(qubit_count: usize, samples: &[u64]) -> Result<Vec<f64>, ReadoutErr> {
+	if samples.is_empty() {
+		return Err(ReadoutErr::NoSamples(0));
+	}
+	let mut frequency = vec![0.; 1 << qubit_count];
+	for &outcome in samples {
+		*frequency
+			.get_mut(outcome as usize)
+			.ok_or(ReadoutErr::InvalidOutcome(outcome))? += 1. / samples.len() as f64;
+	}
+	Ok(frequency)
+}
+
+/// Get the bits of `index` at `qubits` as an index of the group.
+fn gather(index: u64, qubits: &[u32]) -> usize {
+	qubits
+		.iter()
+		.enumerate()
+		.fold(0, |sub, (i, &q)| sub | (((index >> q) & 1) as usize) << i)
+}
+
+/// Replace the bits of `index` at `qubits` with those of the group index `sub`.
+fn scatter(index: u64, qubits: &[u32], sub: usize) -> u64 {
+	qubits.iter().enumerate().fold(index, |index, (i, &q)| {
+		(index & !(1 << q)) | (((sub >> i) & 1) as u64) << q
+	})
+}
+
+/// Multiply the matrix of each group on its qubits of `vector`.
+fn apply_groups(groups: &Groups, vector: &[f64]) -> Vec<f64> {
+	let mut vector = vector.to_vec();
+	for (qubits, matrix) in groups {
+		let dim = 1 << qubits.len();
+		let mask = qubits.iter().fold(0, |mask, &q| mask | 1 << q);
+		let mut next = vec![0.; vector.len()];
+		for (index, &v) in vector.iter().enumerate() {
+			if v == 0. {
+				continue;
+			}
+			let column = gather(index as u64, qubits);
+			for row in 0..dim {
+				next[scatter((index & !mask) as u64, qubits, row) as usize] +=
+					matrix[row * dim + column] * v;
+			}
+		}
+		vector = next;
+	}
+	vector
+}
+
+fn full_matrix(qubit_count: usize, groups: &Groups) -> Vec<f64> {
+	let dim = 1 << qubit_count;
+	let mut matrix = vec![0.; dim * dim];
+	for column in 0..dim {
+		let mut basis = vec![0.; dim];
+		basis[column] = 1.;
+		for (row, v) in apply_groups(groups, &basis).into_iter().enumerate() {
+			matrix[row * dim + column] = v;
+		}
+	}
+	matrix
+}
+
+/// Invert the row-major `dim` × `dim` `matrix` by Gauss-Jordan elimination
+/// with partial pivoting.
+fn inverse(dim: usize, matrix: &[f64]) -> Result<Vec<f64>, ReadoutErr> {
+	let mut a = matrix.to_vec();
+	let mut inv = vec![0.; dim * dim];
+	for i in 0..dim {
+		inv[i * dim + i] = 1.;
+	}
+	for col in 0..dim {
+		let pivot = (col..dim)
+			.max_by(|&i, &j| a[i * dim + col].abs().total_cmp(&a[j * dim + col].abs()))
+			.unwrap();
+		if a[pivot * dim + col].abs() < EPS {
+			return Err(ReadoutErr::SingularConfusionMatrix);
+		}
+		for k in 0..dim {
+			a.swap(col * dim + k, pivot * dim + k);
+			inv.swap(col * dim + k, pivot * dim + k);
+		}
+		let scale = a[col * dim + col];
+		for k in 0..dim {
+			a[col * dim + k] /= scale;
+			inv[col * dim + k] /= scale;
+		}
+		for row in 0..dim {
+			if row == col {
+				continue;
+			}
+			let factor = a[row * dim + col];
+			if factor == 0. {
+				continue;
+			}
+			for k in 0..dim {
+				a[row * dim + k] -= factor * a[col * dim + k];
+				inv[row * dim + k] -= factor * inv[col * dim + k];
+			}
+		}
+	}
+	Ok(inv)
+}
@@ -0,0 +1,472 @@
+use crate::circuit::{Circuit, Gate};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Weight of the extended set relative to the front layer in the SABRE cost.
+const EXTENDED_SET_WEIGHT: f64 = 0.5;
+/// Maximum number of gates looked ahead for the extended set.
+const EXTENDED_SET_SIZE: usize = 20;
+/// Increase of the decay factor of a qubit each time it is swapped.
+const DECAY_DELTA: f64 = 0.001;
+/// Number of swaps without progress after which the decay factors are reset.
+const DECAY_RESET_INTERVAL: usize = 5;
+
+/// Undirected connectivity graph of the physical qubits of a device.
+///
+/// ```
+/// # use qurs::routing::CouplingMap;
+/// let map = CouplingMap::new(4, &[(0, 1), (1, 2), (2, 3)]).unwrap();
+/// assert!(map.is_adjacent(2, 1));
+/// assert_eq!(map.distance(0, 3), 3);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CouplingMap {
+	qubit_count: usize,
+	edges: Vec<(u32, u32)>,
+	distances: Vec<Vec<usize>>,
+}
+
+impl CouplingMap {
+	/// Build the map of `qubit_count` physical qubits connected by `edges`.
+	/// Each edge can be used in both directions.
+	///
+	/// Returns an error if an edge refers to a qubit out of range or the graph
+	/// is not connected.
+	pub fn new(qubit_count: usize, edges: &[(u32, u32)]) -> Result<Self, RoutingErr> {
+		let mut neighbours = vec![Vec::new(); qubit_count];
+		for &(a, b) in edges {
+			if a == b || a as usize >= qubit_count || b as usize >= qubit_count {
+				return Err(RoutingErr::InvalidEdge(a, b));
+			}
+			neighbours[a as usize].push(b as usize);
+			neighbours[b as usize].push(a as usize);
+		}
+		let mut distances = Vec::with_capacity(qubit_count);
+		for start in 0..qubit_count {
+			let mut distance = vec![usize::MAX; qubit_count];
+			distance[start] = 0;
+			let mut queue = VecDeque::from(vec![start]);
+			while let Some(q) = queue.pop_front() {
+				for &n in &neighbours[q] {
+					if distance[n] == usize::MAX {
+						distance[n] = distance[q] + 1;
+						queue.push_back(n);
+					}
+				}
+			}
+			if distance.contains(&usize::MAX) {
+				return Err(RoutingErr::DisconnectedCouplingMap);
+			}
+			distances.push(distance);
+		}
+		Ok(Self {
+			qubit_count,
+			edges: edges.to_vec(),
+			distances,
+		})
+	}
+
+	/// Get the map of `qubit_count` qubits connected in a line.
+	pub fn line(qubit_count: usize) -> Self {
+		let edges: Vec<(u32, u32)> = (1..qubit_count as u32).map(|q| (q - 1, q)).collect();
+		Self::new(qubit_count, &edges).unwrap()
+	}
+
+	/// Get the map of `rows`×`columns` qubits connected in a square grid,
+	/// numbered row by row.
+	pub fn grid(rows: usize, columns: usize) -> Self {
+		let mut edges = Vec::new();
+		for r in 0..rows {
+			for c in 0..columns {
+				let q = (r * columns + c) as u32;
+				if c + 1 < columns {
+					edges.push((q, q + 1));
+				}
+				if r + 1 < rows {
+					edges.push((q, q + columns as u32));
+				}
+			}
+		}
+		Self::new(rows * columns, &edges).unwrap()
+	}
+
+	///Get qubit count
+	pub fn qubit_count(&self) -> usize {
+		self.qubit_count
+	}
+
+	///Get edges
+	pub fn edges(&self) -> &[(u32, u32)] {
+		&self.edges
+	}
+
+	/// Whether physical qubits `a` and `b` are connected by an edge.
+	pub fn is_adjacent(&self, a: u32, b: u32) -> bool {
+		self.distance(a, b) == 1
+	}
+
+	/// Get the length of the shortest path between physical qubits `a` and
+	/// `b`.
+	pub fn distance(&self, a: u32, b: u32) -> usize {
+		self.distances[a as usize][b as usize]
+	}
+}
+
+#[non_exhaustive]
+pub enum RoutingErr {
+	/// The edge is a self loop or refers to a qubit out of range.
+	InvalidEdge(u32, u32),
+	DisconnectedCouplingMap,
+	/// The circuit has more qubits than the device.
+	TooManyQubits(usize, usize),
+	/// The initial layout is not an injective map into the physical qubits.
+	InvalidLayout(Vec<u32>),
+	/// The gate acts on more than two qubits.
+	UnsupportedGate(Gate),
+}
+
+impl fmt::Display for RoutingErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			RoutingErr::InvalidEdge(a, b) => write!(f, "Invalid edge: ({}, {})", a, b),
+			RoutingErr::DisconnectedCouplingMap => {
+				write!(f, "Disconnected coupling map: some qubits cannot interact")
+			}
+			RoutingErr::TooManyQubits(logical, physical) => write!(
+				f,
+				"Too many qubits: the circuit has {} qubits but the device has {}",
+				logical, physical
+			),
+			RoutingErr::InvalidLayout(layout) => write!(f, "Invalid layout: {:?}", layout),
+			RoutingErr::UnsupportedGate(gate) => write!(
+				f,
+				"Unsupported gate: {:?} acts on more than two qubits",
+				gate
+			),
+		}
+	}
+}
+
+impl fmt::Debug for RoutingErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		<Self as fmt::Display>::fmt(self, f)
+	}
+}
+
+/// A circuit rewritten onto physical qubits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoutedCircuit {
+	circuit: Circuit,
+	initial_layout: Vec<u32>,
+	final_layout: Vec<u32>,
+	swap_count: usize,
+}
+
+impl RoutedCircuit {
+	/// Get the circuit on the physical qubits, where every two-qubit gate acts
+	/// on adjacent qubits.
+	pub fn circuit(&self) -> &Circuit {
+		&self.circuit
+	}
+
+	/// Get the physical qubit holding each logical qubit at the beginning.
+	/// Entry `l` is the physical qubit of logical qubit `l`. Physical qubits
+	/// not used by the input circuit are assigned to the extra logical qubits
+	/// in increasing order, so this is a permutation of all physical qubits.
+	pub fn initial_layout(&self) -> &[u32] {
+		&self.initial_layout
+	}
+
+	/// Get the physical qubit holding each logical qubit at the end, in the
+	/// same form as `initial_layout`.
+	///
+	/// Passing it to `permutate_qubit` reorders a state of the physical
+	/// qubits into logical order.
+	pub fn final_layout(&self) -> &[u32] {
+		&self.final_layout
+	}
+
+	/// Get the number of inserted `Gate::Swap`.
+	pub fn swap_count(&self) -> usize {
+		self.swap_count
+	}
+}
+
+/// Map `circuit` onto the physical qubits of `coupling_map`, inserting
+/// `Gate::Swap` with the SABRE heuristic (Li, Ding and Xie, ASPLOS 2019) so
+/// that every two-qubit gate acts on adjacent qubits.
+///
+/// `initial_layout[l]` is the physical qubit of logical qubit `l`. Gates of
+/// three or more qubits must be decomposed first, for example by
+/// `transpiler::transpile`.
+///
+/// ```
+/// # use qurs::circuit::{Circuit, Gate};
+/// # use qurs::routing::{route, CouplingMap};
+/// let mut circuit = Circuit::new(3);
+/// circuit.add_gate(Gate::Cnot(0, 2));
+/// let routed = route(&circuit, &CouplingMap::line(3), &[0, 1, 2]).unwrap();
+/// assert_eq!(routed.swap_count(), 1);
+/// ```
+pub fn route(
+	circuit: &Circuit,
+	coupling_map: &CouplingMap,
+	initial_layout: &[u32],
+) -> Result<RoutedCircuit, RoutingErr> {
+	let physical_count = coupling_map.qubit_count();
+	if circuit.qubit_count() > physical_count {
+		return Err(RoutingErr::TooManyQubits(
+			circuit.qubit_count(),
+			physical_count,
+		));
+	}
+	let layout = complete_layout(initial_layout, circuit.qubit_count(), physical_count)?;
+	for gate in circuit.gates() {
+		if gate.qubits().len() > 2 {
+			return Err(RoutingErr::UnsupportedGate(gate.clone()));
+		}
+	}
+
+	let mut router = Router::new(circuit, coupling_map, layout.clone());
+	router.run();
+	Ok(RoutedCircuit {
+		circuit: router.routed,
+		initial_layout: layout,
+		final_layout: router.layout,
+		swap_count: router.swap_count,
+	})
+}
+
+/// Check `layout` and extend it to all physical qubits.
+fn complete_layout(
+	layout: &[u32],
+	logical_count: usize,
+	physical_count: usize,
+) -> Result<Vec<u32>, RoutingErr> {
+	let mut used = vec![false; physical_count];
+	if layout.len() != logical_count {
+		return Err(RoutingErr::InvalidLayout(layout.to_vec()));
+	}
+	for &p in layout {
+		if p as usize >= physical_count || used[p as usize] {
+			return Err(RoutingErr::InvalidLayout(layout.to_vec()));
+		}
+		used[p as usize] = true;
+	}
+	let mut layout = layout.to_vec();
+	layout.extend((0..physical_count as u32).filter(|&p| !used[p as usize]));
+	Ok(layout)
+}
+
+struct Router<'a> {
+	gates: &'a [Gate],
+	coupling_map: &'a CouplingMap,
+	/// Logical to physical
+	layout: Vec<u32>,
+	/// Physical to logical
+	inverse: Vec<u32>,
+	/// Number of unexecuted predecessors of each gate
+	pending: Vec<usize>,
+	successors: Vec<Vec<usize>>,
+	front: Vec<usize>,
+	decay: Vec<f64>,
+	routed: Circuit,
+	swap_count: usize,
+}
+
+impl<'a> Router<'a> {
+	fn new(circuit: &'a Circuit, coupling_map: &'a CouplingMap, layout: Vec<u32>) -> Self {
+		let gates = circuit.gates();
+		let mut last = vec![None; circuit.qubit_count()];
+		let mut pending = vec![0; gates.len()];
+		let mut successors = vec![Vec::new(); gates.len()];
+		for (i, gate) in gates.iter().enumerate() {
+			for q in gate.qubits() {
+				if let Some(j) = last[q as usize].replace(i) {
+					if !successors[j].contains(&i) {
+						successors[j].push(i);
+						pending[i] += 1;
+					}
+				}
+			}
+		}
+		let front = (0..gates.len()).filter(|&i| pending[i] == 0).collect();
+		let mut inverse = vec![0; layout.len()];
+		for (l, &p) in layout.iter().enumerate() {
+			inverse[p as usize] = l as u32;
+		}
+		Self {
+			gates,
+			coupling_map,
+			layout,
+			inverse,
+			pending,
+			successors,
+			front,
+			decay: vec![1.; coupling_map.qubit_count()],
+			routed: Circuit::new(coupling_map.qubit_count()),
+			swap_count: 0,
+		}
+	}
+
+	fn physical(&self, gate: usize) -> Vec<u32> {
+		self.gates[gate]
+			.qubits()
+			.iter()
+			.map(|&q| self.layout[q as usize])
+			.collect()
+	}
+
+	fn is_executable(&self, gate: usize) -> bool {
+		match self.physical(gate)[..] {
+			[a, b] => self.coupling_map.is_adjacent(a, b),
+			_ => true,
+		}
+	}
+
+	fn run(&mut self) {
+		let mut swaps_without_progress = 0;
+		while !self.front.is_empty() {
+			if self.execute_front() {
+				swaps_without_progress = 0;
+				self.decay.iter_mut().for_each(|d| *d = 1.);
+				continue;
+			}
+			if swaps_without_progress >= 2 * self.coupling_map.qubit_count() {
+				// Guarantee termination by moving the qubits of one gate
+				// together along a shortest path.
+				self.force_route(self.front[0]);
+				continue;
+			}
+			let (a, b) = self.best_swap();
+			self.swap(a, b);
+			swaps_without_progress += 1;
+			if swaps_without_progress % DECAY_RESET_INTERVAL == 0 {
+				self.decay.iter_mut().for_each(|d| *d = 1.);
+			} else {
+				self.decay[a as usize] += DECAY_DELTA;
+				self.decay[b as usize] += DECAY_DELTA;
+			}
+		}
+	}
+
+	/// Apply all executable gates of the front layer until none is left.
+	/// Returns whether any gate was applied.
+	fn execute_front(&mut self) -> bool {
+		let mut progress = false;
+		while let Some(k) = (0..self.front.len()).find(|&k| self.is_executable(self.front[k])) {
+			let i = self.front.remove(k);
+			let layout = &self.layout;
+			let gate = self.gates[i].map_qubits(|q| layout[q as usize]);
+			self.routed.add_gate(gate);
+			for &j in &self.successors[i] {
+				self.pending[j] -= 1;
+				if self.pending[j] == 0 {
+					self.front.push(j);
+				}
+			}
+			progress = true;
+		}
+		progress
+	}
+
+	/// Two-qubit gates following the front layer, in topological order.
+	fn extended_set(&self) -> Vec<usize> {
+		let mut pending = self.pending.clone();
+		let mut queue: VecDeque<usize> = self.front.iter().cloned().collect();
+		let mut extended = Vec::new();
+		while let Some(i) = queue.pop_front() {
+			for &j in &self.successors[i] {
+				pending[j] -= 1;
+				if pending[j] == 0 {
+					if self.gates[j].qubits().len() == 2 {
+						extended.push(j);
+						if extended.len() >= EXTENDED_SET_SIZE {
+							return extended;
+						}
+					}
+					queue.push_back(j);
+				}
+			}
+		}
+		extended
+	}
+
+	fn total_distance(&self, gates: &[usize], layout: &[u32]) -> f64 {
+		gates
+			.iter()
+			.filter_map(|&i| match self.gates[i].qubits()[..] {
+				[a, b] => Some(
+					self.coupling_map
+						.distance(layout[a as usize], layout[b as usize]) as f64,
+				),
+				_ => None,
+			})
+			.sum()
+	}
+
+	/// The swap on an edge touching the front layer with the lowest cost.
+	fn best_swap(&self) -> (u32, u32) {
+		let front: Vec<usize> = self
+			.front
+			.iter()
+			.cloned()
+			.filter(|&i| self.gates[i].qubits().len() == 2)
+			.collect();
+		let extended = self.extended_set();
+		let active: Vec<u32> = front.iter().flat_map(|&i| self.physical(i)).collect();
+		let mut best = None;
+		let mut best_cost = f64::INFINITY;
+		for &(a, b) in self.coupling_map.edges() {
+			if !active.contains(&a) && !active.contains(&b) {
+				continue;
+			}
+			let mut layout = self.layout.clone();
+			let (la, lb) = (self.inverse[a as usize], self.inverse[b as usize]);
+			layout.swap(la as usize, lb as usize);
+			let mut cost = self.total_distance(&front, &layout) / front.len() as f64;
+			if !extended.is_empty() {
+				cost += EXTENDED_SET_WEIGHT * self.total_distance(&extended, &layout)
+					/ extended.len() as f64;
+			}
+			cost *= self.decay[a as usize].max(self.decay[b as usize]);
+			if cost < best_cost {
+				best_cost = cost;
+				best = Some((a, b));
+			}
+		}
+		best.expect("the front layer has a two-qubit gate on a connected map")
+	}
+
+	fn swap(&mut self, a: u32, b: u32) {
+		let (la, lb) = (self.inverse[a as usize], self.inverse[b as usize]);
+		self.layout.swap(la as usize, lb as usize);
+		self.inverse.swap(a as usize, b as usize);
+		self.routed.add_gate(Gate::Swap(a, b));
+		self.swap_count += 1;
+	}
+
+	/// Swap the first qubit of `gate` towards the second until they are
+	/// adjacent.
+	fn force_route(&mut self, gate: usize) {
+		let (mut a, b) = match self.physical(gate)[..] {
+			[a, b] => (a, b),
+			_ => return,
+		};
+		while !self.coupling_map.is_adjacent(a, b) {
+			let next = self
+				.coupling_map
+				.edges()
+				.iter()
+				.filter_map(|&(p, q)| match (p == a, q == a) {
+					(true, _) => Some(q),
+					(_, true) => Some(p),
+					_ => None,
+				})
+				.min_by_key(|&n| self.coupling_map.distance(n, b))
+				.unwrap();
+			self.swap(a, next);
+			a = next;
+		}
+	}
+}
@@ -0,0 +1,521 @@
+use crate::circuit::{Circuit, Gate};
+use crate::measurement::Tableau;
+use crate::observable::Observable;
+use crate::pauli::{Pauli, PauliString};
+use crate::state::{StateErr, StateMut, StateVec};
+use crate::Complex;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Random unitaries applied before measuring in the computational basis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShadowEnsemble {
+	/// A uniformly random Pauli basis X, Y or Z for each qubit
+	Pauli,
+	/// A random global Clifford circuit of `clifford_depth(n)` layers, each of
+	/// uniformly random single-qubit Cliffords followed by `Cnot` on a random
+	/// pairing of qubits. The distribution approaches the uniform one over the
+	/// Clifford group as the depth grows.
+	Clifford,
+}
+
+/// Get the number of layers of random Clifford circuits on `qubit_count`
+/// qubits.
+pub fn clifford_depth(qubit_count: usize) -> usize {
+	2 * qubit_count + 8
+}
+
+/// One measurement outcome with the random unitary applied before it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Snapshot {
+	/// Qubit q was measured in the eigenbasis of `bases[q]`.
+	Pauli { bases: Vec<Pauli>, outcome: u64 },
+	/// The state was measured after `circuit`.
+	Clifford { circuit: Circuit, outcome: u64 },
+}
+
+/// Median-of-means estimate (Huang, Kueng and Preskill, 2020).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowEstimate {
+	/// Median of the batch means
+	pub value: f64,
+	/// Half width of the confidence interval, sqrt(34 Var) with the sample
+	/// variance Var of the batch means
+	pub error: f64,
+	/// Probability 2 exp(-K/2) for K batches bounding the chance of
+	/// |`value` - true value| > `error`
+	pub failure_probability: f64,
+}
+
+/// Classical shadow of a quantum state: a collection of snapshots from which
+/// many properties are estimated.
+///
+/// ```
+/// # use qurs::shadow::{ClassicalShadow, ShadowEnsemble};
+/// # use qurs::pauli::{Pauli, PauliString};
+/// # use qurs::StateVec;
+/// let state = StateVec::new(3);
+/// let shadow = ClassicalShadow::sample(state.as_ref(), ShadowEnsemble::Pauli, 3000).unwrap();
+/// let zz = PauliString::new(&[0, 1], &[Pauli::Z, Pauli::Z]).unwrap();
+/// let estimate = shadow.pauli_expectation_value(&zz, 10);
+/// assert!((estimate.value - 1.).abs() < 0.3);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ClassicalShadow {
+	qubit_count: usize,
+	snapshots: Vec<Snapshot>,
+}
+
+impl ClassicalShadow {
+	/// Measure `snapshot_count` copies of `state` after random unitaries of
+	/// `ensemble`.
+	pub fn sample(
+		state: &[Complex<f64>],
+		ensemble: ShadowEnsemble,
+		snapshot_count: usize,
+	) -> Result<Self, StateErr> {
+		Self::sample_with_rng(state, ensemble, snapshot_count, &mut rand::thread_rng())
+	}
+
+	/// Same as `sample`, drawing the unitaries and measurement outcomes from
+	/// `rng` for reproducible shadows.
+	pub fn sample_with_rng<R: Rng>(
+		state: &[Complex<f64>],
+		ensemble: ShadowEnsemble,
+		snapshot_count: usize,
+		rng: &mut R,
+	) -> Result<Self, StateErr> {
+		if !state.len().is_power_of_two() {
+			return Err(StateErr::InconsistentStateLength(
+				state.len().next_power_of_two(),
+				state.len(),
+			));
+		}
+		let qubit_count = state.len().trailing_zeros() as usize;
+		let mut snapshots = Vec::with_capacity(snapshot_count);
+		for _ in 0..snapshot_count {
+			let mut circuit = Circuit::new(qubit_count);
+			let mut bases = Vec::new();
+			match ensemble {
+				ShadowEnsemble::Pauli => {
+					for q in 0..qubit_count as u32 {
+						let basis = [Pauli::X, Pauli::Y, Pauli::Z][rng.gen_range(0..3)];
+						match basis {
+							Pauli::X => circuit.add_gate(Gate::H(q)),
+							Pauli::Y => {
+								circuit.add_gate(Gate::Sdag(q));
+								circuit.add_gate(Gate::H(q));
+							}
+							_ => {}
+						}
+						bases.push(basis);
+					}
+				}
+				ShadowEnsemble::Clifford => {
+					random_clifford(&mut circuit, rng);
+				}
+			}
+			let mut rotated = StateVec::new(qubit_count);
+			rotated.as_mut().copy_from_slice(state);
+			circuit.update_quantum_state(rotated.as_mut())?;
+			let outcome = sample_outcome(rotated.as_ref(), rng.gen());
+			snapshots.push(match ensemble {
+				ShadowEnsemble::Pauli => Snapshot::Pauli { bases, outcome },
+				ShadowEnsemble::Clifford => Snapshot::Clifford { circuit, outcome },
+			});
+		}
+		Ok(Self {
+			qubit_count,
+			snapshots,
+		})
+	}
+
+	/// Build a shadow from snapshots on `qubit_count` qubits.
+	pub fn from_snapshots(qubit_count: usize, snapshots: Vec<Snapshot>) -> Self {
+		Self {
+			qubit_count,
+			snapshots,
+		}
+	}
+
+	///Get qubit count
+	pub fn qubit_count(&self) -> usize {
+		self.qubit_count
+	}
+
+	///Get snapshots
+	pub fn snapshots(&self) -> &[Snapshot] {
+		&self.snapshots
+	}
+
+	/// Estimate <P> by the median of means over `batches` batches.
+	///
+	/// # Panics
+	/// Panics if `batches` is less than 2 or more than the number of
+	/// snapshots.
+	pub fn pauli_expectation_value(&self, pauli: &PauliString, batches: usize) -> ShadowEstimate {
+		let values: Vec<f64> = self
+			.snapshots
+			.iter()
+			.map(|s| self.pauli_snapshot(s, pauli))
+			.collect();
+		median_of_means(batches, values.len(), |range| {
+			values[range].iter().sum::<f64>()
+		})
+	}
+
+	/// Estimate the expectation value of the real part of `observable` by the
+	/// median of means over `batches` batches.
+	///
+	/// # Panics
+	/// Panics if `batches` is less than 2 or more than the number of
+	/// snapshots.
+	pub fn expectation_value(&self, observable: &Observable, batches: usize) -> ShadowEstimate {
+		let values: Vec<f64> = self
+			.snapshots
+			.iter()
+			.map(|s| {
+				observable
+					.terms()
+					.iter()
+					.map(|(c, p)| c.re * self.pauli_snapshot(s, p))
+					.sum()
+			})
+			.collect();
+		median_of_means(batches, values.len(), |range| {
+			values[range].iter().sum::<f64>()
+		})
+	}
+
+	/// Estimate the fidelity <ψ|ρ|ψ> with the pure state `target` by the
+	/// median of means over `batches` batches.
+	///
+	/// # Panics
+	/// Panics if `target` is not a state of `qubit_count()` qubits, or
+	/// `batches` is less than 2 or more than the number of snapshots.
+	pub fn fidelity(&self, target: &[Complex<f64>], batches: usize) -> ShadowEstimate {
+		assert_eq!(
+			target.len(),
+			1 << self.qubit_count,
+			"the target state must have {} qubits",
+			self.qubit_count
+		);
+		let values: Vec<f64> = self
+			.snapshots
+			.iter()
+			.map(|s| self.fidelity_snapshot(s, target))
+			.collect();
+		median_of_means(batches, values.len(), |range| {
+			values[range].iter().sum::<f64>()
+		})
+	}
+
+	/// Estimate the purity tr(ρ_A^2) of the reduced state on `subsystem` by
+	/// the median over `batches` batches of the mean of tr(ρ̂_i ρ̂_j) over
+	/// pairs of distinct snapshots i and j in the batch.
+	///
+	/// # Panics
+	/// Panics if `subsystem` has a qubit out of range, `batches` is less
+	/// than 2 or more than half the number of snapshots, or a batch has
+	/// snapshots of both ensembles.
+	pub fn purity(&self, subsystem: &[u32], batches: usize) -> ShadowEstimate {
+		for &q in subsystem {
+			assert!(
+				(q as usize) < self.qubit_count,
+				"qubit index {} is out of range for a shadow of {} qubits",
+				q,
+				self.qubit_count
+			);
+		}
+		assert!(
+			2 * batches <= self.snapshots.len(),
+			"every batch needs at least 2 snapshots"
+		);
+		// aligned with the snapshots; Pauli snapshots need no matrix
+		let reduced: Vec<Option<Vec<Complex<f64>>>> = self
+			.snapshots
+			.iter()
+			.map(|s| match s {
+				Snapshot::Clifford { circuit, outcome } => {
+					Some(self.reduced_snapshot(circuit, *outcome, subsystem))
+				}
+				Snapshot::Pauli { .. } => None,
+			})
+			.collect();
+		median_of_means(batches, self.snapshots.len(), |range| {
+			let count = range.len() as f64;
+			let mut sum = 0.;
+			for i in range.clone() {
+				for j in range.start..i {
+					sum += 2. * self.purity_kernel(i, j, subsystem, &reduced);
+				}
+			}
+			// normalized so that dividing by the batch size gives the mean over
+			// pairs
+			sum / (count - 1.)
+		})
+	}
+
+	/// Get tr(ρ̂ P) for the snapshot ρ̂.
+	fn pauli_snapshot(&self, snapshot: &Snapshot, pauli: &PauliString) -> f64 {
+		match snapshot {
+			Snapshot::Pauli { bases, outcome } => pauli
+				.targets()
+				.iter()
+				.zip(pauli.paulis())
+				.filter(|&(_, &p)| p != Pauli::I)
+				.map(|(&q, &p)| {
+					if bases[q as usize] != p {
+						0.
+					} else if (outcome >> q) & 1 == 0 {
+						3.
+					} else {
+						-3.
+					}
+				})
+				.product(),
+			Snapshot::Clifford { circuit, outcome } => {
+				let normalized = pauli.normalized();
+				if normalized.targets().is_empty() {
+					return 1.;
+				}
+				// ρ̂ = (2^n + 1) U†|b><b|U - I, and tr(P) = 0
+				let mut tableau = Tableau::new(self.qubit_count, &normalized);
+				for gate in circuit.gates() {
+					tableau.apply(gate);
+				}
+				match tableau.z_type() {
+					Some((sign, mask)) => {
+						let parity = (outcome & mask).count_ones() % 2;
+						let value = if parity == 0 { sign } else { -sign };
+						((1u64 << self.qubit_count) + 1) as f64 * value
+					}
+					None => 0.,
+				}
+			}
+		}
+	}
+
+	/// Get <ψ|ρ̂|ψ> for the snapshot ρ̂.
+	fn fidelity_snapshot(&self, snapshot: &Snapshot, target: &[Complex<f64>]) -> f64 {
+		match snapshot {
+			Snapshot::Pauli { bases, outcome } => {
+				// ρ̂ = ⊗_q (I + 3 s_q B_q)/2 with the outcome s_q = ±1 of the basis B_q
+				let mut applied = target.to_vec();
+				for (q, basis) in bases.iter().enumerate() {
+					let s = if (outcome >> q) & 1 == 0 { 3. } else { -3. };
+					let b = basis.matrix();
+					let one = Complex::new(1., 0.);
+					let m = [
+						(one + b[0] * s) / 2.,
+						b[1] * s / 2.,
+						b[2] * s / 2.,
+						(one + b[3] * s) / 2.,
+					];
+					apply_single_qubit_matrix(&mut applied, q, &m);
+				}
+				target
+					.iter()
+					.zip(&applied)
+					.map(|(t, a)| t.conj() * a)
+					.sum::<Complex<f64>>()
+					.re
+			}
+			Snapshot::Clifford { circuit, outcome } => {
+				let mut rotated = StateVec::new(self.qubit_count);
+				rotated.as_mut().copy_from_slice(target);
+				circuit.update_quantum_state(rotated.as_mut()).unwrap();
+				let probability = rotated.as_ref()[*outcome as usize].norm_sqr();
+				((1u64 << self.qubit_count) + 1) as f64 * probability - 1.
+			}
+		}
+	}
+
+	/// Get the reduced snapshot ρ̂_A = (2^n + 1) tr_B(U†|b><b|U) - 2^|B| I of
+	/// a Clifford snapshot as a row-major matrix.
+	fn reduced_snapshot(
+		&self,
+		circuit: &Circuit,
+		outcome: u64,
+		subsystem: &[u32],
+	) -> Vec<Complex<f64>> {
+		let n = self.qubit_count;
+		let mut state = StateVec::new(n);
+		state.set_computational_basis(outcome as usize);
+		circuit
+			.inverse()
+			.update_quantum_state(state.as_mut())
+			.unwrap();
+		let dim_a = 1 << subsystem.len();
+		let rest: Vec<u32> = (0..n as u32).filter(|q| !subsystem.contains(q)).collect();
+		let index = |a: usize, b: usize| -> usize {
+			let mut i = 0;
+			for (k, &q) in subsystem.iter().enumerate() {
+				i |= ((a >> k) & 1) << q;
+			}
+			for (k, &q) in rest.iter().enumerate() {
+				i |= ((b >> k) & 1) << q;
+			}
+			i
+		};
+		let psi = state.as_ref();
+		let mut rho = vec![Complex::new(0., 0.); dim_a * dim_a];
+		for a in 0..dim_a {
+			for a2 in 0..dim_a {
+				let mut sum = Complex::new(0., 0.);
+				for b in 0..1 << rest.len() {
+					sum += psi[index(a, b)] * psi[index(a2, b)].conj();
+				}
+				rho[a * dim_a + a2] = sum * ((1u64 << n) + 1) as f64;
+			}
+			rho[a * dim_a + a] -= (1u64 << rest.len()) as f64;
+		}
+		rho
+	}
+
+	/// Get tr(ρ̂_A,i ρ̂_A,j) for snapshots i and j.
+	fn purity_kernel(
+		&self,
+		i: usize,
+		j: usize,
+		subsystem: &[u32],
+		reduced: &[Option<Vec<Complex<f64>>>],
+	) -> f64 {
+		match (&self.snapshots[i], &self.snapshots[j]) {
+			(
+				Snapshot::Pauli {
+					bases: bases_i,
+					outcome: outcome_i,
+				},
+				Snapshot::Pauli {
+					bases: bases_j,
+					outcome: outcome_j,
+				},
+			) => subsystem
+				.iter()
+				.map(|&q| {
+					let q = q as usize;
+					if bases_i[q] != bases_j[q] {
+						0.5
+					} else if (outcome_i >> q) & 1 == (outcome_j >> q) & 1 {
+						5.
+					} else {
+						-4.
+					}
+				})
+				.product(),
+			(Snapshot::Clifford { .. }, Snapshot::Clifford { .. }) => {
+				let (a, b) = (reduced[i].as_ref().unwrap(), reduced[j].as_ref().unwrap());
+				let dim = 1 << subsystem.len();
+				let mut trace = Complex::new(0., 0.);
+				for r in 0..dim {
+					for c in 0..dim {
+						trace += a[r * dim + c] * b[c * dim + r];
+					}
+				}
+				trace.re
+			}
+			_ => panic!("snapshots of different ensembles cannot be combined"),
+		}
+	}
+}
+
+/// Split `count` items into `batches` consecutive batches whose sizes differ
+/// by at most 1, get the mean of each as `sum(range) / range.len()` and
+/// return their median.
+fn median_of_means<F>(batches: usize, count: usize, sum: F) -> ShadowEstimate
+where
+	F: Fn(std::ops::Range<usize>) -> f64,
+{
+	assert!(
+		batches >= 2 && batches <= count,
+		"{} batches cannot be formed from {} snapshots",
+		batches,
+		count
+	);
+	let mut means: Vec<f64> = (0..batches)
+		.map(|k| {
+			let range = k * count / batches..(k + 1) * count / batches;
+			let len = range.len() as f64;
+			sum(range) / len
+		})
+		.collect();
+	let mean = means.iter().sum::<f64>() / batches as f64;
+	let variance = means.iter().map(|m| (m - mean).powi(2)).sum::<f64>() / (batches - 1) as f64;
+	means.sort_by(f64::total_cmp);
+	let value = if batches % 2 == 1 {
+		means[batches / 2]
+	} else {
+		(means[batches / 2 - 1] + means[batches / 2]) / 2.
+	};
+	ShadowEstimate {
+		value,
+		error: (34. * variance).sqrt(),
+		failure_probability: (2. * (-(batches as f64) / 2.).exp()).min(1.),
+	}
+}
+
+/// Get the basis state whose interval of cumulative probability contains `r`
+/// in [0, 1).
+fn sample_outcome(state: &[Complex<f64>], r: f64) -> u64 {
+	let mut cumulative = 0.;
+	for (i, a) in state.iter().enumerate() {
+		cumulative += a.norm_sqr();
+		if r < cumulative {
+			return i as u64;
+		}
+	}
+	// rounding can leave the total probability slightly below 1
+	state.iter().rposition(|a| a.norm_sqr() > 0.).unwrap_or(0) as u64
+}
+
+/// Multiply the 2×2 row-major matrix `m` on qubit `q` of `state`.
+fn apply_single_qubit_matrix(state: &mut [Complex<f64>], q: usize, m: &[Complex<f64>; 4]) {
+	let bit = 1 << q;
+	for i in 0..state.len() {
+		if i & bit == 0 {
+			let (a, b) = (state[i], state[i | bit]);
+			state[i] = m[0] * a + m[1] * b;
+			state[i | bit] = m[2] * a + m[3] * b;
+		}
+	}
+}
+
+/// Append a random Clifford circuit of `clifford_depth` layers.
+fn random_clifford<R: Rng>(circuit: &mut Circuit, rng: &mut R) {
+	let n = circuit.qubit_count();
+	let mut qubits: Vec<u32> = (0..n as u32).collect();
+	for _ in 0..clifford_depth(n) {
+		for q in 0..n as u32 {
+			// 6 permutations of the axes times 4 Pauli operators
+			match rng.gen_range(0..6) {
+				0 => {}
+				1 => circuit.add_gate(Gate::H(q)),
+				2 => circuit.add_gate(Gate::S(q)),
+				3 => {
+					circuit.add_gate(Gate::S(q));
+					circuit.add_gate(Gate::H(q));
+				}
+				4 => {
+					circuit.add_gate(Gate::H(q));
+					circuit.add_gate(Gate::S(q));
+				}
+				_ => {
+					circuit.add_gate(Gate::H(q));
+					circuit.add_gate(Gate::S(q));
+					circuit.add_gate(Gate::H(q));
+				}
+			}
+			match rng.gen_range(0..4) {
+				0 => {}
+				1 => circuit.add_gate(Gate::X(q)),
+				2 => circuit.add_gate(Gate::Y(q)),
+				_ => circuit.add_gate(Gate::Z(q)),
+			}
+		}
+		qubits.shuffle(rng);
+		for pair in qubits.chunks_exact(2) {
+			circuit.add_gate(Gate::Cnot(pair[0], pair[1]));
+		}
+	}
+}
@@ -245,7 +245,7 @@ macro_rules! impl_array_state {
 
 impl_array_state!(1, 2, 4, 8, 16);
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct StateVec<F = f64>(usize, Vec<Complex<F>>);
 
 impl<F: num::Num + Clone> StateVec<F> {
@@ -299,9 +299,16 @@ pub trait GeneralStateMut {}
 
 #[non_exhaustive]
 pub enum StateErr {
+	/// Lengths of two states which must agree: the expected length before
+	/// the length of the given state, or the left state before the right
+	/// state of an operation on two states such as `inner_product`.
 	InconsistentStateLength(usize, usize),
 	InvalidTargetQubitIndex(usize),
 	InvalidTargetList(Vec<u32>),
+	/// Lengths of a target list and the list of values for the targets
+	InconsistentListLength(usize, usize),
+	/// Pauli types must be 0 (I), 1 (X), 2 (Y) or 3 (Z).
+	InvalidPauliType(u32),
 }
 
 impl fmt::Display for StateErr {
@@ -316,6 +323,15 @@ impl fmt::Display for StateErr {
 			StateErr::InvalidTargetList(list) => {
 				write!(f, "Invalid target list: {:?}", list)
 			}
+			StateErr::InconsistentListLength(targets, values) => {
+				write!(f, "Inconsistent list length: target list has length of {targets}, but value list has {values}")
+			}
+			StateErr::InvalidPauliType(t) => {
+				write!(
+					f,
+					"Invalid Pauli type: Pauli type must be in 0..=3, but {t} was supplied"
+				)
+			}
 		}
 	}
 }
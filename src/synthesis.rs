@@ -0,0 +1,630 @@
+use crate::circuit::{Circuit, Gate};
+use crate::optimizer::{FuseSingleQubitGates, Pass};
+use crate::Complex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2, PI};
+
+const EPS: f64 = 1e-12;
+/// Angles closer than this to a multiple of 2π are dropped, since the
+/// rotation is then the identity up to global phase.
+const ANGLE_EPS: f64 = 1e-10;
+
+/// Angles of the ZYZ Euler decomposition
+/// U = e^{iα} Rz(φ) Ry(θ) Rz(λ) of a single-qubit unitary, where
+/// Rz(a) = exp(-i(a/2)Z) and Ry(a) = exp(-i(a/2)Y) as in `qurs::gate`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EulerAngles {
+	/// α
+	pub global_phase: f64,
+	/// θ
+	pub theta: f64,
+	/// φ
+	pub phi: f64,
+	/// λ
+	pub lambda: f64,
+}
+
+/// Decompose a single-qubit unitary given in row-major order into ZYZ Euler
+/// angles. The circuit is `Rz(λ)`, `Ry(θ)` then `Rz(φ)` in application order.
+pub fn euler_zyz(matrix: &[Complex<f64>; 4]) -> EulerAngles {
+	let det = matrix[0] * matrix[3] - matrix[1] * matrix[2];
+	let global_phase = det.arg() / 2.;
+	let phase = Complex::from_polar(1., -global_phase);
+	// V = U / sqrt(det U) is in SU(2)
+	let v: Vec<Complex<f64>> = matrix.iter().map(|m| m * phase).collect();
+	let theta = 2. * v[2].norm().atan2(v[0].norm());
+	// arg(V11) = (φ + λ)/2 and arg(V10) = (φ - λ)/2
+	let sum = if v[3].norm() > EPS { v[3].arg() } else { 0. };
+	let difference = if v[2].norm() > EPS { v[2].arg() } else { 0. };
+	EulerAngles {
+		global_phase,
+		theta,
+		phi: sum + difference,
+		lambda: sum - difference,
+	}
+}
+
+/// Get the matrix e^{iα} Rz(φ) Ry(θ) Rz(λ) in row-major order.
+pub fn euler_zyz_matrix(angles: &EulerAngles) -> [Complex<f64>; 4] {
+	let EulerAngles {
+		global_phase,
+		theta,
+		phi,
+		lambda,
+	} = *angles;
+	let (c, s) = ((theta / 2.).cos(), (theta / 2.).sin());
+	let e = |a: f64| Complex::from_polar(1., a + global_phase);
+	[
+		e(-(phi + lambda) / 2.) * c,
+		-e(-(phi - lambda) / 2.) * s,
+		e((phi - lambda) / 2.) * s,
+		e((phi + lambda) / 2.) * c,
+	]
+}
+
+/// Product `a * b` of row-major 2×2 matrices.
+pub(crate) fn matmul2(a: &[Complex<f64>; 4], b: &[Complex<f64>; 4]) -> [Complex<f64>; 4] {
+	[
+		a[0] * b[0] + a[1] * b[2],
+		a[0] * b[1] + a[1] * b[3],
+		a[2] * b[0] + a[3] * b[2],
+		a[2] * b[1] + a[3] * b[3],
+	]
+}
+
+/// Conjugate transpose of a row-major 2×2 matrix.
+pub(crate) fn dagger2(a: &[Complex<f64>; 4]) -> [Complex<f64>; 4] {
+	[a[0].conj(), a[2].conj(), a[1].conj(), a[3].conj()]
+}
+
+/// Principal square root of a row-major 2×2 unitary matrix.
+pub(crate) fn sqrt2(u: &[Complex<f64>; 4]) -> [Complex<f64>; 4] {
+	pow2(u, 0.5)
+}
+
+/// Power `u^exponent` of a row-major 2×2 unitary matrix, taking the
+/// principal branch of each eigenvalue. Integer exponents are computed by
+/// repeated squaring.
+pub(crate) fn pow2(u: &[Complex<f64>; 4], exponent: f64) -> [Complex<f64>; 4] {
+	let zero = Complex::new(0., 0.);
+	if exponent.fract() == 0. {
+		let mut result = [Complex::new(1., 0.), zero, zero, Complex::new(1., 0.)];
+		let mut square = if exponent < 0. { dagger2(u) } else { *u };
+		let mut remaining = exponent.abs() as u64;
+		while remaining > 0 {
+			if remaining & 1 == 1 {
+				result = matmul2(&result, &square);
+			}
+			square = matmul2(&square, &square);
+			remaining >>= 1;
+		}
+		return result;
+	}
+	if u[1].norm() < EPS && u[2].norm() < EPS {
+		// U is diagonal
+		return [u[0].powf(exponent), zero, zero, u[3].powf(exponent)];
+	}
+	let trace = u[0] + u[3];
+	let det = u[0] * u[3] - u[1] * u[2];
+	let discriminant = (trace * trace / 4. - det).sqrt();
+	let eigenvalue = trace / 2. + discriminant;
+	// eigenvector for `eigenvalue`, from whichever row of U - λI is larger;
+	// the other eigenvector is orthogonal to it
+	let (x0, y0) = (u[1], eigenvalue - u[0]);
+	let (x1, y1) = (eigenvalue - u[3], u[2]);
+	let (x, y) = if x0.norm_sqr() + y0.norm_sqr() > x1.norm_sqr() + y1.norm_sqr() {
+		(x0, y0)
+	} else {
+		(x1, y1)
+	};
+	let norm = (x.norm_sqr() + y.norm_sqr()).sqrt();
+	let (x, y) = (x / norm, y / norm);
+	let (r0, r1) = (
+		eigenvalue.powf(exponent),
+		(trace - eigenvalue).powf(exponent),
+	);
+	// V = r0 |v><v| + r1 |w><w| with w = (-y*, x*)
+	[
+		r0 * x * x.conj() + r1 * y * y.conj(),
+		(r0 - r1) * x * y.conj(),
+		(r0 - r1) * y * x.conj(),
+		r0 * y * y.conj() + r1 * x * x.conj(),
+	]
+}
+
+/// Normalize `angle` into [-π, π), or `None` if it is a multiple of 2π.
+pub(crate) fn nonzero_angle(angle: f64) -> Option<f64> {
+	let r = (angle + PI).rem_euclid(2. * PI) - PI;
+	if r.abs() < ANGLE_EPS {
+		None
+	} else {
+		Some(r)
+	}
+}
+
+/// Emit a single-qubit unitary as `Rz(λ)`, `Ry(θ)` and `Rz(φ)` up to global
+/// phase, leaving out rotations which are the identity.
+pub(crate) fn zyz_gates(qubit: u32, matrix: &[Complex<f64>; 4]) -> Vec<Gate> {
+	let EulerAngles {
+		theta, phi, lambda, ..
+	} = euler_zyz(matrix);
+	let mut gates = Vec::new();
+	let lambda = if nonzero_angle(theta).is_none() {
+		phi + lambda
+	} else {
+		lambda
+	};
+	if let Some(lambda) = nonzero_angle(lambda) {
+		gates.push(Gate::Rz(qubit, lambda));
+	}
+	if nonzero_angle(theta).is_some() {
+		gates.push(Gate::Ry(qubit, theta));
+		if let Some(phi) = nonzero_angle(phi) {
+			gates.push(Gate::Rz(qubit, phi));
+		}
+	}
+	gates
+}
+
+/// KAK (Cartan) decomposition of a two-qubit unitary,
+/// U = e^{iα} (L1 ⊗ L0) exp(i(a XX + b YY + c ZZ)) (R1 ⊗ R0),
+/// where qubit 0 is the less significant bit of the matrix index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KakDecomposition {
+	/// α
+	pub global_phase: f64,
+	/// R0 and R1, applied first
+	pub before: [[Complex<f64>; 4]; 2],
+	/// a, b and c
+	pub interaction: [f64; 3],
+	/// L0 and L1, applied last
+	pub after: [[Complex<f64>; 4]; 2],
+}
+
+/// Compute the KAK decomposition of a 4×4 unitary given in row-major order.
+///
+/// In the magic basis, local unitaries become real orthogonal matrices and
+/// the interaction becomes diagonal, so the decomposition follows from
+/// diagonalizing the symmetric unitary U_B^T U_B with a real orthogonal
+/// matrix (Vatan and Williams, Phys. Rev. A 69, 032315).
+///
+/// # Panics
+/// Panics if `matrix` does not have 16 elements or is not unitary.
+pub fn kak_decomposition(matrix: &[Complex<f64>]) -> KakDecomposition {
+	assert_eq!(matrix.len(), 16, "a two-qubit unitary has 16 elements");
+	let det_phase = determinant4(matrix).arg() / 4.;
+	let phase = Complex::from_polar(1., -det_phase);
+	let u: Vec<Complex<f64>> = matrix.iter().map(|m| m * phase).collect();
+
+	let magic = magic_basis();
+	let magic_dagger = dagger(&magic, 4);
+	let u_magic = matmul(&magic_dagger, &matmul(&u, &magic, 4), 4);
+	let symmetric = matmul(&transpose(&u_magic, 4), &u_magic, 4);
+	let p = real_eigenbasis(&symmetric);
+	let p_complex: Vec<Complex<f64>> = p.iter().map(|&x| Complex::new(x, 0.)).collect();
+	let diagonal = matmul(
+		&transpose(&p_complex, 4),
+		&matmul(&symmetric, &p_complex, 4),
+		4,
+	);
+
+	// A^2 = P^T U_B^T U_B P, with det A = 1 so that K1 is in SO(4)
+	let mut theta: Vec<f64> = (0..4).map(|k| diagonal[k * 5].arg() / 2.).collect();
+	if ((theta.iter().sum::<f64>() / PI).round() as i64).rem_euclid(2) == 1 {
+		theta[0] += PI;
+	}
+	let a_inverse: Vec<Complex<f64>> = (0..16)
+		.map(|k| {
+			if k % 5 == 0 {
+				Complex::from_polar(1., -theta[k / 5])
+			} else {
+				Complex::new(0., 0.)
+			}
+		})
+		.collect();
+	let k1 = matmul(&u_magic, &matmul(&p_complex, &a_inverse, 4), 4);
+	let k2 = transpose(&p_complex, 4);
+	let after = matmul(&magic, &matmul(&k1, &magic_dagger, 4), 4);
+	let before = matmul(&magic, &matmul(&k2, &magic_dagger, 4), 4);
+
+	// eigenvalues of (XX, YY, ZZ) on the magic basis states are
+	// (1, -1, 1), (1, 1, -1), (-1, -1, -1) and (-1, 1, 1)
+	let interaction = [
+		(theta[0] + theta[1] - theta[2] - theta[3]) / 4.,
+		(-theta[0] + theta[1] - theta[2] + theta[3]) / 4.,
+		(theta[0] - theta[1] - theta[2] + theta[3]) / 4.,
+	];
+	KakDecomposition {
+		global_phase: det_phase + theta.iter().sum::<f64>() / 4.,
+		before: tensor_factors(&before),
+		interaction,
+		after: tensor_factors(&after),
+	}
+}
+
+/// Decompose a two-qubit unitary into at most three `Gate::Cnot` and
+/// `Gate::Rz`, `Gate::Ry` rotations, up to global phase. `targets[0]` is the
+/// less significant bit of the matrix index as in `Gate::DenseMatrix`.
+///
+/// ```
+/// # use qurs::circuit::{equivalent, Circuit, Gate};
+/// # use qurs::synthesis::decompose_two_qubit_unitary;
+/// let mut circuit = Circuit::new(2);
+/// circuit.add_gate(Gate::Swap(0, 1));
+/// let mut decomposed = Circuit::new(2);
+/// for gate in decompose_two_qubit_unitary(&[0, 1], &circuit.to_unitary()) {
+///     decomposed.add_gate(gate);
+/// }
+/// assert!(equivalent(&circuit, &decomposed, true));
+/// ```
+///
+/// # Panics
+/// Panics if `matrix` does not have 16 elements or is not unitary.
+pub fn decompose_two_qubit_unitary(targets: &[u32; 2], matrix: &[Complex<f64>]) -> Vec<Gate> {
+	let KakDecomposition {
+		before,
+		interaction: [a, b, c],
+		after,
+		..
+	} = kak_decomposition(matrix);
+	let [q0, q1] = *targets;
+	let dense = |qubit: u32, matrix: &[Complex<f64>]| Gate::DenseMatrix {
+		targets: vec![qubit],
+		matrix: matrix.to_vec(),
+	};
+	let local = |gate: Gate| {
+		let qubit = gate.qubits()[0];
+		dense(qubit, &gate.matrix())
+	};
+
+	let mut gates = vec![dense(q0, &before[0]), dense(q1, &before[1])];
+	let quarter_turns: Vec<i64> = [a, b, c]
+		.iter()
+		.map(|x| (x / FRAC_PI_2).round() as i64)
+		.collect();
+	let is_local = [a, b, c]
+		.iter()
+		.zip(&quarter_turns)
+		.all(|(x, &k)| (x - k as f64 * FRAC_PI_2).abs() < ANGLE_EPS);
+	if is_local {
+		// exp(i(π/2)PP) = iPP
+		let paulis = [Gate::X, Gate::Y, Gate::Z];
+		for (pauli, k) in paulis.iter().zip(quarter_turns) {
+			if k.rem_euclid(2) == 1 {
+				gates.push(local(pauli(q0)));
+				gates.push(local(pauli(q1)));
+			}
+		}
+	} else {
+		// CNOT exp(i(aXX + bYY + cZZ)) CNOT = exp(iaX0) exp(icZ1) exp(-ibX0Z1)
+		// where exp(-ibX0Z1) = CZ Rx0(2b) CZ, and CNOT CZ is a CNOT up to
+		// phase gates.
+		gates.extend(vec![
+			Gate::Cnot(q0, q1),
+			local(Gate::Rx(q0, -2. * a)),
+			local(Gate::Rz(q1, -2. * c)),
+			local(Gate::H(q1)),
+			Gate::Cnot(q0, q1),
+			local(Gate::H(q1)),
+			local(Gate::Rx(q0, 2. * b)),
+			local(Gate::Sdag(q1)),
+			Gate::Cnot(q0, q1),
+			local(Gate::S(q1)),
+			local(Gate::Sdag(q0)),
+		]);
+	}
+	gates.push(dense(q0, &after[0]));
+	gates.push(dense(q1, &after[1]));
+
+	let mut lowered = Circuit::new(q0.max(q1) as usize + 1);
+	for gate in gates {
+		lowered.add_gate(gate);
+	}
+	let mut decomposed = Vec::new();
+	for gate in FuseSingleQubitGates.run(&lowered).gates() {
+		match gate {
+			Gate::DenseMatrix { targets, matrix } => {
+				let matrix = [matrix[0], matrix[1], matrix[2], matrix[3]];
+				decomposed.extend(zyz_gates(targets[0], &matrix));
+			}
+			gate => decomposed.push(gate.clone()),
+		}
+	}
+	decomposed
+}
+
+/// Synthesize a circuit preparing `state` from |0...0> up to global phase,
+/// with uniformly controlled `Gate::Ry` and `Gate::Rz` rotations (Möttönen et
+/// al., Quantum Inf. Comput. 5, 467).
+///
+/// The magnitudes are loaded from the most significant qubit down, each
+/// qubit rotated by an angle depending on the qubits above it. The phases
+/// are then fixed from the least significant qubit up. Each uniformly
+/// controlled rotation with k controls takes 2^k rotations and 2^k CNOTs.
+/// Only the direction of `state` matters, so it need not be normalized.
+///
+/// ```
+/// # use qurs::synthesis::prepare_state;
+/// # use qurs::{inner_product, Complex, StateVec};
+/// let h = std::f64::consts::FRAC_1_SQRT_2;
+/// let target = [
+///     Complex::new(h, 0.),
+///     Complex::new(0., 0.),
+///     Complex::new(0., 0.),
+///     Complex::new(0., h),
+/// ];
+/// let mut state = StateVec::new(2);
+/// prepare_state(&target).update_quantum_state(state.as_mut()).unwrap();
+/// let fidelity = inner_product(&target, state.as_ref()).unwrap().norm_sqr();
+/// assert!((fidelity - 1.).abs() < 1e-10);
+/// ```
+///
+/// # Panics
+/// Panics if the length of `state` is not a power of two.
+pub fn prepare_state(state: &[Complex<f64>]) -> Circuit {
+	assert!(
+		state.len().is_power_of_two(),
+		"state length {} is not a power of two",
+		state.len()
+	);
+	let qubit_count = state.len().trailing_zeros();
+	let mut circuit = Circuit::new(qubit_count as usize);
+
+	let mut squared_norms: Vec<f64> = state.iter().map(|a| a.norm_sqr()).collect();
+	let mut magnitude_angles = Vec::new();
+	let mut phases: Vec<f64> = state.iter().map(|a| a.arg()).collect();
+	let mut phase_angles = Vec::new();
+	for _ in 0..qubit_count {
+		// entry x is for the value x of the qubits above the target
+		magnitude_angles.push(
+			squared_norms
+				.chunks(2)
+				.map(|pair| 2. * pair[1].sqrt().atan2(pair[0].sqrt()))
+				.collect::<Vec<f64>>(),
+		);
+		squared_norms = squared_norms
+			.chunks(2)
+			.map(|pair| pair[0] + pair[1])
+			.collect();
+		phase_angles.push(
+			phases
+				.chunks(2)
+				.map(|pair| pair[1] - pair[0])
+				.collect::<Vec<f64>>(),
+		);
+		phases = phases
+			.chunks(2)
+			.map(|pair| (pair[0] + pair[1]) / 2.)
+			.collect();
+	}
+
+	for target in (0..qubit_count).rev() {
+		let angles = &magnitude_angles[target as usize];
+		uniformly_controlled_rotation(&mut circuit, target, angles, Gate::Ry);
+	}
+	for target in 0..qubit_count {
+		let angles = &phase_angles[target as usize];
+		uniformly_controlled_rotation(&mut circuit, target, angles, Gate::Rz);
+	}
+	circuit
+}
+
+/// Append a rotation of `target` by `angles[x]` when the qubits above it
+/// hold the value x.
+///
+/// The rotations are interleaved with CNOTs whose controls follow the Gray
+/// code, which solves for the angles θ with
+/// `angles[x] = Σ_i (-1)^{popcount(x & gray(i))} θ_i`.
+fn uniformly_controlled_rotation<F>(circuit: &mut Circuit, target: u32, angles: &[f64], rotation: F)
+where
+	F: Fn(u32, f64) -> Gate,
+{
+	let gray = |i: usize| i ^ (i >> 1);
+	let count = angles.len();
+	let thetas: Vec<f64> = (0..count)
+		.map(|i| {
+			angles
+				.iter()
+				.enumerate()
+				.map(|(x, angle)| {
+					if (x & gray(i)).count_ones() % 2 == 0 {
+						*angle
+					} else {
+						-angle
+					}
+				})
+				.sum::<f64>()
+				/ count as f64
+		})
+		.collect();
+	if thetas[1..].iter().all(|theta| theta.abs() < ANGLE_EPS) {
+		// the rotation does not depend on the controls
+		if thetas[0].abs() >= ANGLE_EPS {
+			circuit.add_gate(rotation(target, thetas[0]));
+		}
+		return;
+	}
+	for (i, theta) in thetas.iter().enumerate() {
+		if theta.abs() >= ANGLE_EPS {
+			circuit.add_gate(rotation(target, *theta));
+		}
+		let changed = gray(i) ^ gray((i + 1) % count);
+		circuit.add_gate(Gate::Cnot(target + 1 + changed.trailing_zeros(), target));
+	}
+}
+
+/// Columns are the magic basis states (|00> + |11>)/√2, i(|01> + |10>)/√2,
+/// (|01> - |10>)/√2 and i(|00> - |11>)/√2.
+fn magic_basis() -> Vec<Complex<f64>> {
+	let h = FRAC_1_SQRT_2;
+	let (r, i, z) = (
+		Complex::new(h, 0.),
+		Complex::new(0., h),
+		Complex::new(0., 0.),
+	);
+	vec![r, z, z, i, z, i, r, z, z, i, -r, z, r, z, z, -i]
+}
+
+/// Product of row-major `dim`×`dim` matrices.
+pub(crate) fn matmul(a: &[Complex<f64>], b: &[Complex<f64>], dim: usize) -> Vec<Complex<f64>> {
+	let mut product = vec![Complex::new(0., 0.); dim * dim];
+	for i in 0..dim {
+		for k in 0..dim {
+			let a_ik = a[i * dim + k];
+			for j in 0..dim {
+				product[i * dim + j] += a_ik * b[k * dim + j];
+			}
+		}
+	}
+	product
+}
+
+fn transpose(a: &[Complex<f64>], dim: usize) -> Vec<Complex<f64>> {
+	(0..dim * dim)
+		.map(|k| a[(k % dim) * dim + k / dim])
+		.collect()
+}
+
+fn dagger(a: &[Complex<f64>], dim: usize) -> Vec<Complex<f64>> {
+	transpose(a, dim).iter().map(|x| x.conj()).collect()
+}
+
+/// Determinant of a row-major 4×4 matrix by Laplace expansion.
+fn determinant4(a: &[Complex<f64>]) -> Complex<f64> {
+	let minor = |r: [usize; 3], c: [usize; 3]| {
+		let m = |i: usize, j: usize| a[r[i] * 4 + c[j]];
+		m(0, 0) * (m(1, 1) * m(2, 2) - m(1, 2) * m(2, 1))
+			- m(0, 1) * (m(1, 0) * m(2, 2) - m(1, 2) * m(2, 0))
+			+ m(0, 2) * (m(1, 0) * m(2, 1) - m(1, 1) * m(2, 0))
+	};
+	let columns = [[1, 2, 3], [0, 2, 3], [0, 1, 3], [0, 1, 2]];
+	(0..4)
+		.map(|j| {
+			let sign = if j % 2 == 0 { 1. } else { -1. };
+			a[j] * minor([1, 2, 3], columns[j]) * sign
+		})
+		.sum()
+}
+
+/// Real orthogonal P with determinant 1 such that P^T M P is diagonal, for a
+/// symmetric unitary M.
+///
+/// The real and imaginary parts of M are commuting real symmetric matrices,
+/// so an eigenbasis of a generic linear combination of them diagonalizes M.
+/// A few fixed combinations are tried first, then pseudorandom ones from a
+/// fixed seed so that the result is reproducible, keeping the best basis if
+/// none is exact to 1e-10.
+///
+/// # Panics
+/// Panics if no combination diagonalizes M to 1e-6, which happens only if M
+/// is not a symmetric unitary.
+fn real_eigenbasis(m: &[Complex<f64>]) -> [f64; 16] {
+	const RANDOM_ATTEMPTS: usize = 100;
+	let fixed = [(1., 0.5773), (0.2871, 1.), (1., -1.3507), (-0.6133, 0.2718)];
+	let mut rng = StdRng::seed_from_u64(0x5eed);
+	let random = (0..RANDOM_ATTEMPTS).map(|_| (rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)));
+	let mut best = (f64::INFINITY, [0.; 16]);
+	for (x, y) in fixed.iter().cloned().chain(random) {
+		let mut a = [0.; 16];
+		for k in 0..16 {
+			a[k] = x * m[k].re + y * m[k].im;
+		}
+		let p = jacobi_eigenvectors(a);
+		let off_diagonal: f64 = (0..16)
+			.filter(|k| k % 5 != 0)
+			.map(|k| {
+				let (i, j) = (k / 4, k % 4);
+				(0..16)
+					.map(|l| p[(l / 4) * 4 + i] * m[l] * p[(l % 4) * 4 + j])
+					.sum::<Complex<f64>>()
+					.norm()
+			})
+			.fold(0., f64::max);
+		if off_diagonal < best.0 {
+			best = (off_diagonal, p);
+		}
+		if off_diagonal < 1e-10 {
+			break;
+		}
+	}
+	let (off_diagonal, mut p) = best;
+	assert!(
+		off_diagonal < 1e-6,
+		"no real eigenbasis found; the matrix is not a symmetric unitary"
+	);
+	let p_complex: Vec<Complex<f64>> = p.iter().map(|&x| Complex::new(x, 0.)).collect();
+	if determinant4(&p_complex).re < 0. {
+		for i in 0..4 {
+			p[i * 4] = -p[i * 4];
+		}
+	}
+	p
+}
+
+/// Eigenvectors of a real symmetric row-major 4×4 matrix as the columns of
+/// an orthogonal matrix, by cyclic Jacobi rotations.
+fn jacobi_eigenvectors(mut a: [f64; 16]) -> [f64; 16] {
+	const N: usize = 4;
+	let mut v = [0.; 16];
+	for i in 0..N {
+		v[i * N + i] = 1.;
+	}
+	for _ in 0..100 {
+		let off_diagonal: f64 = (0..16).filter(|k| k % 5 != 0).map(|k| a[k] * a[k]).sum();
+		if off_diagonal < 1e-30 {
+			break;
+		}
+		for p in 0..N {
+			for q in p + 1..N {
+				if a[p * N + q] == 0. {
+					continue;
+				}
+				let theta = (a[q * N + q] - a[p * N + p]) / (2. * a[p * N + q]);
+				let t = theta.signum() / (theta.abs() + (theta * theta + 1.).sqrt());
+				let c = 1. / (t * t + 1.).sqrt();
+				let s = t * c;
+				for k in 0..N {
+					let (kp, kq) = (a[k * N + p], a[k * N + q]);
+					a[k * N + p] = c * kp - s * kq;
+					a[k * N + q] = s * kp + c * kq;
+				}
+				for k in 0..N {
+					let (pk, qk) = (a[p * N + k], a[q * N + k]);
+					a[p * N + k] = c * pk - s * qk;
+					a[q * N + k] = s * pk + c * qk;
+				}
+				for k in 0..N {
+					let (kp, kq) = (v[k * N + p], v[k * N + q]);
+					v[k * N + p] = c * kp - s * kq;
+					v[k * N + q] = s * kp + c * kq;
+				}
+			}
+		}
+	}
+	v
+}
+
+/// Split a row-major 4×4 matrix L = L1 ⊗ L0 into [L0, L1], where qubit 0 is
+/// the less significant bit.
+fn tensor_factors(l: &[Complex<f64>]) -> [[Complex<f64>; 4]; 2] {
+	let k = (0..16)
+		.max_by(|&i, &j| l[i].norm().partial_cmp(&l[j].norm()).unwrap())
+		.unwrap();
+	let (i1, i0, j1, j0) = (k / 8, (k / 4) % 2, (k % 4) / 2, k % 2);
+	let mut l0 = [Complex::new(0., 0.); 4];
+	for p in 0..2 {
+		for q in 0..2 {
+			l0[p * 2 + q] = l[(i1 * 2 + p) * 4 + j1 * 2 + q];
+		}
+	}
+	let scale = (l0[0] * l0[3] - l0[1] * l0[2]).sqrt();
+	l0.iter_mut().for_each(|x| *x /= scale);
+	let mut l1 = [Complex::new(0., 0.); 4];
+	for r in 0..2 {
+		for s in 0..2 {
+			l1[r * 2 + s] = l[(r * 2 + i0) * 4 + s * 2 + j0] / l0[i0 * 2 + j0];
+		}
+	}
+	[l0, l1]
+}
@@ -0,0 +1,567 @@
+use crate::circuit::{Circuit, Gate};
+use crate::pauli::{pauli_list, pauli_matrix_element, Pauli};
+use crate::state::{StateErr, StateRef, StateVec};
+use crate::synthesis::matmul;
+use crate::Complex;
+use std::fmt;
+
+#[non_exhaustive]
+pub enum TomographyErr {
+	/// Number of measurement circuits and number of sample lists
+	InconsistentDataLength(usize, usize),
+	/// The sample list of the circuit is empty.
+	NoSamples(usize),
+	/// The outcome does not fit in the qubits of the tomography.
+	InvalidOutcome(u64),
+}
+
+impl fmt::Display for TomographyErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			TomographyErr::InconsistentDataLength(circuits, samples) => write!(
+				f,
+				"Inconsistent data length: {} circuits were measured, but {} sample lists were supplied",
+				circuits, samples
+			),
+			TomographyErr::NoSamples(i) => write!(f, "No samples: circuit {} has no samples", i),
+			TomographyErr::InvalidOutcome(outcome) => {
+				write!(f, "Invalid outcome: {} is out of range", outcome)
+			}
+		}
+	}
+}
+
+impl fmt::Debug for TomographyErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		<Self as fmt::Display>::fmt(self, f)
+	}
+}
+
+/// Full state tomography measuring every qubit in the X, Y or Z basis, over
+/// all 3^n combinations.
+///
+/// Density matrices are row-major 2^n × 2^n matrices.
+///
+/// ```
+/// # use qurs::tomography::StateTomography;
+/// # use qurs::StateVec;
+/// let state = StateVec::new(1);
+/// let tomography = StateTomography::new(1);
+/// let samples = tomography.sample(state.as_ref(), 100).unwrap();
+/// let rho = tomography.linear_inversion(&samples).unwrap();
+/// assert!((rho[0].re - 1.).abs() < 1e-10);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateTomography {
+	qubit_count: usize,
+	bases: Vec<Vec<Pauli>>,
+}
+
+impl StateTomography {
+	/// Build the tomography of `qubit_count` qubits. Bases are ordered with the
+	/// basis of qubit 0 changing fastest.
+	pub fn new(qubit_count: usize) -> Self {
+		let bases = (0..3usize.pow(qubit_count as u32))
+			.map(|mut index| {
+				(0..qubit_count)
+					.map(|_| {
+						let basis = [Pauli::X, Pauli::Y, Pauli::Z][index % 3];
+						index /= 3;
+						basis
+					})
+					.collect()
+			})
+			.collect();
+		Self { qubit_count, bases }
+	}
+
+	///Get qubit count
+	pub fn qubit_count(&self) -> usize {
+		self.qubit_count
+	}
+
+	///Get measurement bases, indexed by qubit
+	pub fn bases(&self) -> &[Vec<Pauli>] {
+		&self.bases
+	}
+
+	/// Get the basis change circuits to be applied to the state before
+	/// measuring in the computational basis, one for each of `bases()`.
+	pub fn circuits(&self) -> Vec<Circuit> {
+		self.bases
+			.iter()
+			.map(|basis| {
+				let mut circuit = Circuit::new(self.qubit_count);
+				add_basis_change(&mut circuit, basis);
+				circuit
+			})
+			.collect()
+	}
+
+	/// Run the measurement circuits on `state`, sampling `shots` times from
+	/// each.
+	pub fn sample(&self, state: &[Complex<f64>], shots: u32) -> Result<Vec<Vec<u64>>, StateErr> {
+		if state.len() != 1 << self.qubit_count {
+			return Err(StateErr::InconsistentStateLength(
+				1 << self.qubit_count,
+				state.len(),
+			));
+		}
+		self.circuits()
+			.iter()
+			.map(|circuit| {
+				let mut rotated = StateVec::new(self.qubit_count);
+				rotated.as_mut().copy_from_slice(state);
+				circuit.update_quantum_state(rotated.as_mut())?;
+				Ok(rotated.sampling(shots))
+			})
+			.collect()
+	}
+
+	/// Reconstruct the density matrix ρ = Σ_P <P> P / 2^n by linear
+	/// inversion, from the `samples` of each circuit of `circuits()`. <P> is
+	/// averaged over every basis that measures P.
+	///
+	/// The result has unit trace and is Hermitian, but may have negative
+	/// eigenvalues.
+	pub fn linear_inversion(
+		&self,
+		samples: &[Vec<u64>],
+	) -> Result<Vec<Complex<f64>>, TomographyErr> {
+		let frequencies = self.frequencies(samples)?;
+		let n = self.qubit_count;
+		let dim = 1 << n;
+		let mut rho = vec![Complex::new(0., 0.); dim * dim];
+		for types in 0..1usize << (2 * n) {
+			let paulis = pauli_list(n, types);
+			let mask = paulis
+				.iter()
+				.enumerate()
+				.filter(|(_, &p)| p != Pauli::I)
+				.fold(0, |mask, (q, _)| mask | 1 << q);
+			let mut sum = 0.;
+			let mut count = 0;
+			for (basis, frequency) in self.bases.iter().zip(&frequencies) {
+				if paulis
+					.iter()
+					.zip(basis)
+					.any(|(&p, &b)| p != Pauli::I && p != b)
+				{
+					continue;
+				}
+				sum += frequency
+					.iter()
+					.enumerate()
+					.map(|(b, f)| {
+						if (b & mask).count_ones() & 1 == 0 {
+							*f
+						} else {
+							-*f
+						}
+					})
+					.sum::<f64>();
+				count += 1;
+			}
+			let expectation = sum / count as f64;
+			for r in 0..dim {
+				for c in 0..dim {
+					rho[r * dim + c] +=
+						pauli_matrix_element(&paulis, r, c) * (expectation / dim as f64);
+				}
+			}
+		}
+		Ok(rho)
+	}
+
+	/// Reconstruct the density matrix maximizing the likelihood of `samples`
+	/// by the iterative RρR algorithm (Hradil, 1997), starting from the
+	/// maximally mixed state. Stops after `max_iterations` iterations or when
+	/// no element changes by more than `tolerance`.
+	///
+	/// The result is positive semidefinite with unit trace.
+	pub fn maximum_likelihood(
+		&self,
+		samples: &[Vec<u64>],
+		max_iterations: usize,
+		tolerance: f64,
+	) -> Result<Vec<Complex<f64>>, TomographyErr> {
+		let frequencies = self.frequencies(samples)?;
+		let dim = 1 << self.qubit_count;
+		let mut rho = vec![Complex::new(0., 0.); dim * dim];
+		for i in 0..dim {
+			rho[i * dim + i] = Complex::new(1. / dim as f64, 0.);
+		}
+		let projectors: Vec<Vec<[Complex<f64>; 4]>> = self
+			.bases
+			.iter()
+			.map(|basis| basis.iter().map(|b| b.matrix()).collect())
+			.collect();
+		for _ in 0..max_iterations {
+			let mut r = vec![Complex::new(0., 0.); dim * dim];
+			for (matrices, frequency) in projectors.iter().zip(&frequencies) {
+				for (outcome, &f) in frequency.iter().enumerate() {
+					if f == 0. {
+						continue;
+					}
+					let element =
+						|row: usize, col: usize| projector_element(matrices, outcome, row, col);
+					let mut probability = 0.;
+					for row in 0..dim {
+						for col in 0..dim {
+							probability += (element(row, col) * rho[col * dim + row]).re;
+						}
+					}
+					let weight = f / probability.max(1e-12);
+					for row in 0..dim {
+						for col in 0..dim {
+							r[row * dim + col] += element(row, col) * weight;
+						}
+					}
+				}
+			}
+			let mut next = matmul(&matmul(&r, &rho, dim), &r, dim);
+			let trace: f64 = (0..dim).map(|i| next[i * dim + i].re).sum();
+			for x in next.iter_mut() {
+				*x /= trace;
+			}
+			let change = next
+				.iter()
+				.zip(&rho)
+				.map(|(a, b)| (a - b).norm())
+				.fold(0., f64::max);
+			rho = next;
+			if change <= tolerance {
+				break;
+			}
+		}
+		Ok(rho)
+	}
+
+	/// Get the relative frequency of each outcome for each basis.
+	fn frequencies(&self, samples: &[Vec<u64>]) -> Result<Vec<Vec<f64>>, TomographyErr> {
+		if samples.len() != self.bases.len() {
+			return Err(TomographyErr::InconsistentDataLength(
+				self.bases.len(),
+				samples.len(),
+			));
+		}
+		samples
+			.iter()
+			.enumerate()
+			.map(|(i, outcomes)| {
+				if outcomes.is_empty() {
+					return Err(TomographyErr::NoSamples(i));
+				}
+				let mut frequency = vec![0.; 1 << self.qubit_count];
+				for &outcome in outcomes {
+					*frequency
+						.get_mut(outcome as usize)
+						.ok_or(TomographyErr::InvalidOutcome(outcome))? += 1. / outcomes.len() as f64;
+				}
+				Ok(frequency)
+			})
+			.collect()
+	}
+}
+
+/// Single-qubit input state of process tomography.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preparation {
+	/// |0>
+	Zero,
+	/// |1>
+	One,
+	/// |+> = (|0> + |1>)/√2
+	Plus,
+	/// |+i> = (|0> + i|1>)/√2
+	PlusI,
+}
+
+impl Preparation {
+	/// Get the coefficients c_k of the matrix unit |j><l| = Σ_k c_k ρ_k over the
+	/// density matrices ρ_k of `Zero`, `One`, `Plus` and `PlusI`.
+	fn dual(j: usize, l: usize) -> [Complex<f64>; 4] {
+		let zero = Complex::new(0., 0.);
+		let one = Complex::new(1., 0.);
+		let i = Complex::new(0., 1.);
+		match (j, l) {
+			(0, 0) => [one, zero, zero, zero],
+			(1, 1) => [zero, one, zero, zero],
+			// |0><1| = (X + iY)/2 = ρ_+ + iρ_+i - (1 + i)/2 I
+			(0, 1) => [-(one + i) / 2., -(one + i) / 2., one, i],
+			_ => [-(one - i) / 2., -(one - i) / 2., one, -i],
+		}
+	}
+}
+
+/// Full process tomography preparing every qubit in |0>, |1>, |+> or |+i>,
+/// over all 4^n combinations, and measuring the output by
+/// `StateTomography`.
+///
+/// The Choi matrix of a channel E is Λ = Σ_jl |j><l| ⊗ E(|j><l|), a row-major
+/// 4^n × 4^n matrix whose element at (j 2^n + a, l 2^n + b) is
+/// E(|j><l|)[a, b]. Its trace is 2^n.
+///
+/// ```
+/// # use qurs::tomography::ProcessTomography;
+/// # use qurs::circuit::{Circuit, Gate};
+/// let mut circuit = Circuit::new(1);
+/// circuit.add_gate(Gate::X(0));
+/// let tomography = ProcessTomography::new(1);
+/// let samples = tomography.sample(&circuit, 200).unwrap();
+/// let ptm = tomography.pauli_transfer_matrix(&samples).unwrap();
+/// assert!((ptm[0] - 1.).abs() < 1e-10);
+/// assert!((ptm[15] + 1.).abs() < 1e-10);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProcessTomography {
+	preparations: Vec<Vec<Preparation>>,
+	measurement: StateTomography,
+}
+
+impl ProcessTomography {
+	/// Build the tomography of `qubit_count` qubits. Preparations are ordered
+	/// with the state of qubit 0 changing fastest.
+	pub fn new(qubit_count: usize) -> Self {
+		let preparations = (0..4usize.pow(qubit_count as u32))
+			.map(|index| {
+				(0..qubit_count)
+					.map(|q| {
+						[
+							Preparation::Zero,
+							Preparation::One,
+							Preparation::Plus,
+							Preparation::PlusI,
+						][(index >> (2 * q)) & 3]
+					})
+					.collect()
+			})
+			.collect();
+		Self {
+			preparations,
+			measurement: StateTomography::new(qubit_count),
+		}
+	}
+
+	///Get qubit count
+	pub fn qubit_count(&self) -> usize {
+		self.measurement.qubit_count
+	}
+
+	///Get input states, indexed by qubit
+	pub fn preparations(&self) -> &[Vec<Preparation>] {
+		&self.preparations
+	}
+
+	///Get the state tomography of the output
+	pub fn measurement(&self) -> &StateTomography {
+		&self.measurement
+	}
+
+	/// Get the circuits preparing each input state, running `process` and
+	/// changing the basis for each measurement. Circuits for the same input
+	/// state are consecutive, in the order of `measurement().circuits()`.
+	pub fn circuits(&self, process: &Circuit) -> Vec<Circuit> {
+		let n = self.qubit_count();
+		let mut circuits = Vec::new();
+		for preparation in &self.preparations {
+			for basis in self.measurement.bases() {
+				let mut circuit = Circuit::new(n);
+				for (q, p) in preparation.iter().enumerate() {
+					let q = q as u32;
+					match p {
+						Preparation::Zero => {}
+						Preparation::One => circuit.add_gate(Gate::X(q)),
+						Preparation::Plus => circuit.add_gate(Gate::H(q)),
+						Preparation::PlusI => {
+							circuit.add_gate(Gate::H(q));
+							circuit.add_gate(Gate::S(q));
+						}
+					}
+				}
+				circuit.merge_circuit(process);
+				add_basis_change(&mut circuit, basis);
+				circuits.push(circuit);
+			}
+		}
+		circuits
+	}
+
+	/// Run `circuits(process)` on |0...0>, sampling `shots` times from each.
+	pub fn sample(&self, process: &Circuit, shots: u32) -> Result<Vec<Vec<u64>>, StateErr> {
+		self.circuits(process)
+			.iter()
+			.map(|circuit| {
+				let mut state = StateVec::new(self.qubit_count());
+				circuit.update_quantum_state(state.as_mut())?;
+				Ok(state.sampling(shots))
+			})
+			.collect()
+	}
+
+	/// Reconstruct the Choi matrix by linear inversion of the output states
+	/// and of the input states.
+	pub fn choi(&self, samples: &[Vec<u64>]) -> Result<Vec<Complex<f64>>, TomographyErr> {
+		let per_input = self.measurement.bases.len();
+		let circuit_count = self.preparations.len() * per_input;
+		if samples.len() != circuit_count {
+			return Err(TomographyErr::InconsistentDataLength(
+				circuit_count,
+				samples.len(),
+			));
+		}
+		let outputs = samples
+			.chunks(per_input)
+			.enumerate()
+			.map(|(k, chunk)| {
+				self.measurement
+					.linear_inversion(chunk)
+					.map_err(|e| match e {
+						TomographyErr::NoSamples(i) => TomographyErr::NoSamples(k * per_input + i),
+						e => e,
+					})
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+		let n = self.qubit_count();
+		let dim = 1 << n;
+		let mut choi = vec![Complex::new(0., 0.); dim * dim * dim * dim];
+		for j in 0..dim {
+			for l in 0..dim {
+				let duals: Vec<[Complex<f64>; 4]> = (0..n)
+					.map(|q| Preparation::dual((j >> q) & 1, (l >> q) & 1))
+					.collect();
+				for (k, output) in outputs.iter().enumerate() {
+					let coefficient: Complex<f64> = duals
+						.iter()
+						.enumerate()
+						.map(|(q, dual)| dual[(k >> (2 * q)) & 3])
+						.product();
+					if coefficient.norm_sqr() == 0. {
+						continue;
+					}
+					for a in 0..dim {
+						for b in 0..dim {
+							choi[(j * dim + a) * dim * dim + l * dim + b] +=
+								coefficient * output[a * dim + b];
+						}
+					}
+				}
+			}
+		}
+		Ok(choi)
+	}
+
+	/// Reconstruct the Pauli transfer matrix by linear inversion. See
+	/// `choi_to_pauli_transfer_matrix`.
+	pub fn pauli_transfer_matrix(&self, samples: &[Vec<u64>]) -> Result<Vec<f64>, TomographyErr> {
+		Ok(choi_to_pauli_transfer_matrix(
+			self.qubit_count(),
+			&self.choi(samples)?,
+		))
+	}
+}
+
+/// Get the Choi matrix of the channel ρ ↦ UρU† of the row-major 2^n × 2^n
+/// `unitary`, in the convention of `ProcessTomography`.
+pub fn unitary_choi(qubit_count: usize, unitary: &[Complex<f64>]) -> Vec<Complex<f64>> {
+	let dim = 1 << qubit_count;
+	assert_eq!(
+		unitary.len(),
+		dim * dim,
+		"the unitary must act on {} qubits",
+		qubit_count
+	);
+	let mut choi = vec![Complex::new(0., 0.); dim * dim * dim * dim];
+	for j in 0..dim {
+		for l in 0..dim {
+			for a in 0..dim {
+				for b in 0..dim {
+					choi[(j * dim + a) * dim * dim + l * dim + b] =
+						unitary[a * dim + j] * unitary[b * dim + l].conj();
+				}
+			}
+		}
+	}
+	choi
+}
+
+/// Get the row-major 4^n × 4^n Pauli transfer matrix R_ij = tr(P_i E(P_j))/2^n
+/// of the channel E with the Choi matrix `choi`. Paulis are indexed by
+/// Σ_q t_q 4^q for the Pauli type t_q of qubit q.
+pub fn choi_to_pauli_transfer_matrix(qubit_count: usize, choi: &[Complex<f64>]) -> Vec<f64> {
+	let dim = 1 << qubit_count;
+	let size = dim * dim;
+	assert_eq!(
+		choi.len(),
+		size * size,
+		"the Choi matrix must act on {} qubits",
+		2 * qubit_count
+	);
+	let paulis: Vec<Vec<Pauli>> = (0..size)
+		.map(|types| pauli_list(qubit_count, types))
+		.collect();
+	let mut ptm = vec![0.; size * size];
+	for (col, p) in paulis.iter().enumerate() {
+		// E(P) = Σ_jl P[j, l] E(|j><l|)
+		let mut image = vec![Complex::new(0., 0.); size];
+		for j in 0..dim {
+			for l in 0..dim {
+				let element = pauli_matrix_element(p, j, l);
+				if element.norm_sqr() == 0. {
+					continue;
+				}
+				for a in 0..dim {
+					for b in 0..dim {
+						image[a * dim + b] += element * choi[(j * dim + a) * size + l * dim + b];
+					}
+				}
+			}
+		}
+		for (row, q) in paulis.iter().enumerate() {
+			let mut trace = Complex::new(0., 0.);
+			for a in 0..dim {
+				for b in 0..dim {
+					trace += pauli_matrix_element(q, b, a) * image[a * dim + b];
+				}
+			}
+			ptm[row * size + col] = trace.re / dim as f64;
+		}
+	}
+	ptm
+}
+
+/// Append the rotation from the eigenbasis of `basis[q]` on each qubit q to
+/// the computational basis.
+fn add_basis_change(circuit: &mut Circuit, basis: &[Pauli]) {
+	for (q, &p) in basis.iter().enumerate() {
+		let q = q as u32;
+		match p {
+			Pauli::X => circuit.add_gate(Gate::H(q)),
+			Pauli::Y => {
+				circuit.add_gate(Gate::Sdag(q));
+				circuit.add_gate(Gate::H(q));
+			}
+			_ => {}
+		}
+	}
+}
+
+/// Get the element at (`row`, `col`) of the projector ⊗_q (I ± B_q)/2 for the
+/// measurement `outcome` in the basis with the single-qubit `matrices` B_q.
+fn projector_element(
+	matrices: &[[Complex<f64>; 4]],
+	outcome: usize,
+	row: usize,
+	col: usize,
+) -> Complex<f64> {
+	matrices
+		.iter()
+		.enumerate()
+		.map(|(q, m)| {
+			let (r, c) = ((row >> q) & 1, (col >> q) & 1);
+			let identity = if r == c { 1. } else { 0. };
+			let sign = if (outcome >> q) & 1 == 0 { 1. } else { -1. };
+			(m[r * 2 + c] * sign + identity) / 2.
+		})
+		.product()
+}
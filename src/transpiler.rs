@@ -0,0 +1,426 @@
+use crate::circuit::{Circuit, Gate};
+use crate::gate::ControlValue;
+use crate::optimizer::{FuseSingleQubitGates, Pass};
+use crate::synthesis::{
+	dagger2, decompose_two_qubit_unitary, euler_zyz, euler_zyz_matrix, nonzero_angle, sqrt2,
+	zyz_gates, EulerAngles,
+};
+use crate::Complex;
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::fmt;
+
+/// Tolerance for recognizing special angles and matrices.
+const ANGLE_EPS: f64 = 1e-10;
+
+/// Native gate set of a target device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Basis {
+	/// `Rz`, `SqrtX`, `X` and `Cnot`
+	RzSxXCnot,
+	/// `Rx`, `Ry`, `Rz` and `Cz`
+	RxRyRzCz,
+}
+
+impl Basis {
+	/// Whether `gate` belongs to this basis.
+	pub fn contains(&self, gate: &Gate) -> bool {
+		match self {
+			Basis::RzSxXCnot => matches!(
+				gate,
+				Gate::Rz(..) | Gate::SqrtX(_) | Gate::X(_) | Gate::Cnot(..)
+			),
+			Basis::RxRyRzCz => matches!(
+				gate,
+				Gate::Rx(..) | Gate::Ry(..) | Gate::Rz(..) | Gate::Cz(..)
+			),
+		}
+	}
+}
+
+#[non_exhaustive]
+pub enum TranspileErr {
+	/// The gate is not unitary or no decomposition is known for it.
+	UnsupportedGate(Gate),
+}
+
+impl fmt::Display for TranspileErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			TranspileErr::UnsupportedGate(gate) => {
+				write!(
+					f,
+					"Unsupported gate: no decomposition is known for {:?}",
+					gate
+				)
+			}
+		}
+	}
+}
+
+impl fmt::Debug for TranspileErr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		<Self as fmt::Display>::fmt(self, f)
+	}
+}
+
+/// Rewrite `circuit` into the gates of `basis`. The result is equal to the
+/// input up to global phase.
+///
+/// Every gate is first decomposed exactly into single-qubit unitaries and
+/// CNOT or CZ, using `synthesis::decompose_two_qubit_unitary` for two-qubit
+/// gates without a known construction. Runs of single-qubit unitaries are
+/// then fused and emitted through their ZYZ Euler angles.
+///
+/// ```
+/// # use qurs::circuit::{equivalent, Circuit, Gate};
+/// # use qurs::transpiler::{transpile, Basis};
+/// let mut circuit = Circuit::new(3);
+/// circuit.add_gate(Gate::Ccnot(0, 1, 2));
+/// let transpiled = transpile(&circuit, Basis::RxRyRzCz).unwrap();
+/// assert!(transpiled.gates().iter().all(|g| Basis::RxRyRzCz.contains(g)));
+/// assert!(equivalent(&circuit, &transpiled, true));
+/// ```
+pub fn transpile(circuit: &Circuit, basis: Basis) -> Result<Circuit, TranspileErr> {
+	let mut lowered = Circuit::new(circuit.qubit_count());
+	for gate in circuit.gates() {
+		for gate in decompose(gate)? {
+			match (basis, gate) {
+				(Basis::RzSxXCnot, Gate::Cz(c, t)) => {
+					lowered.add_gate(single(&Gate::H(t)));
+					lowered.add_gate(Gate::Cnot(c, t));
+					lowered.add_gate(single(&Gate::H(t)));
+				}
+				(Basis::RxRyRzCz, Gate::Cnot(c, t)) => {
+					lowered.add_gate(single(&Gate::H(t)));
+					lowered.add_gate(Gate::Cz(c, t));
+					lowered.add_gate(single(&Gate::H(t)));
+				}
+				(_, gate) => lowered.add_gate(gate),
+			}
+		}
+	}
+
+	let fused = FuseSingleQubitGates.run(&lowered);
+	let mut transpiled = Circuit::new(circuit.qubit_count());
+	for gate in fused.gates() {
+		match gate {
+			Gate::DenseMatrix { targets, matrix } => {
+				let matrix = [matrix[0], matrix[1], matrix[2], matrix[3]];
+				for gate in single_qubit_in_basis(targets[0], &matrix, basis) {
+					transpiled.add_gate(gate);
+				}
+			}
+			gate => transpiled.add_gate(gate.clone()),
+		}
+	}
+	Ok(transpiled)
+}
+
+/// Emit a single-qubit unitary in `basis`, up to global phase.
+fn single_qubit_in_basis(qubit: u32, matrix: &[Complex<f64>; 4], basis: Basis) -> Vec<Gate> {
+	if basis == Basis::RxRyRzCz {
+		return zyz_gates(qubit, matrix);
+	}
+	let EulerAngles {
+		theta, phi, lambda, ..
+	} = euler_zyz(matrix);
+	let mut gates = Vec::new();
+	let rz = |gates: &mut Vec<Gate>, angle: f64| {
+		if let Some(angle) = nonzero_angle(angle) {
+			gates.push(Gate::Rz(qubit, angle));
+		}
+	};
+	if nonzero_angle(theta).is_none() {
+		rz(&mut gates, phi + lambda);
+	} else if (theta - PI).abs() < ANGLE_EPS {
+		// Ry(π) = XZ and Z ∝ Rz(π)
+		rz(&mut gates, lambda + PI);
+		gates.push(Gate::X(qubit));
+		rz(&mut gates, phi);
+	} else {
+		// Ry(θ) ∝ Rz(π) SX Rz(θ + π) SX
+		rz(&mut gates, lambda);
+		gates.push(Gate::SqrtX(qubit));
+		rz(&mut gates, theta + PI);
+		gates.push(Gate::SqrtX(qubit));
+		rz(&mut gates, phi + PI);
+	}
+	gates
+}
+
+/// Get a single-qubit gate as `Gate::DenseMatrix`.
+fn single(gate: &Gate) -> Gate {
+	Gate::DenseMatrix {
+		targets: gate.qubits(),
+		matrix: gate.matrix(),
+	}
+}
+
+fn dense(qubit: u32, matrix: [Complex<f64>; 4]) -> Gate {
+	Gate::DenseMatrix {
+		targets: vec![qubit],
+		matrix: matrix.to_vec(),
+	}
+}
+
+/// Decompose `gate` exactly into single-qubit `Gate::DenseMatrix`, `Gate::Cnot`
+/// and `Gate::Cz`.
+fn decompose(gate: &Gate) -> Result<Vec<Gate>, TranspileErr> {
+	use Gate::*;
+	let gates = match gate {
+		P0(_) | P1(_) => return Err(TranspileErr::UnsupportedGate(gate.clone())),
+		Cnot(..) | Cz(..) => vec![gate.clone()],
+		Swap(a, b) => vec![Cnot(*a, *b), Cnot(*b, *a), Cnot(*a, *b)],
+		Ccz(a, b, c) => ccz(*a, *b, *c),
+		Ccnot(a, b, c) => {
+			let mut gates = vec![single(&H(*c))];
+			gates.extend(ccz(*a, *b, *c));
+			gates.push(single(&H(*c)));
+			gates
+		}
+		MultiControlled {
+			controls,
+			target,
+			matrix,
+		} => controlled_unitary(controls, *target, matrix),
+		PauliRotation {
+			targets,
+			paulis,
+			angle,
+		} => pauli_rotation(targets, paulis, *angle),
+		DenseMatrix { targets, .. } if targets.len() == 1 => vec![gate.clone()],
+		DenseMatrix { targets, .. } if targets.len() > 2 => {
+			return Err(TranspileErr::UnsupportedGate(gate.clone()))
+		}
+		Controlled {
+			controls,
+			targets,
+			matrix,
+		} if targets.len() <= 2 => {
+			// controls on |0> are controls on |1> between X gates
+			let flips: Vec<Gate> = controls
+				.iter()
+				.filter(|&&(_, value)| value == ControlValue::Zero)
+				.map(|&(q, _)| single(&X(q)))
+				.collect();
+			let qubits: Vec<u32> = controls.iter().map(|&(q, _)| q).collect();
+			let mut gates = flips.clone();
+			if let [target] = targets[..] {
+				let matrix = [matrix[0], matrix[1], matrix[2], matrix[3]];
+				gates.extend(controlled_unitary(&qubits, target, &matrix));
+			} else {
+				gates.extend(controlled_two_qubit_unitary(&qubits, targets, matrix));
+			}
+			gates.extend(flips);
+			gates
+		}
+		MultiControlledPhase {
+			controls,
+			target,
+			angle,
+		} => {
+			let matrix = [
+				Complex::new(1., 0.),
+				Complex::new(0., 0.),
+				Complex::new(0., 0.),
+				Complex::from_polar(1., *angle),
+			];
+			controlled_unitary(controls, *target, &matrix)
+		}
+		gate => match gate.qubits()[..] {
+			[_] => vec![single(gate)],
+			[a, b] => decompose_two_qubit_unitary(&[a, b], &gate.matrix())
+				.iter()
+				.map(|gate| match gate {
+					Cnot(..) => gate.clone(),
+					gate => single(gate),
+				})
+				.collect(),
+			_ => return Err(TranspileErr::UnsupportedGate(gate.clone())),
+		},
+	};
+	Ok(gates)
+}
+
+/// CCZ with 6 CNOTs and T gates (Nielsen & Chuang, Fig. 4.9 without the
+/// Hadamards on the target).
+fn ccz(a: u32, b: u32, c: u32) -> Vec<Gate> {
+	use Gate::*;
+	let t = |q| single(&T(q));
+	let tdag = |q| single(&Tdag(q));
+	vec![
+		Cnot(b, c),
+		tdag(c),
+		Cnot(a, c),
+		t(c),
+		Cnot(b, c),
+		tdag(c),
+		Cnot(a, c),
+		t(b),
+		t(c),
+		Cnot(a, b),
+		t(a),
+		tdag(b),
+		Cnot(a, b),
+	]
+}
+
+/// Controlled two-qubit unitary: the controls are added to every gate of the
+/// decomposition of the target unitary and to the global phase it leaves out,
+/// so that a controlled CNOT becomes a Toffoli gate.
+fn controlled_two_qubit_unitary(
+	controls: &[u32],
+	targets: &[u32],
+	matrix: &[Complex<f64>],
+) -> Vec<Gate> {
+	// decompose on qubits 0 and 1 to compare with `matrix` for the global phase
+	let local = decompose_two_qubit_unitary(&[0, 1], matrix);
+	let mut circuit = Circuit::new(2);
+	for gate in &local {
+		circuit.add_gate(gate.clone());
+	}
+	let overlap: Complex<f64> = circuit
+		.to_unitary()
+		.iter()
+		.zip(matrix)
+		.map(|(u, m)| u.conj() * m)
+		.sum();
+
+	let x = [
+		Complex::new(0., 0.),
+		Complex::new(1., 0.),
+		Complex::new(1., 0.),
+		Complex::new(0., 0.),
+	];
+	let mut gates = Vec::new();
+	for gate in local {
+		match gate.map_qubits(|q| targets[q as usize]) {
+			Gate::Cnot(a, b) => {
+				let mut qubits = controls.to_vec();
+				qubits.push(a);
+				gates.extend(controlled_unitary(&qubits, b, &x));
+			}
+			gate => {
+				let m = gate.matrix();
+				let matrix = [m[0], m[1], m[2], m[3]];
+				gates.extend(controlled_unitary(controls, gate.qubits()[0], &matrix));
+			}
+		}
+	}
+	if let (Some((&first, rest)), Some(angle)) =
+		(controls.split_first(), nonzero_angle(overlap.arg()))
+	{
+		let phase = [
+			Complex::new(1., 0.),
+			Complex::new(0., 0.),
+			Complex::new(0., 0.),
+			Complex::from_polar(1., angle),
+		];
+		gates.extend(controlled_unitary(rest, first, &phase));
+	}
+	gates
+}
+
+fn is_x(matrix: &[Complex<f64>; 4]) -> bool {
+	let x = [
+		Complex::new(0., 0.),
+		Complex::new(1., 0.),
+		Complex::new(1., 0.),
+		Complex::new(0., 0.),
+	];
+	matrix
+		.iter()
+		.zip(&x)
+		.all(|(a, b)| (a - b).norm() < ANGLE_EPS)
+}
+
+/// Single-qubit `matrix` on `target` controlled by all `controls`.
+fn controlled_unitary(controls: &[u32], target: u32, matrix: &[Complex<f64>; 4]) -> Vec<Gate> {
+	match controls {
+		[] => vec![dense(target, *matrix)],
+		[c] if is_x(matrix) => vec![Gate::Cnot(*c, target)],
+		[a, b] if is_x(matrix) => {
+			let mut gates = vec![single(&Gate::H(target))];
+			gates.extend(ccz(*a, *b, target));
+			gates.push(single(&Gate::H(target)));
+			gates
+		}
+		[c] => {
+			// U = e^{iα} A X B X C with ABC = I (Nielsen & Chuang, Cor. 4.2)
+			let angles = euler_zyz(matrix);
+			let rotation = |theta, phi, lambda| {
+				euler_zyz_matrix(&EulerAngles {
+					global_phase: 0.,
+					theta,
+					phi,
+					lambda,
+				})
+			};
+			let a = rotation(angles.theta / 2., angles.phi, 0.);
+			let b = rotation(-angles.theta / 2., 0., -(angles.lambda + angles.phi) / 2.);
+			let c_matrix = rotation(0., 0., (angles.lambda - angles.phi) / 2.);
+			let phase = [
+				Complex::new(1., 0.),
+				Complex::new(0., 0.),
+				Complex::new(0., 0.),
+				Complex::from_polar(1., angles.global_phase),
+			];
+			vec![
+				dense(target, c_matrix),
+				Gate::Cnot(*c, target),
+				dense(target, b),
+				Gate::Cnot(*c, target),
+				dense(target, a),
+				dense(*c, phase),
+			]
+		}
+		_ => {
+			// Barenco et al. (1995), Lemma 7.5 with V^2 = U
+			let (last, rest) = controls.split_last().unwrap();
+			let v = sqrt2(matrix);
+			let x = [
+				Complex::new(0., 0.),
+				Complex::new(1., 0.),
+				Complex::new(1., 0.),
+				Complex::new(0., 0.),
+			];
+			let mut gates = controlled_unitary(&[*last], target, &v);
+			gates.extend(controlled_unitary(rest, *last, &x));
+			gates.extend(controlled_unitary(&[*last], target, &dagger2(&v)));
+			gates.extend(controlled_unitary(rest, *last, &x));
+			gates.extend(controlled_unitary(rest, target, &v));
+			gates
+		}
+	}
+}
+
+/// exp(-i(θ/2)P) by changing each qubit into the Z basis and computing the
+/// parity with a CNOT ladder.
+fn pauli_rotation(targets: &[u32], paulis: &[u32], angle: f64) -> Vec<Gate> {
+	let active: Vec<(u32, u32)> = targets
+		.iter()
+		.cloned()
+		.zip(paulis.iter().cloned())
+		.filter(|&(_, p)| p != 0)
+		.collect();
+	let (last, _) = match active.last() {
+		Some(last) => *last,
+		// the identity only changes the global phase
+		None => return Vec::new(),
+	};
+	let to_z = |q: u32, p: u32, sign: f64| match p {
+		1 => Some(single(&Gate::H(q))),
+		2 => Some(single(&Gate::Rx(q, sign * FRAC_PI_2))),
+		_ => None,
+	};
+	let mut gates: Vec<Gate> = active.iter().filter_map(|&(q, p)| to_z(q, p, 1.)).collect();
+	for pair in active.windows(2) {
+		gates.push(Gate::Cnot(pair[0].0, pair[1].0));
+	}
+	gates.push(single(&Gate::Rz(last, angle)));
+	for pair in active.windows(2).rev() {
+		gates.push(Gate::Cnot(pair[0].0, pair[1].0));
+	}
+	gates.extend(active.iter().filter_map(|&(q, p)| to_z(q, p, -1.)));
+	gates
+}
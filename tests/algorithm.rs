@@ -0,0 +1,138 @@
+use num::Complex;
+use qurs::algorithm::{
+	grover, grover_iteration_count, inverse_qft, maximum_likelihood_amplitude_estimation,
+	phase_estimation, phase_oracle, qft,
+};
+use qurs::circuit::{Circuit, Gate};
+use qurs::prelude::*;
+use qurs::{self, inner_product, StateVec};
+use std::f64::consts::PI;
+
+const EPS: f64 = 1e-10;
+
+#[test]
+fn qft_basis_state() {
+	let n = 4;
+	let dim = 1 << n;
+	let targets: Vec<u32> = (0..n).collect();
+	let circuit = qft(n as usize, &targets, None);
+	for x in 0..dim {
+		let mut state = StateVec::new(n as usize);
+		state.set_computational_basis(x);
+		circuit.update_quantum_state(state.as_mut()).unwrap();
+		for y in 0..dim {
+			let expected = Complex::from_polar(1., 2. * PI * (x * y) as f64 / dim as f64)
+				/ (dim as f64).sqrt();
+			assert!((state.as_ref()[y] - expected).norm() < EPS);
+		}
+	}
+}
+
+#[test]
+fn inverse_qft_roundtrip() {
+	let targets = [1, 3, 4];
+	let mut state = StateVec::new(5);
+	state.set_haar_random_state_with_seed(7);
+	let mut transformed = StateVec::new(5);
+	transformed.as_mut().copy_from_slice(state.as_ref());
+	qft(5, &targets, None)
+		.update_quantum_state(transformed.as_mut())
+		.unwrap();
+	inverse_qft(5, &targets, None)
+		.update_quantum_state(transformed.as_mut())
+		.unwrap();
+	let overlap = inner_product(state.as_ref(), transformed.as_ref()).unwrap();
+	assert!((overlap - Complex::new(1., 0.)).norm() < EPS);
+}
+
+#[test]
+fn approximate_qft() {
+	let targets: Vec<u32> = (0..6).collect();
+	let exact = qft(6, &targets, None);
+	let approximate = qft(6, &targets, Some(4));
+	assert!(approximate.gate_count() < exact.gate_count());
+
+	let mut a = StateVec::new(6);
+	let mut b = StateVec::new(6);
+	a.set_computational_basis(0b101101);
+	b.set_computational_basis(0b101101);
+	exact.update_quantum_state(a.as_mut()).unwrap();
+	approximate.update_quantum_state(b.as_mut()).unwrap();
+	assert!(inner_product(a.as_ref(), b.as_ref()).unwrap().norm_sqr() > 0.9);
+}
+
+#[test]
+fn grover_search() {
+	let n = 5;
+	let marked = 0b10110;
+	let oracle = phase_oracle(n, &[marked]);
+	let circuit = grover(&oracle, grover_iteration_count(n, 1));
+	let mut state = StateVec::new(n);
+	circuit.update_quantum_state(state.as_mut()).unwrap();
+	// sin^2((2k+1)θ) with sin θ = 1/√32 and k = 4
+	let theta = (1. / 32f64).sqrt().asin();
+	let expected = (9. * theta).sin().powi(2);
+	assert!((state.as_ref()[marked].norm_sqr() - expected).abs() < EPS);
+}
+
+#[test]
+fn grover_iteration_count_bounds() {
+	assert_eq!(grover_iteration_count(3, 8), 0);
+	assert_eq!(grover_iteration_count(4, 1), 3);
+}
+
+#[test]
+#[should_panic]
+fn grover_iteration_count_rejects_no_marked_state() {
+	grover_iteration_count(3, 0);
+}
+
+#[test]
+#[should_panic]
+fn grover_iteration_count_rejects_too_many_marked_states() {
+	grover_iteration_count(3, 9);
+}
+
+#[test]
+fn phase_estimation_exact_phase() {
+	// U = diag(1, e^{2πiφ}) on qubit 3, prepared in the eigenstate |1>
+	let phase = 5. / 8.;
+	let evaluation = [0, 1, 2];
+	let mut circuit = Circuit::new(4);
+	circuit.add_gate(Gate::X(3));
+	circuit.merge_circuit(&phase_estimation(4, &evaluation, |c, control, k| {
+		let angle = 2. * PI * phase * 2f64.powi(k as i32);
+		c.add_gate(Gate::MultiControlled {
+			controls: vec![control],
+			target: 3,
+			matrix: [
+				Complex::new(1., 0.),
+				Complex::new(0., 0.),
+				Complex::new(0., 0.),
+				Complex::from_polar(1., angle),
+			],
+		});
+	}));
+	let mut state = StateVec::new(4);
+	circuit.update_quantum_state(state.as_mut()).unwrap();
+	let probability = state
+		.get_marginal_probability(&[0, 1, 2], &[1, 0, 1])
+		.unwrap();
+	assert!((probability - 1.).abs() < EPS);
+}
+
+#[test]
+fn amplitude_estimation() {
+	let theta: f64 = 0.3;
+	let mut preparation = Circuit::new(2);
+	preparation.add_gate(Gate::Ry(0, 2. * theta));
+	preparation.add_gate(Gate::H(1));
+	let estimate = maximum_likelihood_amplitude_estimation(
+		&preparation,
+		|i| i & 1 == 1,
+		&[0, 1, 2, 4, 8, 16],
+		200,
+	)
+	.unwrap();
+	assert!((estimate - theta.sin().powi(2)).abs() < 0.01);
+}
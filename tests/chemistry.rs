@@ -0,0 +1,350 @@
+use qurs::chemistry::{hartree_fock_circuit, CoupledClusterAnsatz, Fcidump, FcidumpErr};
+use qurs::circuit::Gate;
+use qurs::fermion::FermionEncoding;
+use qurs::observable::Observable;
+use qurs::prelude::*;
+use qurs::{Complex, StateVec};
+use std::f64::consts::PI;
+
+mod common;
+use common::ground_energy;
+
+/// H2 in STO-3G at 0.7414 Å, listing only symmetry-unique integrals
+const H2: &str = " &FCI NORB=  2,NELEC=  2,MS2=0,
+  ORBSYM=1,5,
+  ISYM=1,
+ &END
+  0.6744887663D+00   1   1   1   1
+  0.1812875334D+00   2   1   2   1
+  0.6636340479D+00   2   2   1   1
+  0.6973979495D+00   2   2   2   2
+ -0.1252463573D+01   1   1   0   0
+ -0.4759487753D+00   2   2   0   0
+ -0.5782   1   0   0   0
+  0.6703   2   0   0   0
+  0.7137539936D+00   0   0   0   0
+";
+
+/// LiH in STO-3G at 1.5949 Å with the Li 1s orbital frozen and the σ orbitals
+/// 2σ, 3σ and 4σ active
+const LIH: &str = " &FCI NORB=3,NELEC=2,MS2=0,
+  ORBSYM=1,1,1,
+  ISYM=1,
+ &END
+ 4.876647760608894e-01   1   1   1   1
+-4.849324294572174e-02   2   1   1   1
+ 1.301296418731437e-02   2   1   2   1
+ 2.237559368164468e-01   2   2   1   1
+ 7.416875005451660e-03   2   2   2   1
+ 3.379360501840698e-01   2   2   2   2
+ 1.270574492851430e-01   3   1   1   1
+-3.453980172757545e-02   3   1   2   1
+-1.228152782431959e-02   3   1   2   2
+ 1.238712536377989e-01   3   1   3   1
+-5.134025509176682e-02   3   2   1   1
+ 9.356423622028535e-03   3   2   2   1
+ 3.598195080574405e-02   3   2   2   2
+-3.185609578249337e-02   3   2   3   1
+ 2.643646116222163e-02   3   2   3   2
+ 4.540458932688288e-01   3   3   1   1
+-4.329290302005920e-02   3   3   2   1
+ 2.414684621707575e-01   3   3   2   2
+ 1.345351954511388e-01   3   3   3   1
+-4.405174020293193e-02   3   3   3   2
+ 4.539619017996532e-01   3   3   3   3
+-7.733695128362101e-01   1   1   0   0
+ 4.849324294295665e-02   2   1   0   0
+-3.562370609177381e-01   2   2   0   0
+-1.270574492860666e-01   3   1   0   0
+ 6.814070845642478e-02   3   2   0   0
+-2.350912529750351e-01   3   3   0   0
+-6.802952709782606e+00   0   0   0   0
+";
+
+const E_HF: f64 = -2. * 1.252463573 + 0.6744887663 + 0.7137539936;
+const E_DOUBLE: f64 = -2. * 0.4759487753 + 0.6973979495 + 0.7137539936;
+const EXCHANGE: f64 = 0.1812875334;
+
+#[test]
+fn test_parse_header_and_symmetry() {
+	let fcidump = Fcidump::parse(H2).unwrap();
+	assert_eq!(fcidump.orbital_count(), 2);
+	assert_eq!(fcidump.electron_count(), 2);
+	assert_eq!(fcidump.ms2(), 0);
+	assert_eq!(fcidump.orbital_symmetries(), &[1, 5]);
+	assert_eq!(fcidump.symmetry(), 1);
+	assert_eq!(fcidump.core_energy(), 0.7137539936);
+	assert_eq!(fcidump.one_body_integral(1, 1), -0.4759487753);
+	assert_eq!(fcidump.one_body_integral(0, 1), 0.);
+	for &(p, q, r, s) in &[(0, 1, 0, 1), (1, 0, 0, 1), (0, 1, 1, 0), (1, 0, 1, 0)] {
+		assert_eq!(fcidump.two_body_integral(p, q, r, s), EXCHANGE);
+	}
+	assert_eq!(fcidump.two_body_integral(0, 0, 1, 1), 0.6636340479);
+	assert_eq!(fcidump.two_body_integral(0, 1, 1, 1), 0.);
+	assert!((fcidump.hartree_fock_energy() - E_HF).abs() < 1e-12);
+}
+
+#[test]
+fn test_hartree_fock_state() {
+	let fcidump = Fcidump::parse(H2).unwrap();
+	assert_eq!(fcidump.hartree_fock_occupation(), 0b0011);
+	assert_eq!(
+		fcidump.hartree_fock_state(FermionEncoding::JordanWigner),
+		0b0011
+	);
+	assert_eq!(fcidump.hartree_fock_state(FermionEncoding::Parity), 0b0001);
+	assert_eq!(
+		fcidump.hartree_fock_state(FermionEncoding::BravyiKitaev),
+		0b0001
+	);
+	for &encoding in &[
+		FermionEncoding::JordanWigner,
+		FermionEncoding::Parity,
+		FermionEncoding::BravyiKitaev,
+	] {
+		let h = fcidump.observable(encoding);
+		let mut state = StateVec::new(h.qubit_count());
+		state.set_computational_basis(fcidump.hartree_fock_state(encoding));
+		let energy = h.expectation_value(&state).unwrap();
+		assert!((energy.re - E_HF).abs() < 1e-10, "{:?}", encoding);
+		assert!(energy.im.abs() < 1e-12);
+	}
+}
+
+#[test]
+fn test_hamiltonian_matrix_elements() {
+	let fcidump = Fcidump::parse(H2).unwrap();
+	let h = fcidump.observable(FermionEncoding::JordanWigner);
+	assert!(h.is_hermitian(1e-12));
+	let m = h.matrix();
+	let (hf, double) = (0b0011, 0b1100);
+	assert!((m[double * 16 + double].re - E_DOUBLE).abs() < 1e-10);
+	assert!((m[double * 16 + hf].norm() - EXCHANGE).abs() < 1e-10);
+	// the ground state lies in span{|HF>, |double>} and has the FCI energy
+	let mean = (E_HF + E_DOUBLE) / 2.;
+	let ground = mean - ((E_DOUBLE - E_HF).powi(2) / 4. + EXCHANGE * EXCHANGE).sqrt();
+	assert!((ground + 1.1373).abs() < 1e-4);
+}
+
+#[test]
+fn test_read_file() {
+	let path = std::env::temp_dir().join("qurs_test_h2.fcidump");
+	std::fs::write(&path, H2).unwrap();
+	assert_eq!(Fcidump::read(&path).unwrap(), Fcidump::parse(H2).unwrap());
+	std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_parse_errors() {
+	assert!(matches!(
+		Fcidump::parse("NORB=2"),
+		Err(FcidumpErr::InvalidHeader(_))
+	));
+	assert!(matches!(
+		Fcidump::parse("&FCI NELEC=2 &END"),
+		Err(FcidumpErr::MissingKey("NORB"))
+	));
+	assert!(matches!(
+		Fcidump::parse("&FCI NORB=1, NELEC=2, UHF=.TRUE. &END"),
+		Err(FcidumpErr::UnsupportedUhf)
+	));
+	assert!(matches!(
+		Fcidump::parse("&FCI NORB=1, NELEC=2 /\n 1.0 2 2 0 0"),
+		Err(FcidumpErr::InvalidOrbitalIndex(2, 2))
+	));
+	assert!(matches!(
+		Fcidump::parse("&FCI NORB=1, NELEC=2 &END\n 1.0 1 1 1"),
+		Err(FcidumpErr::InvalidLine(2, _))
+	));
+	assert!(matches!(
+		Fcidump::parse("&FCI NORB=2, NELEC=2 &END\n 1.0 0 1 0 0"),
+		Err(FcidumpErr::InvalidLine(2, _))
+	));
+	for header in &["NELEC=1, MS2=3", "NELEC=2, MS2=1", "NELEC=2, MS2=-4"] {
+		assert!(matches!(
+			Fcidump::parse(&format!("&FCI NORB=2, {} &END", header)),
+			Err(FcidumpErr::InvalidHeader(_))
+		));
+	}
+	assert!(matches!(
+		Fcidump::parse("&FCI NORB=1, NELEC=2, MS2=2 &END"),
+		Err(FcidumpErr::InvalidHeader(_))
+	));
+	for header in &[
+		"ISYM=-1",
+		"ISYM=4294967297",
+		"ISYM=9",
+		"ORBSYM=1",
+		"ORBSYM=1,0",
+		"ORBSYM=1,2,3",
+	] {
+		assert!(matches!(
+			Fcidump::parse(&format!("&FCI NORB=2, NELEC=2, {} &END", header)),
+			Err(FcidumpErr::InvalidHeader(_))
+		));
+	}
+	assert!(matches!(
+		Fcidump::read("/nonexistent/qurs.fcidump"),
+		Err(FcidumpErr::Io(_))
+	));
+}
+
+/// Exact ground energy in the sector of `electron_count` electrons with
+/// S_z = 0 under the Jordan–Wigner encoding.
+fn exact_ground_energy(h: &Observable, electron_count: u32) -> f64 {
+	let even = (0..h.qubit_count())
+		.step_by(2)
+		.map(|q| 1 << q)
+		.sum::<usize>();
+	ground_energy(h, |i| {
+		i.count_ones() == electron_count && (i & even).count_ones() == electron_count / 2
+	})
+}
+
+/// <ψ|H|ψ> with the dense matrix of H, which is faster than summing the Pauli
+/// terms.
+fn energy(ansatz: &CoupledClusterAnsatz, h: &[Complex<f64>], parameters: &[f64]) -> f64 {
+	let mut state = StateVec::new(ansatz.qubit_count());
+	ansatz
+		.circuit(parameters)
+		.update_quantum_state(state.as_mut())
+		.unwrap();
+	let psi = state.as_ref();
+	let dim = psi.len();
+	(0..dim)
+		.map(|r| {
+			let row: Complex<f64> = (0..dim).map(|c| h[r * dim + c] * psi[c]).sum();
+			(psi[r].conj() * row).re
+		})
+		.sum()
+}
+
+/// Minimize the energy by sweeps of exact minimization in one parameter,
+/// using that the energy is a trigonometric polynomial of degree at most 4 in
+/// each parameter.
+fn minimize(ansatz: &CoupledClusterAnsatz, h: &Observable) -> f64 {
+	let h = &h.matrix();
+	const SAMPLES: usize = 9;
+	let mut parameters = vec![0.; ansatz.parameter_count()];
+	let mut best = energy(ansatz, h, &parameters);
+	for _ in 0..30 {
+		let previous = best;
+		for k in 0..parameters.len() {
+			let origin = parameters[k];
+			let samples: Vec<f64> = (0..SAMPLES)
+				.map(|s| {
+					parameters[k] = origin + 2. * PI * s as f64 / SAMPLES as f64;
+					energy(ansatz, h, &parameters)
+				})
+				.collect();
+			// interpolate by the discrete Fourier transform
+			let coefficients: Vec<(f64, f64)> = (1..=SAMPLES / 2)
+				.map(|m| {
+					samples.iter().enumerate().fold((0., 0.), |(a, b), (s, e)| {
+						let phase = 2. * PI * (m * s) as f64 / SAMPLES as f64;
+						(a + e * phase.cos(), b + e * phase.sin())
+					})
+				})
+				.collect();
+			let f = |x: f64| -> f64 {
+				coefficients
+					.iter()
+					.enumerate()
+					.map(|(m, (a, b))| {
+						let phase = (m + 1) as f64 * x;
+						2. / SAMPLES as f64 * (a * phase.cos() + b * phase.sin())
+					})
+					.sum()
+			};
+			let argmin = |center: f64, width: f64| {
+				(0..=400)
+					.map(|i| center + width * (i as f64 / 200. - 1.))
+					.min_by(|a, b| f(*a).total_cmp(&f(*b)))
+					.unwrap()
+			};
+			let x = argmin(PI, PI);
+			let x = argmin(x, PI / 200.);
+			let x = argmin(x, PI / 20000.);
+			parameters[k] = origin + x;
+			best = energy(ansatz, h, &parameters);
+		}
+		if previous - best < 1e-10 {
+			break;
+		}
+	}
+	best
+}
+
+#[test]
+fn test_hartree_fock_circuit() {
+	let fcidump = Fcidump::parse(LIH).unwrap();
+	let h = fcidump.observable(FermionEncoding::JordanWigner);
+	let mut state = StateVec::new(6);
+	hartree_fock_circuit(fcidump.hartree_fock_occupation(), 6)
+		.update_quantum_state(state.as_mut())
+		.unwrap();
+	let energy = h.expectation_value(&state).unwrap().re;
+	assert!((energy - fcidump.hartree_fock_energy()).abs() < 1e-10);
+	assert!((energy + 7.862027).abs() < 1e-6);
+
+	let ansatz = CoupledClusterAnsatz::uccsd(0b11, 6);
+	let zeros = vec![0.; ansatz.parameter_count()];
+	assert_eq!(
+		ansatz.circuit(&zeros).gates()[..2],
+		hartree_fock_circuit(0b11, 6).gates()[..]
+	);
+}
+
+#[test]
+fn test_parameter_counts() {
+	// singles: 2 x 2, doubles: αβ -> αβ 4
+	assert_eq!(CoupledClusterAnsatz::uccsd(0b11, 6).parameter_count(), 8);
+	// H2: 2 singles and 1 double
+	assert_eq!(CoupledClusterAnsatz::uccsd(0b11, 4).parameter_count(), 3);
+	// singles: 2 x 2 x 2, doubles: αα 1, ββ 1 and αβ 4 x 4
+	assert_eq!(CoupledClusterAnsatz::uccsd(0b1111, 8).parameter_count(), 26);
+	// MS2 = 2 with α electrons in orbitals 0 and 1: singles 0 -> 4 and 2 -> 4
+	let ansatz = CoupledClusterAnsatz::uccsd(0b101, 6);
+	assert_eq!(ansatz.parameter_count(), 2);
+	assert_eq!(ansatz.electron_count(), 2);
+	assert_eq!(
+		ansatz.circuit(&[0., 0.]).gates()[..2],
+		[Gate::X(0), Gate::X(2)]
+	);
+	// 3 spatial orbitals: 3 singles and 3 paired doubles per layer
+	assert_eq!(
+		CoupledClusterAnsatz::k_upccgsd(2, 0b11, 6).parameter_count(),
+		12
+	);
+}
+
+#[test]
+fn test_uccsd_h2() {
+	let h = Fcidump::parse(H2)
+		.unwrap()
+		.observable(FermionEncoding::JordanWigner);
+	let exact = exact_ground_energy(&h, 2);
+	let ansatz = CoupledClusterAnsatz::uccsd(0b11, 4);
+	let energy = minimize(&ansatz, &h);
+	assert!((energy - exact).abs() < 1e-6, "{} != {}", energy, exact);
+	assert!(energy < E_HF - 0.02);
+}
+
+#[test]
+fn test_uccsd_lih() {
+	let fcidump = Fcidump::parse(LIH).unwrap();
+	let h = fcidump.observable(FermionEncoding::JordanWigner);
+	let exact = exact_ground_energy(&h, 2);
+	assert!(exact < fcidump.hartree_fock_energy() - 1e-3);
+	let energy = minimize(&CoupledClusterAnsatz::uccsd(0b11, 6), &h);
+	assert!((energy - exact).abs() < 1e-6, "{} != {}", energy, exact);
+}
+
+#[test]
+fn test_k_upccgsd_lih() {
+	let fcidump = Fcidump::parse(LIH).unwrap();
+	let h = fcidump.observable(FermionEncoding::JordanWigner);
+	let exact = exact_ground_energy(&h, 2);
+	let energy = minimize(&CoupledClusterAnsatz::k_upccgsd(2, 0b11, 6), &h);
+	assert!((energy - exact).abs() < 1e-6, "{} != {}", energy, exact);
+}
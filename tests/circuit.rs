@@ -0,0 +1,352 @@
+use num::{Complex, One, Zero};
+use qurs::circuit::{equivalent, Circuit, Gate};
+use std::f64::consts::PI;
+
+fn circuit_of(qubit_count: usize, gates: &[Gate]) -> Circuit {
+	let mut circuit = Circuit::new(qubit_count);
+	for gate in gates {
+		circuit.add_gate(gate.clone());
+	}
+	circuit
+}
+
+#[test]
+fn to_unitary() {
+	let cnot = circuit_of(2, &[Gate::Cnot(0, 1)]).to_unitary();
+	let (o, z) = (Complex::one(), Complex::zero());
+	#[rustfmt::skip]
+	let expected = [
+		o, z, z, z,
+		z, z, z, o,
+		z, z, o, z,
+		z, o, z, z,
+	];
+	assert_eq!(cnot, expected);
+
+	let h = circuit_of(1, &[Gate::H(0)]).to_unitary();
+	let s = std::f64::consts::FRAC_1_SQRT_2;
+	for (actual, expected) in h.iter().zip(&[s, s, s, -s]) {
+		assert!((actual - expected).norm() < 1e-12);
+	}
+}
+
+#[test]
+fn equivalent_small() {
+	let x = circuit_of(1, &[Gate::X(0)]);
+	let hzh = circuit_of(1, &[Gate::H(0), Gate::Z(0), Gate::H(0)]);
+	assert!(equivalent(&x, &hzh, false));
+
+	// Rz(π) = -iZ
+	let z = circuit_of(1, &[Gate::Z(0)]);
+	let rz = circuit_of(1, &[Gate::Rz(0, PI)]);
+	assert!(!equivalent(&z, &rz, false));
+	assert!(equivalent(&z, &rz, true));
+
+	// CZ differs from the identity by a relative phase only.
+	let cz = circuit_of(2, &[Gate::Cz(0, 1)]);
+	let identity = Circuit::new(2);
+	assert!(!equivalent(&cz, &identity, true));
+	assert!(!equivalent(&cz, &circuit_of(3, &[Gate::Cz(0, 1)]), true));
+}
+
+#[test]
+fn equivalent_large() {
+	let n = 12;
+	let swap = circuit_of(n, &[Gate::Swap(0, 11)]);
+	let cnots = circuit_of(
+		n,
+		&[Gate::Cnot(0, 11), Gate::Cnot(11, 0), Gate::Cnot(0, 11)],
+	);
+	assert!(equivalent(&swap, &cnots, false));
+	assert!(!equivalent(&swap, &Circuit::new(n), true));
+
+	let rx = circuit_of(n, &[Gate::Rx(5, 2. * PI)]);
+	assert!(!equivalent(&rx, &Circuit::new(n), false));
+	assert!(equivalent(&rx, &Circuit::new(n), true));
+	let cz = circuit_of(n, &[Gate::Cz(3, 7)]);
+	assert!(!equivalent(&cz, &Circuit::new(n), true));
+}
+
+#[test]
+fn controlled_gates() {
+	use qurs::gate::ControlValue::{One, Zero};
+
+	// controlled on |1> matches the built-in controlled gates
+	let cnot = circuit_of(2, &[Gate::X(1).controlled(&[(0, One)])]);
+	assert!(equivalent(
+		&cnot,
+		&circuit_of(2, &[Gate::Cnot(0, 1)]),
+		false
+	));
+	let toffoli = circuit_of(3, &[Gate::Cnot(1, 2).controlled(&[(0, One)])]);
+	assert!(equivalent(
+		&toffoli,
+		&circuit_of(3, &[Gate::Ccnot(0, 1, 2)]),
+		false
+	));
+
+	// controlled on |0> is controlled on |1> between X gates
+	let zero_controlled = circuit_of(3, &[Gate::Ry(2, 0.4).controlled(&[(0, Zero), (1, One)])]);
+	let expected = circuit_of(
+		3,
+		&[
+			Gate::X(0),
+			Gate::MultiControlled {
+				controls: vec![0, 1],
+				target: 2,
+				matrix: [
+					Complex::new((0.2f64).cos(), 0.),
+					Complex::new(-(0.2f64).sin(), 0.),
+					Complex::new((0.2f64).sin(), 0.),
+					Complex::new((0.2f64).cos(), 0.),
+				],
+			},
+			Gate::X(0),
+		],
+	);
+	assert!(equivalent(&zero_controlled, &expected, false));
+
+	// Fredkin gate swaps the targets only when the control is |1>
+	let fredkin = circuit_of(3, &[Gate::Swap(1, 2).controlled(&[(0, One)])]).to_unitary();
+	for j in 0..8 {
+		let i = if j & 1 == 1 {
+			(j & 1) | ((j >> 1) & 1) << 2 | ((j >> 2) & 1) << 1
+		} else {
+			j
+		};
+		assert_eq!(fredkin[i * 8 + j], Complex::one());
+	}
+}
+
+#[test]
+#[should_panic]
+fn controlled_gate_rejects_target_as_control() {
+	Gate::Cnot(0, 1).controlled(&[(1, qurs::gate::ControlValue::One)]);
+}
+
+fn all_gates() -> Vec<Gate> {
+	use qurs::gate::ControlValue;
+	let u = [
+		Complex::new(0.6, 0.),
+		Complex::new(0., 0.8),
+		Complex::new(0., 0.8),
+		Complex::new(0.6, 0.),
+	];
+	vec![
+		Gate::X(0),
+		Gate::Y(1),
+		Gate::Z(2),
+		Gate::H(0),
+		Gate::S(1),
+		Gate::Sdag(2),
+		Gate::T(0),
+		Gate::Tdag(1),
+		Gate::SqrtX(2),
+		Gate::SqrtXdag(0),
+		Gate::SqrtY(1),
+		Gate::SqrtYdag(2),
+		Gate::Cz(0, 1),
+		Gate::Cnot(2, 0),
+		Gate::Swap(1, 2),
+		Gate::Ccnot(0, 1, 2),
+		Gate::Ccz(2, 1, 0),
+		Gate::Rx(0, 0.3),
+		Gate::Ry(1, -0.7),
+		Gate::Rz(2, 1.9),
+		Gate::Rxx(0, 2, 0.4),
+		Gate::Ryy(1, 0, -1.3),
+		Gate::Rzz(2, 1, 0.8),
+		Gate::ISwap(0, 1),
+		Gate::SqrtISwap(1, 2),
+		Gate::FSim(2, 0, 0.5, -0.9),
+		Gate::Ecr(0, 2),
+		Gate::Givens(1, 0, 0.6),
+		Gate::Phase(2, -0.4),
+		Gate::U2(0, 0.2, 1.1),
+		Gate::U3(1, 0.9, -0.3, 2.2),
+		Gate::Cp(2, 0, 1.4),
+		Gate::MultiControlledPhase {
+			controls: vec![0, 1],
+			target: 2,
+			angle: 0.5,
+		},
+		Gate::MultiControlled {
+			controls: vec![2],
+			target: 0,
+			matrix: u,
+		},
+		Gate::DenseMatrix {
+			targets: vec![1],
+			matrix: u.to_vec(),
+		},
+		Gate::PauliRotation {
+			targets: vec![0, 1, 2],
+			paulis: vec![1, 2, 3],
+			angle: 0.7,
+		},
+		Gate::Swap(0, 2).controlled(&[(1, ControlValue::Zero)]),
+	]
+}
+
+#[test]
+fn dagger_is_inverse() {
+	for gate in all_gates() {
+		let circuit = circuit_of(3, &[gate.clone(), gate.dagger()]);
+		assert!(equivalent(&circuit, &Circuit::new(3), false), "{:?}", gate);
+		let expected: Vec<Complex<f64>> = {
+			let m = gate.matrix();
+			let dim = (m.len() as f64).sqrt() as usize;
+			(0..m.len())
+				.map(|k| m[(k % dim) * dim + k / dim].conj())
+				.collect()
+		};
+		for (a, b) in gate.dagger().matrix().iter().zip(&expected) {
+			assert!((a - b).norm() < 1e-12, "{:?}", gate);
+		}
+	}
+	assert_eq!(Gate::P0(0).dagger(), Gate::P0(0));
+
+	let circuit = circuit_of(3, &all_gates());
+	let mut round_trip = circuit.clone();
+	round_trip.merge_circuit(&circuit.inverse());
+	assert!(equivalent(&round_trip, &Circuit::new(3), false));
+}
+
+#[test]
+fn gate_powers() {
+	for gate in all_gates() {
+		let gate_of = |exponent: f64| gate.pow(exponent).unwrap();
+		assert!(
+			equivalent(
+				&circuit_of(3, &[gate_of(1.)]),
+				&circuit_of(3, std::slice::from_ref(&gate)),
+				false
+			),
+			"{:?}",
+			gate
+		);
+		assert!(
+			equivalent(
+				&circuit_of(3, &[gate_of(-1.)]),
+				&circuit_of(3, &[gate.dagger()]),
+				false
+			),
+			"{:?}",
+			gate
+		);
+		assert!(
+			equivalent(
+				&circuit_of(3, &[gate_of(3.)]),
+				&circuit_of(3, &[gate.clone(), gate.clone(), gate.clone()]),
+				false
+			),
+			"{:?}",
+			gate
+		);
+		if let Some(root) = gate.pow(0.5) {
+			assert!(
+				equivalent(
+					&circuit_of(3, &[root.clone(), root]),
+					&circuit_of(3, std::slice::from_ref(&gate)),
+					false
+				),
+				"{:?}",
+				gate
+			);
+		}
+	}
+	assert!(equivalent(
+		&circuit_of(1, &[Gate::X(0).pow(0.5).unwrap()]),
+		&circuit_of(1, &[Gate::SqrtX(0)]),
+		false
+	));
+	assert!(Gate::Swap(0, 1).pow(0.5).is_none());
+	assert!(Gate::P1(0).pow(-1.).is_none());
+	for projection in [Gate::P0(0), Gate::P1(0)] {
+		assert_eq!(projection.pow(3.), Some(projection.clone()));
+		assert!(equivalent(
+			&circuit_of(1, &[projection.pow(0.).unwrap()]),
+			&Circuit::new(1),
+			false
+		));
+	}
+}
+
+#[test]
+fn powers_of_diagonal_gates() {
+	use qurs::gate::ControlValue;
+	let controlled_rz = Gate::Rz(1, 1.).controlled(&[(0, ControlValue::One)]);
+	let diagonal = Gate::DenseMatrix {
+		targets: vec![0],
+		matrix: vec![
+			Complex::from_polar(1., 2.8),
+			Complex::zero(),
+			Complex::zero(),
+			Complex::one(),
+		],
+	};
+	let rz = Gate::DenseMatrix {
+		targets: vec![0],
+		matrix: Gate::Rz(0, 1.).matrix(),
+	};
+	for gate in &[controlled_rz, diagonal, rz] {
+		let qubit_count = gate.qubits().len();
+		let square = gate.pow(2.).unwrap();
+		assert!(
+			equivalent(
+				&circuit_of(qubit_count, &[square]),
+				&circuit_of(qubit_count, &[gate.clone(), gate.clone()]),
+				false
+			),
+			"{:?}",
+			gate
+		);
+		let root = gate.pow(0.5).unwrap();
+		assert!(root.matrix().iter().all(|m| m.is_finite()), "{:?}", gate);
+		assert!(
+			equivalent(
+				&circuit_of(qubit_count, &[root.clone(), root]),
+				&circuit_of(qubit_count, std::slice::from_ref(gate)),
+				false
+			),
+			"{:?}",
+			gate
+		);
+	}
+	// the principal square root of Rz(1) is Rz(1/2)
+	let root = Gate::DenseMatrix {
+		targets: vec![0],
+		matrix: Gate::Rz(0, 1.).matrix(),
+	}
+	.pow(0.5)
+	.unwrap();
+	assert!(equivalent(
+		&circuit_of(1, &[root]),
+		&circuit_of(1, &[Gate::Rz(0, 0.5)]),
+		false
+	));
+}
+
+#[test]
+#[should_panic]
+fn add_gate_rejects_short_pauli_list() {
+	let mut circuit = Circuit::new(3);
+	circuit.add_gate(Gate::PauliRotation {
+		targets: vec![0, 1, 2],
+		paulis: vec![1, 3],
+		angle: 0.5,
+	});
+}
+
+#[test]
+#[should_panic]
+fn pauli_rotation_rejects_invalid_pauli_type() {
+	let mut state = vec![Complex::zero(); 4];
+	state[0] = Complex::one();
+	Gate::PauliRotation {
+		targets: vec![0],
+		paulis: vec![4],
+		angle: 0.5,
+	}
+	.update_quantum_state(&mut state);
+}
@@ -0,0 +1,88 @@
+//! Helpers shared by the integration tests. Each test crate uses only some
+//! of them.
+#![allow(dead_code)]
+
+use qurs::observable::Observable;
+use qurs::Complex;
+use rand::Rng;
+
+/// Haar random `dim`×`dim` unitary in row-major order.
+pub fn haar_random_unitary(dim: usize, rng: &mut impl Rng) -> Vec<Complex<f64>> {
+	// Gram-Schmidt on a complex Gaussian matrix
+	let mut gaussian = || {
+		let (u, v): (f64, f64) = (rng.gen_range(1e-12..1.), rng.gen_range(0.0..1.));
+		let r = (-2. * u.ln()).sqrt();
+		let angle = 2. * std::f64::consts::PI * v;
+		r * angle.cos()
+	};
+	let mut columns: Vec<Vec<Complex<f64>>> = Vec::new();
+	for _ in 0..dim {
+		let mut column: Vec<Complex<f64>> = (0..dim)
+			.map(|_| Complex::new(gaussian(), gaussian()))
+			.collect();
+		for previous in &columns {
+			let overlap: Complex<f64> = previous
+				.iter()
+				.zip(&column)
+				.map(|(p, c)| p.conj() * c)
+				.sum();
+			for (c, p) in column.iter_mut().zip(previous) {
+				*c -= overlap * p;
+			}
+		}
+		let norm = column.iter().map(|c| c.norm_sqr()).sum::<f64>().sqrt();
+		columns.push(column.iter().map(|c| c / norm).collect());
+	}
+	(0..dim * dim).map(|k| columns[k % dim][k / dim]).collect()
+}
+
+/// Lowest eigenvalue of the real symmetric matrix by the Jacobi method.
+fn lowest_eigenvalue(mut a: Vec<f64>, n: usize) -> f64 {
+	for _ in 0..100 {
+		let off: f64 = (0..n * n)
+			.filter(|k| k / n != k % n)
+			.map(|k| a[k].powi(2))
+			.sum();
+		if off < 1e-24 {
+			break;
+		}
+		for p in 0..n {
+			for q in p + 1..n {
+				if a[p * n + q].abs() < 1e-300 {
+					continue;
+				}
+				let theta = (a[q * n + q] - a[p * n + p]) / (2. * a[p * n + q]);
+				let t = theta.signum() / (theta.abs() + (theta * theta + 1.).sqrt());
+				let (c, s) = (1. / (t * t + 1.).sqrt(), t / (t * t + 1.).sqrt());
+				for k in 0..n {
+					let (kp, kq) = (a[k * n + p], a[k * n + q]);
+					a[k * n + p] = c * kp - s * kq;
+					a[k * n + q] = s * kp + c * kq;
+				}
+				for k in 0..n {
+					let (pk, qk) = (a[p * n + k], a[q * n + k]);
+					a[p * n + k] = c * pk - s * qk;
+					a[q * n + k] = s * pk + c * qk;
+				}
+			}
+		}
+	}
+	(0..n).map(|i| a[i * n + i]).fold(f64::INFINITY, f64::min)
+}
+
+/// Lowest eigenvalue of `h` restricted to the basis states satisfying
+/// `sector`.
+pub fn ground_energy<F: Fn(usize) -> bool>(h: &Observable, sector: F) -> f64 {
+	let dim = 1 << h.qubit_count();
+	let basis: Vec<usize> = (0..dim).filter(|&i| sector(i)).collect();
+	let m = h.matrix();
+	let n = basis.len();
+	let mut a = vec![0.; n * n];
+	for (i, &r) in basis.iter().enumerate() {
+		for (j, &c) in basis.iter().enumerate() {
+			assert!(m[r * dim + c].im.abs() < 1e-12);
+			a[i * n + j] = m[r * dim + c].re;
+		}
+	}
+	lowest_eigenvalue(a, n)
+}
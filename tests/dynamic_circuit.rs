@@ -0,0 +1,150 @@
+use num::Complex;
+use qurs::circuit::Gate;
+use qurs::dynamic_circuit::{register_value, Condition, DynamicCircuit};
+use qurs::prelude::*;
+use qurs::{self, drop_qubit, inner_product, StateVec};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const EPS: f64 = 1e-10;
+
+#[test]
+fn teleportation() {
+	let (theta, phi) = (0.7, 1.9);
+	let mut expected = StateVec::new(1);
+	Gate::Ry(0, theta).update_quantum_state(expected.as_mut());
+	Gate::Rz(0, phi).update_quantum_state(expected.as_mut());
+
+	let mut circuit = DynamicCircuit::new(3, 2);
+	circuit.add_gate(Gate::Ry(0, theta));
+	circuit.add_gate(Gate::Rz(0, phi));
+	circuit.add_gate(Gate::H(1));
+	circuit.add_gate(Gate::Cnot(1, 2));
+	circuit.add_gate(Gate::Cnot(0, 1));
+	circuit.add_gate(Gate::H(0));
+	circuit.add_measure(0, 0);
+	circuit.add_measure(1, 1);
+	circuit.add_conditional_gate(
+		Condition::Bit {
+			bit: 1,
+			value: true,
+		},
+		Gate::X(2),
+	);
+	circuit.add_conditional_gate(
+		Condition::Bit {
+			bit: 0,
+			value: true,
+		},
+		Gate::Z(2),
+	);
+
+	for _ in 0..20 {
+		let mut state = StateVec::new(3);
+		let register = circuit.update_quantum_state(state.as_mut()).unwrap();
+		assert!((state.get_squared_norm() - 1.).abs() < EPS);
+		let teleported =
+			drop_qubit(&state, &[0, 1], &[register[0] as u32, register[1] as u32]).unwrap();
+		let fidelity = inner_product(expected.as_ref(), teleported.as_ref())
+			.unwrap()
+			.norm_sqr();
+		assert!((fidelity - 1.).abs() < EPS);
+	}
+}
+
+#[test]
+fn register_condition() {
+	let mut circuit = DynamicCircuit::new(3, 2);
+	circuit.add_gate(Gate::X(0));
+	circuit.add_gate(Gate::X(1));
+	circuit.add_measure(0, 0);
+	circuit.add_measure(1, 1);
+	circuit.add_conditional_gate(Condition::Register(1), Gate::X(2));
+	circuit.add_conditional_gate(Condition::Register(3), Gate::H(2));
+
+	let mut state = StateVec::new(3);
+	let register = circuit.update_quantum_state(state.as_mut()).unwrap();
+	assert_eq!(register, [true, true]);
+	assert_eq!(register_value(&register), 3);
+	assert!((state.get_zero_probability(2).unwrap() - 0.5).abs() < EPS);
+}
+
+#[test]
+fn reset() {
+	let mut circuit = DynamicCircuit::new(2, 1);
+	circuit.add_gate(Gate::H(0));
+	circuit.add_gate(Gate::Cnot(0, 1));
+	circuit.add_reset(0);
+	circuit.add_measure(1, 0);
+	for _ in 0..10 {
+		let mut state = StateVec::new(2);
+		let register = circuit.update_quantum_state(state.as_mut()).unwrap();
+		assert!((state.get_zero_probability(0).unwrap() - 1.).abs() < EPS);
+		let expected = if register[0] { 0b10 } else { 0b00 };
+		assert!((state.as_ref()[expected] - Complex::new(1., 0.)).norm() < EPS);
+	}
+}
+
+#[test]
+fn seeded_runs_are_reproducible() {
+	let mut circuit = DynamicCircuit::new(4, 4);
+	for q in 0..4 {
+		circuit.add_gate(Gate::H(q));
+		circuit.add_measure(q, q as usize);
+	}
+	let run = |seed: u64| {
+		let mut rng = StdRng::seed_from_u64(seed);
+		(0..8)
+			.map(|_| {
+				let mut state = StateVec::new(4);
+				let register = circuit
+					.update_quantum_state_with_rng(state.as_mut(), &mut rng)
+					.unwrap();
+				register_value(&register)
+			})
+			.collect::<Vec<_>>()
+	};
+	assert_eq!(run(7), run(7));
+	assert_ne!(run(7), run(8));
+}
+
+#[test]
+fn outcomes_of_negligible_probability_are_never_chosen() {
+	// the outcome 1 has a probability of about 1e-26
+	let mut circuit = DynamicCircuit::new(1, 1);
+	circuit.add_gate(Gate::Ry(0, 2e-13));
+	circuit.add_measure(0, 0);
+	for _ in 0..100 {
+		let mut state = StateVec::new(1);
+		assert_eq!(
+			circuit.update_quantum_state(state.as_mut()).unwrap(),
+			[false]
+		);
+		assert!((state.get_squared_norm() - 1.).abs() < EPS);
+	}
+}
+
+#[test]
+#[should_panic]
+fn register_condition_rejects_wide_register() {
+	let mut circuit = DynamicCircuit::new(1, 65);
+	circuit.add_conditional_gate(Condition::Register(1), Gate::X(0));
+}
+
+#[test]
+fn wide_register_with_bit_conditions() {
+	let mut circuit = DynamicCircuit::new(1, 65);
+	circuit.add_gate(Gate::X(0));
+	circuit.add_measure(0, 64);
+	circuit.add_conditional_gate(
+		Condition::Bit {
+			bit: 64,
+			value: true,
+		},
+		Gate::X(0),
+	);
+	let mut state = StateVec::new(1);
+	let register = circuit.update_quantum_state(state.as_mut()).unwrap();
+	assert!(register[64]);
+	assert!((state.get_zero_probability(0).unwrap() - 1.).abs() < EPS);
+}
@@ -0,0 +1,186 @@
+use qurs::fermion::{FermionEncoding, FermionOperator, LadderOperator::*};
+use qurs::observable::Observable;
+use qurs::prelude::*;
+use qurs::{Complex, StateVec};
+use rand::{Rng, SeedableRng};
+
+const ENCODINGS: [FermionEncoding; 3] = [
+	FermionEncoding::JordanWigner,
+	FermionEncoding::Parity,
+	FermionEncoding::BravyiKitaev,
+];
+
+fn c(re: f64) -> Complex<f64> {
+	Complex::new(re, 0.)
+}
+
+fn assert_matrix_eq(a: &[Complex<f64>], b: &[Complex<f64>]) {
+	for (x, y) in a.iter().zip(b) {
+		assert!((x - y).norm() < 1e-10, "{} != {}", x, y);
+	}
+}
+
+fn matmul(a: &[Complex<f64>], b: &[Complex<f64>], dim: usize) -> Vec<Complex<f64>> {
+	let mut m = vec![c(0.); dim * dim];
+	for i in 0..dim {
+		for k in 0..dim {
+			for j in 0..dim {
+				m[i * dim + j] += a[i * dim + k] * b[k * dim + j];
+			}
+		}
+	}
+	m
+}
+
+/// tr(H^k) for k = 1..=dim, which determine the spectrum of H.
+fn power_traces(observable: &Observable) -> Vec<f64> {
+	let dim = 1 << observable.qubit_count();
+	let h = observable.matrix();
+	let mut power = h.clone();
+	let mut traces = Vec::new();
+	for _ in 0..dim {
+		traces.push((0..dim).map(|i| power[i * dim + i].re).sum());
+		power = matmul(&power, &h, dim);
+	}
+	traces
+}
+
+fn random_hamiltonian(mode_count: usize, seed: u64) -> FermionOperator {
+	let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+	let mut h = FermionOperator::new();
+	for p in 0..mode_count {
+		for q in 0..mode_count {
+			let t = Complex::new(rng.gen_range(-0.3..0.3), rng.gen_range(-0.3..0.3));
+			h.add_term(t, &[Create(p), Annihilate(q)]);
+			h.add_term(t.conj(), &[Create(q), Annihilate(p)]);
+		}
+	}
+	for _ in 0..6 {
+		let modes: Vec<usize> = (0..4).map(|_| rng.gen_range(0..mode_count)).collect();
+		let v = c(rng.gen_range(-0.3..0.3));
+		let term = FermionOperator::term(
+			v,
+			&[
+				Create(modes[0]),
+				Create(modes[1]),
+				Annihilate(modes[2]),
+				Annihilate(modes[3]),
+			],
+		);
+		h = h + term.clone() + term.dagger();
+	}
+	h
+}
+
+#[test]
+fn test_normal_ordered() {
+	let op = FermionOperator::term(c(2.), &[Annihilate(1), Create(0), Create(1)]);
+	let ordered = op.normal_ordered();
+	// a_1 a†_0 a†_1 = -a†_0 - a†_1 a†_0 a_1
+	assert_eq!(ordered.terms().len(), 2);
+	assert!(ordered
+		.terms()
+		.iter()
+		.any(|(k, ops)| (k - c(-2.)).norm() < 1e-12 && ops == &[Create(0)]));
+	assert!(ordered.terms().iter().any(
+		|(k, ops)| (k - c(-2.)).norm() < 1e-12 && ops == &[Create(1), Create(0), Annihilate(1)]
+	));
+
+	assert!(FermionOperator::term(c(1.), &[Create(2), Create(2)])
+		.normal_ordered()
+		.terms()
+		.is_empty());
+
+	let h = random_hamiltonian(3, 4);
+	for &encoding in &ENCODINGS {
+		assert_matrix_eq(
+			&h.encode(encoding, 3).matrix(),
+			&h.normal_ordered().encode(encoding, 3).matrix(),
+		);
+	}
+}
+
+#[test]
+fn test_dagger() {
+	let op = FermionOperator::term(Complex::new(1., 2.), &[Create(0), Annihilate(1)]);
+	let dagger = op.dagger();
+	assert_eq!(
+		dagger.terms()[0],
+		(Complex::new(1., -2.), vec![Create(1), Annihilate(0)])
+	);
+	assert!(!op.is_hermitian(1e-12));
+	assert!((op.clone() + dagger).is_hermitian(1e-12));
+	assert!(random_hamiltonian(4, 1).is_hermitian(1e-12));
+}
+
+#[test]
+fn test_anticommutation_relations() {
+	let n = 5;
+	for &encoding in &ENCODINGS {
+		for p in 0..n {
+			for q in 0..n {
+				let a = |op| FermionOperator::term(c(1.), &[op]).encode(encoding, n);
+				let mut anticommutator =
+					a(Annihilate(p)) * a(Create(q)) + a(Create(q)) * a(Annihilate(p));
+				anticommutator.simplify(1e-12);
+				let expected = if p == q {
+					Observable::identity(n, c(1.))
+				} else {
+					Observable::new(n)
+				};
+				assert_eq!(anticommutator, expected, "{:?} {} {}", encoding, p, q);
+
+				let mut anticommutator =
+					a(Annihilate(p)) * a(Annihilate(q)) + a(Annihilate(q)) * a(Annihilate(p));
+				anticommutator.simplify(1e-12);
+				assert_eq!(anticommutator, Observable::new(n));
+			}
+		}
+	}
+}
+
+#[test]
+fn test_occupation() {
+	// occupations of 4 modes and their qubit representations
+	let cases = [
+		(0b1011usize, FermionEncoding::JordanWigner, 0b1011usize),
+		(0b1011, FermionEncoding::Parity, 0b1001),
+		(0b1011, FermionEncoding::BravyiKitaev, 0b1001),
+		(0b1101, FermionEncoding::BravyiKitaev, 0b1111),
+		(0b0110, FermionEncoding::BravyiKitaev, 0b0110),
+	];
+	for &(occupation, encoding, qubits) in &cases {
+		let mut state = StateVec::new(4);
+		state.set_computational_basis(qubits);
+		for j in 0..4 {
+			let number =
+				FermionOperator::term(c(1.), &[Create(j), Annihilate(j)]).encode(encoding, 4);
+			let expected = ((occupation >> j) & 1) as f64;
+			let actual = number.expectation_value(&state).unwrap();
+			assert!(
+				(actual - c(expected)).norm() < 1e-12,
+				"{:?} mode {}: {}",
+				encoding,
+				j,
+				actual
+			);
+		}
+	}
+}
+
+#[test]
+fn test_spectra_agree_across_encodings() {
+	for &(n, seed) in &[(3, 2), (4, 3), (5, 5)] {
+		let h = random_hamiltonian(n, seed);
+		let observables: Vec<Observable> = ENCODINGS.iter().map(|&e| h.encode(e, n)).collect();
+		for o in &observables {
+			assert!(o.is_hermitian(1e-12));
+		}
+		let expected = power_traces(&observables[0]);
+		for o in &observables[1..] {
+			for (a, b) in power_traces(o).iter().zip(&expected) {
+				assert!((a - b).abs() <= 1e-9 * b.abs().max(1.), "{} != {}", a, b);
+			}
+		}
+	}
+}
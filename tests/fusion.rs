@@ -0,0 +1,63 @@
+use qurs::circuit::{Circuit, Gate};
+use qurs::fusion::FusedCircuit;
+use qurs::prelude::*;
+use qurs::{self, inner_product, StateVec};
+use rand::{Rng, SeedableRng};
+
+fn random_circuit(qubit_count: u32, gate_count: usize, seed: u64) -> Circuit {
+	let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+	let mut circuit = Circuit::new(qubit_count as usize);
+	for _ in 0..gate_count {
+		let a = rng.gen_range(0..qubit_count);
+		let b = (a + rng.gen_range(1..qubit_count)) % qubit_count;
+		let angle = rng.gen_range(-3.0..3.0);
+		let gate = match rng.gen_range(0..7) {
+			0 => Gate::H(a),
+			1 => Gate::T(a),
+			2 => Gate::Rx(a, angle),
+			3 => Gate::Rz(a, angle),
+			4 => Gate::Cnot(a, b),
+			5 => Gate::Cz(a, b),
+			_ => Gate::PauliRotation {
+				targets: vec![a, b],
+				paulis: vec![1, 2],
+				angle,
+			},
+		};
+		circuit.add_gate(gate);
+	}
+	circuit
+}
+
+#[test]
+fn fused_circuit_matches_gate_by_gate() {
+	let circuit = random_circuit(6, 200, 1);
+	for max_block_qubits in 1..=5 {
+		let fused = FusedCircuit::new(&circuit, max_block_qubits);
+		assert!(fused.block_count() <= circuit.gate_count());
+		for block in fused.blocks() {
+			assert!(block.qubits().len() <= max_block_qubits.max(2));
+		}
+
+		let mut expected = StateVec::new(6);
+		expected.set_haar_random_state_with_seed(2);
+		let mut actual = StateVec::new(6);
+		actual.as_mut().copy_from_slice(expected.as_ref());
+		circuit.update_quantum_state(expected.as_mut()).unwrap();
+		fused.update_quantum_state(actual.as_mut()).unwrap();
+		let overlap = inner_product(expected.as_ref(), actual.as_ref()).unwrap();
+		assert!((overlap.re - 1.).abs() < 1e-10 && overlap.im.abs() < 1e-10);
+	}
+	assert!(FusedCircuit::new(&circuit, 4).block_count() < circuit.gate_count() / 2);
+}
+
+#[test]
+fn fused_circuit_rejects_wrong_state() {
+	let fused = FusedCircuit::new(&random_circuit(3, 10, 3), 2);
+	let mut state = StateVec::new(4);
+	// the expected length comes first
+	assert!(matches!(
+		fused.update_quantum_state(state.as_mut()),
+		Err(StateErr::InconsistentStateLength(8, 16))
+	));
+}
@@ -33,3 +33,241 @@ fn test_ccnot_gate() {
 		);
 	});
 }
+
+type Matrix = Vec<Complex<f64>>;
+
+/// Matrix of `gate` on 2 qubits, applied to each basis state.
+fn two_qubit_matrix<F: Fn(&mut [Complex<f64>])>(gate: F) -> Matrix {
+	let mut matrix = vec![Complex::zero(); 16];
+	for j in 0..4 {
+		let mut state = [Complex::zero(); 4];
+		state.set_computational_basis(j);
+		gate(&mut state);
+		for i in 0..4 {
+			matrix[i * 4 + j] = state[i];
+		}
+	}
+	matrix
+}
+
+/// `a ⊗ b` where `b` acts on the less significant qubit.
+fn kron(a: &[Complex<f64>], b: &[Complex<f64>]) -> Matrix {
+	(0..16)
+		.map(|k| {
+			let (i, j) = (k / 4, k % 4);
+			a[(i / 2) * 2 + j / 2] * b[(i % 2) * 2 + j % 2]
+		})
+		.collect()
+}
+
+fn pauli(p: usize) -> Matrix {
+	let (o, l, i) = (Complex::zero(), Complex::new(1., 0.), Complex::new(0., 1.));
+	match p {
+		0 => vec![l, o, o, l],
+		1 => vec![o, l, l, o],
+		2 => vec![o, -i, i, o],
+		_ => vec![l, o, o, -l],
+	}
+}
+
+/// exp(-i(θ/2)P) for P with P^2 = I.
+fn rotation(p: &[Complex<f64>], angle: f64) -> Matrix {
+	(0..16)
+		.map(|k| {
+			let identity = if k % 5 == 0 { 1. } else { 0. };
+			Complex::new((angle / 2.).cos() * identity, 0.)
+				- Complex::new(0., (angle / 2.).sin()) * p[k]
+		})
+		.collect()
+}
+
+fn assert_matrix_eq(actual: &[Complex<f64>], expected: &[Complex<f64>]) {
+	for (a, e) in actual.iter().zip(expected) {
+		assert!((a - e).norm() < 1e-12, "{:?} != {:?}", actual, expected);
+	}
+}
+
+#[test]
+fn test_two_qubit_rotation_gates() {
+	use qurs::gate::{rxx_gate, ryy_gate, rzz_gate};
+	let angle = 0.7;
+	let xx = kron(&pauli(1), &pauli(1));
+	let yy = kron(&pauli(2), &pauli(2));
+	let zz = kron(&pauli(3), &pauli(3));
+	assert_matrix_eq(
+		&two_qubit_matrix(|s| rxx_gate(0, 1, angle, s)),
+		&rotation(&xx, angle),
+	);
+	assert_matrix_eq(
+		&two_qubit_matrix(|s| ryy_gate(0, 1, angle, s)),
+		&rotation(&yy, angle),
+	);
+	assert_matrix_eq(
+		&two_qubit_matrix(|s| rzz_gate(1, 0, angle, s)),
+		&rotation(&zz, angle),
+	);
+}
+
+#[test]
+fn test_swap_like_gates() {
+	use qurs::gate::{fsim_gate, givens_gate, iswap_gate, sqrt_iswap_gate};
+	let (o, l, i) = (Complex::zero(), Complex::new(1., 0.), Complex::new(0., 1.));
+	#[rustfmt::skip]
+	let iswap = [
+		l, o, o, o,
+		o, o, i, o,
+		o, i, o, o,
+		o, o, o, l,
+	];
+	assert_matrix_eq(&two_qubit_matrix(|s| iswap_gate(0, 1, s)), &iswap);
+
+	let h = std::f64::consts::FRAC_1_SQRT_2;
+	let (c, s) = (Complex::new(h, 0.), Complex::new(0., h));
+	#[rustfmt::skip]
+	let sqrt_iswap = [
+		l, o, o, o,
+		o, c, s, o,
+		o, s, c, o,
+		o, o, o, l,
+	];
+	let actual = two_qubit_matrix(|s| sqrt_iswap_gate(0, 1, s));
+	assert_matrix_eq(&actual, &sqrt_iswap);
+
+	let (theta, phi): (f64, f64) = (0.3, 1.1);
+	let (c, s) = (
+		Complex::new(theta.cos(), 0.),
+		Complex::new(0., -theta.sin()),
+	);
+	#[rustfmt::skip]
+	let fsim = [
+		l, o, o, o,
+		o, c, s, o,
+		o, s, c, o,
+		o, o, o, Complex::from_polar(1., -phi),
+	];
+	assert_matrix_eq(&two_qubit_matrix(|s| fsim_gate(0, 1, theta, phi, s)), &fsim);
+
+	// exp(-i(θ/4)(Y⊗X - X⊗Y)) with X⊗Y and Y⊗X commuting
+	let angle = 0.9;
+	let expected: Matrix = {
+		let yx = rotation(&kron(&pauli(2), &pauli(1)), angle / 2.);
+		let xy = rotation(&kron(&pauli(1), &pauli(2)), -angle / 2.);
+		(0..16)
+			.map(|k| {
+				(0..4)
+					.map(|m| yx[(k / 4) * 4 + m] * xy[m * 4 + k % 4])
+					.sum()
+			})
+			.collect()
+	};
+	assert_matrix_eq(
+		&two_qubit_matrix(|s| givens_gate(0, 1, angle, s)),
+		&expected,
+	);
+}
+
+#[test]
+fn test_ecr_gate() {
+	use qurs::gate::ecr_gate;
+	let ix = kron(&pauli(0), &pauli(1));
+	let xy = kron(&pauli(1), &pauli(2));
+	let h = std::f64::consts::FRAC_1_SQRT_2;
+	let expected: Matrix = (0..16).map(|k| (ix[k] - xy[k]) * h).collect();
+	assert_matrix_eq(&two_qubit_matrix(|s| ecr_gate(0, 1, s)), &expected);
+}
+
+fn single_qubit_matrix<F: Fn(&mut [Complex<f64>])>(gate: F) -> Matrix {
+	let mut matrix = vec![Complex::zero(); 4];
+	for j in 0..2 {
+		let mut state = [Complex::zero(); 2];
+		state.set_computational_basis(j);
+		gate(&mut state);
+		for i in 0..2 {
+			matrix[i * 2 + j] = state[i];
+		}
+	}
+	matrix
+}
+
+#[test]
+fn test_u_gates() {
+	use qurs::gate::{phase_gate, ry_gate, rz_gate, u1_gate, u2_gate, u3_gate};
+	let (theta, phi, lambda): (f64, f64, f64) = (0.4, -1.2, 2.3);
+	let (o, l) = (Complex::zero(), Complex::new(1., 0.));
+	let phase = vec![l, o, o, Complex::from_polar(1., lambda)];
+	assert_matrix_eq(&single_qubit_matrix(|s| phase_gate(0, lambda, s)), &phase);
+	assert_matrix_eq(&single_qubit_matrix(|s| u1_gate(0, lambda, s)), &phase);
+
+	let u3 = |theta: f64| {
+		let (c, s) = ((theta / 2.).cos(), (theta / 2.).sin());
+		vec![
+			Complex::new(c, 0.),
+			-Complex::from_polar(s, lambda),
+			Complex::from_polar(s, phi),
+			Complex::from_polar(c, phi + lambda),
+		]
+	};
+	let actual = single_qubit_matrix(|s| u3_gate(0, theta, phi, lambda, s));
+	assert_matrix_eq(&actual, &u3(theta));
+	let actual = single_qubit_matrix(|s| u2_gate(0, phi, lambda, s));
+	assert_matrix_eq(&actual, &u3(std::f64::consts::FRAC_PI_2));
+
+	// U(θ, φ, λ) = e^{i(φ+λ)/2} Rz(φ) Ry(θ) Rz(λ)
+	let rotations = single_qubit_matrix(|s| {
+		rz_gate(0, lambda, s);
+		ry_gate(0, theta, s);
+		rz_gate(0, phi, s);
+	});
+	let global_phase = Complex::from_polar(1., (phi + lambda) / 2.);
+	let expected: Matrix = rotations.iter().map(|x| x * global_phase).collect();
+	assert_matrix_eq(&u3(theta), &expected);
+}
+
+#[test]
+fn test_controlled_phase_gates() {
+	use qurs::gate::{cp_gate, multi_control_phase_gate};
+	let lambda = 0.9;
+	let (o, l) = (Complex::zero(), Complex::new(1., 0.));
+	#[rustfmt::skip]
+	let cp = [
+		l, o, o, o,
+		o, l, o, o,
+		o, o, l, o,
+		o, o, o, Complex::from_polar(1., lambda),
+	];
+	assert_matrix_eq(&two_qubit_matrix(|s| cp_gate(0, 1, lambda, s)), &cp);
+	assert_matrix_eq(&two_qubit_matrix(|s| cp_gate(1, 0, lambda, s)), &cp);
+
+	for basis in 0..16 {
+		let mut state = [Complex::zero(); 16];
+		state.set_computational_basis(basis);
+		multi_control_phase_gate(&[0, 3, 1], 2, lambda, &mut state);
+		let expected = if basis == 15 {
+			Complex::from_polar(1., lambda)
+		} else {
+			l
+		};
+		assert!((state[basis] - expected).norm() < 1e-12);
+	}
+}
+
+#[test]
+fn test_multi_control_dense_matrix_gate() {
+	use qurs::gate::{multi_control_dense_matrix_gate, swap_gate, ControlValue};
+	// swap qubits 0 and 2 when qubit 1 is |0> and qubit 3 is |1>
+	let swap = two_qubit_matrix(|s| swap_gate(0, 1, s));
+	let controls = [(1, ControlValue::Zero), (3, ControlValue::One)];
+	for basis in 0..16 {
+		let mut state = [Complex::zero(); 16];
+		state.set_computational_basis(basis);
+		multi_control_dense_matrix_gate(&controls, &[0, 2], &swap, &mut state);
+		let active = basis & 0b1010 == 0b1000;
+		let (b0, b2) = (basis & 1, (basis >> 2) & 1);
+		let expected = if active {
+			(basis & 0b1010) | b2 | (b0 << 2)
+		} else {
+			basis
+		};
+		assert!((state[expected] - Complex::new(1., 0.)).norm() < 1e-12);
+	}
+}
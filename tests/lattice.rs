@@ -0,0 +1,134 @@
+use qurs::lattice::{
+	fermi_hubbard, heisenberg, ising, xxz, Boundary, Coefficients, Lattice, LatticeErr,
+};
+
+mod common;
+use common::ground_energy;
+
+fn assert_close(a: f64, b: f64) {
+	assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+}
+
+#[test]
+fn test_lattice_builders() {
+	assert_eq!(
+		Lattice::chain(4, Boundary::Open).edges(),
+		&[(0, 1), (1, 2), (2, 3)]
+	);
+	assert_eq!(
+		Lattice::chain(4, Boundary::Periodic).edges(),
+		&[(0, 1), (1, 2), (2, 3), (3, 0)]
+	);
+	// no duplicated edge for a periodic chain of 2 sites
+	assert_eq!(Lattice::chain(2, Boundary::Periodic).edges(), &[(0, 1)]);
+	assert_eq!(
+		Lattice::ladder(2, Boundary::Open).edges(),
+		&[(0, 1), (2, 3), (0, 2), (1, 3)]
+	);
+	assert_eq!(Lattice::ladder(4, Boundary::Periodic).edges().len(), 12);
+	let square = Lattice::square(3, 2, Boundary::Open);
+	assert_eq!(square.site_count(), 6);
+	assert_eq!(square.edges().len(), 7);
+	assert_eq!(Lattice::square(3, 3, Boundary::Periodic).edges().len(), 18);
+	assert!(matches!(
+		Lattice::new(3, &[(0, 1), (1, 0)]),
+		Err(LatticeErr::InvalidEdge(1, 0))
+	));
+	assert!(matches!(
+		Lattice::new(2, &[(0, 2)]),
+		Err(LatticeErr::InvalidEdge(0, 2))
+	));
+}
+
+#[test]
+fn test_ising_periodic_chain() {
+	// E_0 = -Σ_k sqrt(J^2 + h^2 + 2Jh cos k) with k = (2m + 1)π/N
+	let n = 6;
+	for &(j, h) in &[(1., 0.5), (1., 1.), (0.7, 1.3)] {
+		let exact: f64 = -(0..n)
+			.map(|m| {
+				let k = (2 * m + 1) as f64 * std::f64::consts::PI / n as f64;
+				(j * j + h * h + 2. * j * h * k.cos()).sqrt()
+			})
+			.sum::<f64>();
+		let observable = ising(&Lattice::chain(n, Boundary::Periodic), j, h, 0.);
+		assert_close(ground_energy(&observable, |_| true), exact);
+	}
+}
+
+#[test]
+fn test_ising_individual_coefficients() {
+	let lattice = Lattice::chain(2, Boundary::Open);
+	// -J Z_0 Z_1 - h X_1 - g Z_0
+	let observable = ising(&lattice, 2., vec![0., 1.5], vec![0.5, 0.]);
+	assert_eq!(observable.term_count(), 3);
+	// Z_0 is conserved, and Z_0 = 1 leaves -0.5 - 2 Z_1 - 1.5 X_1
+	assert_close(ground_energy(&observable, |_| true), -0.5 - 2.5);
+}
+
+#[test]
+#[should_panic]
+fn test_coefficient_count_mismatch() {
+	let lattice = Lattice::chain(3, Boundary::Open);
+	ising(&lattice, Coefficients::Individual(vec![1.]), 0., 0.);
+}
+
+#[test]
+fn test_heisenberg() {
+	// singlet of two spins: J (XX + YY + ZZ) = -3J
+	let dimer = heisenberg(&Lattice::chain(2, Boundary::Open), 1., 0.);
+	assert_close(ground_energy(&dimer, |_| true), -3.);
+	// 4-site ring, equal to the 2×2 square and ladder: E_0 = -8J
+	for lattice in &[
+		Lattice::chain(4, Boundary::Periodic),
+		Lattice::square(2, 2, Boundary::Open),
+		Lattice::ladder(2, Boundary::Open),
+	] {
+		assert_close(ground_energy(&heisenberg(lattice, 1., 0.), |_| true), -8.);
+	}
+	// a strong field polarizes all spins to |0>
+	let polarized = heisenberg(&Lattice::chain(4, Boundary::Periodic), 1., 10.);
+	assert_close(ground_energy(&polarized, |_| true), 4. - 40.);
+}
+
+#[test]
+fn test_xxz() {
+	// dimer: the triplet |01> + |10> has 2J - JΔ and the singlet -2J - JΔ
+	for &delta in &[-2., 0., 0.5, 3.] {
+		let observable = xxz(&Lattice::chain(2, Boundary::Open), 1., delta, 0.);
+		let exact = f64::min(-2. - delta, delta);
+		assert_close(ground_energy(&observable, |_| true), exact);
+	}
+	// XX ring of 4 sites: 2 fermions with hopping 2J and antiperiodic
+	// boundary, filling ε_k = 4J cos k at k = ±3π/4
+	let xx = xxz(&Lattice::chain(4, Boundary::Periodic), 1., 0., 0.);
+	let ground = ground_energy(&xx, |i| i.count_ones() == 2);
+	assert_close(ground, -4. * std::f64::consts::SQRT_2);
+}
+
+#[test]
+fn test_hubbard_dimer() {
+	// 2 electrons with S_z = 0: E_0 = U/2 - sqrt(U^2/4 + 4t^2)
+	let sz_zero = |i: usize| i.count_ones() == 2 && (i & 0b0101).count_ones() == 1;
+	for &(t, u) in &[(1., 0.), (1., 4.), (0.5, 8.)] {
+		let observable = fermi_hubbard(&Lattice::chain(2, Boundary::Open), t, u, 0.);
+		assert_eq!(observable.qubit_count(), 4);
+		let exact = u / 2. - (u * u / 4. + 4. * t * t).sqrt();
+		assert_close(ground_energy(&observable, sz_zero), exact);
+	}
+	// one electron: bonding orbital -t, shifted by the chemical potential
+	let observable = fermi_hubbard(&Lattice::chain(2, Boundary::Open), 1., 4., 0.3);
+	assert_close(
+		ground_energy(&observable, |i| i.count_ones() == 1),
+		-1. - 0.3,
+	);
+}
+
+#[test]
+fn test_hubbard_free_ring() {
+	// U = 0 on a 4-site ring: single-particle energies -2t cos k for
+	// k = 0, ±π/2, π, so two electrons per spin have -2t each
+	let observable = fermi_hubbard(&Lattice::chain(4, Boundary::Periodic), 1., 0., 0.);
+	let half_filling = |i: usize| i.count_ones() == 4 && (i & 0x55).count_ones() == 2;
+	assert_close(ground_energy(&observable, half_filling), -4.);
+}
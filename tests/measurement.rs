@@ -0,0 +1,164 @@
+use qurs::lattice::{fermi_hubbard, heisenberg, Boundary, Lattice};
+use qurs::measurement::{optimal_shot_allocation, Grouping, MeasurementPlan};
+use qurs::observable::Observable;
+use qurs::pauli::{Pauli, PauliString};
+use qurs::prelude::*;
+use qurs::{Complex, StateVec};
+use rand::{Rng, SeedableRng};
+
+fn random_observable(qubit_count: usize, term_count: usize, seed: u64) -> Observable {
+	let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+	let mut observable = Observable::new(qubit_count);
+	for _ in 0..term_count {
+		let paulis: Vec<Pauli> = (0..qubit_count)
+			.map(|_| Pauli::from_type(rng.gen_range(0..4)).unwrap())
+			.collect();
+		observable.add_term(
+			Complex::new(rng.gen_range(-1.0..1.0), 0.),
+			PauliString::from_whole_list(&paulis),
+		);
+	}
+	observable.simplify(0.);
+	observable
+}
+
+fn test_observables() -> Vec<Observable> {
+	vec![
+		heisenberg(&Lattice::chain(4, Boundary::Periodic), 1., 0.3),
+		fermi_hubbard(&Lattice::chain(3, Boundary::Open), 1., 4., 0.5),
+		random_observable(5, 40, 1),
+		random_observable(4, 60, 2),
+	]
+}
+
+/// Check the grouping and the exact expectation value of each group computed
+/// from the probabilities after the basis change.
+fn check_plan(observable: &Observable, grouping: Grouping, seed: u32) -> usize {
+	let plan = MeasurementPlan::new(observable, grouping);
+	let mut state = StateVec::new(observable.qubit_count());
+	state.set_haar_random_state_with_seed(seed);
+
+	let mut seen = vec![false; observable.term_count()];
+	let mut total = plan.constant();
+	for group in plan.groups() {
+		for (k, &i) in group.terms().iter().enumerate() {
+			assert!(!seen[i]);
+			seen[i] = true;
+			for &j in &group.terms()[..k] {
+				assert!(grouping.compatible(&observable.terms()[i].1, &observable.terms()[j].1));
+			}
+		}
+		if grouping == Grouping::QubitWise {
+			assert!(group
+				.circuit()
+				.gates()
+				.iter()
+				.all(|g| g.qubits().len() == 1));
+		}
+
+		let mut rotated = state.clone();
+		group
+			.circuit()
+			.update_quantum_state(rotated.as_mut())
+			.unwrap();
+		let from_probabilities: f64 = rotated
+			.as_ref()
+			.iter()
+			.enumerate()
+			.map(|(outcome, a)| a.norm_sqr() * group.value(outcome as u64))
+			.sum();
+		let exact: f64 = group
+			.terms()
+			.iter()
+			.map(|&i| {
+				let (c, p) = &observable.terms()[i];
+				c.re * p.expectation_value(&state).unwrap()
+			})
+			.sum();
+		assert!(
+			(from_probabilities - exact).abs() < 1e-10,
+			"{} != {}",
+			from_probabilities,
+			exact
+		);
+		total += exact;
+	}
+	let identity_count = observable
+		.terms()
+		.iter()
+		.filter(|(_, p)| p.targets().is_empty())
+		.count();
+	assert_eq!(
+		seen.iter().filter(|&&s| s).count() + identity_count,
+		observable.term_count()
+	);
+	let expected = observable.expectation_value(&state).unwrap().re;
+	assert!((total - expected).abs() < 1e-10);
+	plan.groups().len()
+}
+
+#[test]
+fn test_qubit_wise_grouping() {
+	for (k, observable) in test_observables().iter().enumerate() {
+		check_plan(observable, Grouping::QubitWise, k as u32);
+	}
+	// XX, YY and ZZ terms with the Z field
+	let h = heisenberg(&Lattice::chain(4, Boundary::Periodic), 1., 0.3);
+	assert_eq!(
+		MeasurementPlan::new(&h, Grouping::QubitWise).groups().len(),
+		3
+	);
+}
+
+#[test]
+fn test_commuting_grouping() {
+	for (k, observable) in test_observables().iter().enumerate() {
+		let commuting = check_plan(observable, Grouping::Commuting, k as u32 + 10);
+		let qubit_wise = MeasurementPlan::new(observable, Grouping::QubitWise)
+			.groups()
+			.len();
+		assert!(commuting <= qubit_wise);
+	}
+	let h = fermi_hubbard(&Lattice::chain(3, Boundary::Open), 1., 4., 0.5);
+	assert!(
+		MeasurementPlan::new(&h, Grouping::Commuting).groups().len()
+			< MeasurementPlan::new(&h, Grouping::QubitWise).groups().len()
+	);
+}
+
+#[test]
+fn test_estimate() {
+	let h = heisenberg(&Lattice::chain(4, Boundary::Periodic), 1., 0.3);
+	let mut state = StateVec::new(4);
+	state.set_haar_random_state_with_seed(3);
+	let exact = h.expectation_value(&state).unwrap().re;
+	for &grouping in &[Grouping::QubitWise, Grouping::Commuting] {
+		let plan = MeasurementPlan::new(&h, grouping);
+		let shots = plan.default_shot_allocation(30000);
+		assert_eq!(shots.iter().sum::<u32>(), 30000);
+		let estimate = plan.estimate(state.as_ref(), &shots).unwrap();
+		assert!(estimate.variance > 0.);
+		assert!(
+			(estimate.value - exact).abs() < 5. * estimate.variance.sqrt(),
+			"{} != {}",
+			estimate.value,
+			exact
+		);
+
+		let shots = estimate.optimal_shot_allocation(30000);
+		let next = plan.estimate(state.as_ref(), &shots).unwrap();
+		assert!((next.value - exact).abs() < 5. * next.variance.sqrt());
+	}
+}
+
+#[test]
+fn test_optimal_shot_allocation() {
+	assert_eq!(
+		optimal_shot_allocation(&[1., 2., 1.], 406),
+		vec![102, 202, 102]
+	);
+	assert_eq!(optimal_shot_allocation(&[1., 2.], 10), vec![4, 6]);
+	assert_eq!(optimal_shot_allocation(&[0., 0.], 11), vec![6, 5]);
+	assert_eq!(optimal_shot_allocation(&[1., 3.], 3), vec![1, 2]);
+	assert!(optimal_shot_allocation(&[], 10).is_empty());
+}
@@ -0,0 +1,182 @@
+use qurs::circuit::{equivalent, Circuit, Gate};
+use qurs::mitigation::{
+	fold, Extrapolation, Folding, MitigationErr, ProbabilisticErrorCancellation,
+	ZeroNoiseExtrapolation,
+};
+use qurs::noise::{Channel, DensityMatrix, NoisyCircuit};
+use qurs::observable::Observable;
+use qurs::pauli::{Pauli, PauliString};
+use qurs::Complex;
+
+fn test_circuit() -> Circuit {
+	let mut circuit = Circuit::new(2);
+	circuit.add_gate(Gate::Ry(0, 0.9));
+	circuit.add_gate(Gate::H(1));
+	circuit.add_gate(Gate::Cnot(0, 1));
+	circuit.add_gate(Gate::Rz(1, 0.4));
+	circuit.add_gate(Gate::S(0));
+	circuit.add_gate(Gate::Cz(1, 0));
+	circuit.add_gate(Gate::Rx(0, 0.3));
+	circuit
+}
+
+fn depolarizing_noise(gate: &Gate) -> Vec<Channel> {
+	vec![Channel::depolarizing(&gate.qubits(), 0.02).unwrap()]
+}
+
+fn test_observable() -> Observable {
+	let mut observable = Observable::new(2);
+	observable.add_term(
+		Complex::new(1., 0.),
+		PauliString::new(&[0, 1], &[Pauli::Z, Pauli::X]).unwrap(),
+	);
+	observable.add_term(
+		Complex::new(0.5, 0.),
+		PauliString::new(&[0], &[Pauli::Y]).unwrap(),
+	);
+	observable
+}
+
+fn noisy_value(circuit: &NoisyCircuit, observable: &Observable) -> f64 {
+	let mut rho = DensityMatrix::new(circuit.qubit_count());
+	circuit.update_density_matrix(&mut rho).unwrap();
+	rho.expectation_value(observable).unwrap().re
+}
+
+fn ideal_value(circuit: &Circuit, observable: &Observable) -> f64 {
+	noisy_value(
+		&NoisyCircuit::with_gate_noise(circuit, |_| Vec::new()),
+		observable,
+	)
+}
+
+#[test]
+fn test_fold() {
+	let circuit = test_circuit();
+	for &folding in &[Folding::Global, Folding::Local] {
+		for &(scale, count) in &[(1., 7), (1.6, 11), (2., 15), (3., 21), (3.3, 23), (5., 35)] {
+			let folded = fold(&circuit, scale, folding).unwrap();
+			assert_eq!(folded.gate_count(), count);
+			assert!(equivalent(&folded, &circuit, false));
+		}
+	}
+	let folded = fold(&circuit, 2., Folding::Local).unwrap();
+	assert_eq!(
+		&folded.gates()[..4],
+		&[
+			Gate::Ry(0, 0.9),
+			Gate::Ry(0, -0.9),
+			Gate::Ry(0, 0.9),
+			Gate::H(1)
+		]
+	);
+	assert!(matches!(
+		fold(&circuit, 0.5, Folding::Global),
+		Err(MitigationErr::InvalidScaleFactor(_))
+	));
+}
+
+#[test]
+fn test_extrapolation() {
+	let x = [1., 1.5, 2., 3.];
+	let cubic: Vec<f64> = x
+		.iter()
+		.map(|x| 0.7 - 0.2 * x + 0.05 * x * x - 0.01 * x * x * x)
+		.collect();
+	let value = Extrapolation::Richardson.extrapolate(&x, &cubic).unwrap();
+	assert!((value - 0.7).abs() < 1e-12);
+	let line: Vec<f64> = x.iter().map(|x| 0.7 - 0.2 * x).collect();
+	let value = Extrapolation::Linear.extrapolate(&x, &line).unwrap();
+	assert!((value - 0.7).abs() < 1e-12);
+	let exponential: Vec<f64> = x.iter().map(|x| 0.1 - 0.6 * (-0.3 * x).exp()).collect();
+	let value = Extrapolation::Exponential { asymptote: 0.1 }
+		.extrapolate(&x, &exponential)
+		.unwrap();
+	assert!((value + 0.5).abs() < 1e-12);
+	assert!(matches!(
+		Extrapolation::Exponential { asymptote: -0.2 }.extrapolate(&x, &exponential),
+		Err(MitigationErr::InvalidExponentialData(_))
+	));
+	assert!(matches!(
+		Extrapolation::Linear.extrapolate(&x, &line[1..]),
+		Err(MitigationErr::InconsistentDataLength(4, 3))
+	));
+	assert!(matches!(
+		Extrapolation::Linear.extrapolate(&[1.], &[0.]),
+		Err(MitigationErr::TooFewScaleFactors(1))
+	));
+	assert!(matches!(
+		ZeroNoiseExtrapolation::new(&[1., 1.], Folding::Global, Extrapolation::Linear),
+		Err(MitigationErr::InvalidScaleFactor(_))
+	));
+}
+
+#[test]
+fn test_zero_noise_extrapolation() {
+	let circuit = test_circuit();
+	let observable = test_observable();
+	let ideal = ideal_value(&circuit, &observable);
+	let executor = |c: &Circuit| {
+		noisy_value(
+			&NoisyCircuit::with_gate_noise(c, depolarizing_noise),
+			&observable,
+		)
+	};
+	let unmitigated = (executor(&circuit) - ideal).abs();
+	assert!(unmitigated > 0.02);
+	for &folding in &[Folding::Global, Folding::Local] {
+		for &extrapolation in &[
+			Extrapolation::Richardson,
+			Extrapolation::Linear,
+			Extrapolation::Exponential { asymptote: 0. },
+		] {
+			let zne = ZeroNoiseExtrapolation::new(&[1., 2., 3.], folding, extrapolation).unwrap();
+			assert_eq!(zne.circuits(&circuit).len(), 3);
+			let value = zne.estimate(&circuit, executor).unwrap();
+			assert!(
+				(value - ideal).abs() < unmitigated / 3.,
+				"{:?} {:?} {} {} {}",
+				folding,
+				extrapolation,
+				value,
+				ideal,
+				unmitigated
+			);
+		}
+	}
+}
+
+#[test]
+fn test_probabilistic_error_cancellation() {
+	let circuit = test_circuit();
+	let observable = test_observable();
+	let ideal = ideal_value(&circuit, &observable);
+	let noisy = NoisyCircuit::with_gate_noise(&circuit, depolarizing_noise);
+	let pec = ProbabilisticErrorCancellation::new(&noisy).unwrap();
+	// γ = (3/λ - 1)/2 for one qubit and (15/λ - 7)/8 for two qubits, with
+	// λ = 1 - 4^k p/(4^k - 1)
+	let one: f64 = (3. / (1. - 4. * 0.02 / 3.) - 1.) / 2.;
+	let two: f64 = (15. / (1. - 16. * 0.02 / 15.) - 7.) / 8.;
+	assert!((pec.gamma() - one.powi(5) * two.powi(2)).abs() < 1e-10);
+	let estimate = pec.estimate(4000, |c| noisy_value(c, &observable));
+	assert!(estimate.standard_error > 0. && estimate.standard_error < 0.02);
+	assert!(
+		(estimate.value - ideal).abs() < 5. * estimate.standard_error,
+		"{:?} {}",
+		estimate,
+		ideal
+	);
+	let mut damped = NoisyCircuit::new(1);
+	damped.add_gate(Gate::X(0));
+	damped.add_channel(Channel::amplitude_damping(0, 0.1).unwrap());
+	assert!(matches!(
+		ProbabilisticErrorCancellation::new(&damped),
+		Err(MitigationErr::NonPauliChannel(1))
+	));
+	let mut erased = NoisyCircuit::new(1);
+	erased.add_channel(Channel::depolarizing(&[0], 0.75).unwrap());
+	assert!(matches!(
+		ProbabilisticErrorCancellation::new(&erased),
+		Err(MitigationErr::NonInvertibleChannel(0))
+	));
+}
@@ -0,0 +1,191 @@
+use qurs::circuit::{Circuit, Gate};
+use qurs::noise::{Channel, DensityMatrix, NoiseErr, NoisyCircuit, Operation};
+use qurs::observable::Observable;
+use qurs::pauli::{Pauli, PauliString};
+use qurs::{Complex, StateVec};
+
+const EPS: f64 = 1e-10;
+
+fn test_circuit() -> Circuit {
+	let mut circuit = Circuit::new(3);
+	circuit.add_gate(Gate::H(0));
+	circuit.add_gate(Gate::Ry(1, 0.7));
+	circuit.add_gate(Gate::Cnot(0, 2));
+	circuit.add_gate(Gate::T(2));
+	circuit.add_gate(Gate::Rzz(1, 2, 0.4));
+	circuit.add_gate(Gate::Ccnot(2, 1, 0));
+	circuit.add_gate(Gate::U3(1, 0.3, 0.2, 0.1));
+	circuit
+}
+
+fn test_noise(gate: &Gate) -> Vec<Channel> {
+	let qubits = gate.qubits();
+	let mut channels = vec![Channel::depolarizing(&qubits[..qubits.len().min(2)], 0.05).unwrap()];
+	for &q in &qubits {
+		channels.push(Channel::amplitude_damping(q, 0.03).unwrap());
+	}
+	channels
+}
+
+fn max_distance(a: &[Complex<f64>], b: &[Complex<f64>]) -> f64 {
+	a.iter()
+		.zip(b)
+		.map(|(x, y)| (x - y).norm())
+		.fold(0., f64::max)
+}
+
+#[test]
+fn test_unitary_evolution() {
+	let circuit = test_circuit();
+	let mut state = StateVec::new(3);
+	circuit.update_quantum_state(state.as_mut()).unwrap();
+	let mut rho = DensityMatrix::new(3);
+	rho.apply_circuit(&circuit).unwrap();
+	assert!(
+		max_distance(
+			rho.matrix(),
+			DensityMatrix::from_state(state.as_ref()).matrix()
+		) < EPS
+	);
+	assert!((rho.trace() - 1.).abs() < EPS);
+	assert!((rho.purity() - 1.).abs() < EPS);
+	assert!((rho.fidelity(state.as_ref()).unwrap() - 1.).abs() < EPS);
+	let mut observable = Observable::new(3);
+	observable.add_term(
+		Complex::new(0.5, 0.),
+		PauliString::new(&[0, 2], &[Pauli::X, Pauli::Y]).unwrap(),
+	);
+	observable.add_term(
+		Complex::new(-1.2, 0.),
+		PauliString::new(&[1], &[Pauli::Z]).unwrap(),
+	);
+	let expected = observable.expectation_value(&state).unwrap();
+	assert!((rho.expectation_value(&observable).unwrap() - expected).norm() < EPS);
+}
+
+#[test]
+fn test_channels() {
+	let p = 0.3;
+	let mut rho = DensityMatrix::new(1);
+	rho.apply_channel(&Channel::depolarizing(&[0], p).unwrap());
+	assert!((rho.probabilities()[1] - 2. * p / 3.).abs() < EPS);
+	let mut rho = DensityMatrix::new(2);
+	rho.apply_gate(&Gate::X(1));
+	rho.apply_channel(&Channel::amplitude_damping(1, 0.2).unwrap());
+	assert!((rho.probabilities()[0] - 0.2).abs() < EPS);
+	assert!((rho.probabilities()[2] - 0.8).abs() < EPS);
+	let mut rho = DensityMatrix::new(1);
+	rho.apply_gate(&Gate::H(0));
+	rho.apply_channel(&Channel::phase_damping(0, 0.36).unwrap());
+	assert!((rho.matrix()[1].re - 0.4).abs() < EPS);
+	// phase damping is a phase flip with p = (1 - sqrt(1 - λ))/2
+	let probabilities = Channel::phase_damping(0, 0.36)
+		.unwrap()
+		.pauli_probabilities()
+		.unwrap();
+	for (p, expected) in probabilities.iter().zip(&[0.9, 0., 0., 0.1]) {
+		assert!((p - expected).abs() < EPS);
+	}
+	assert!(Channel::amplitude_damping(0, 0.1)
+		.unwrap()
+		.pauli_probabilities()
+		.is_none());
+	let probabilities = Channel::depolarizing(&[3, 1], 0.15)
+		.unwrap()
+		.pauli_probabilities()
+		.unwrap();
+	assert!((probabilities[0] - 0.85).abs() < EPS);
+	assert!(probabilities[1..].iter().all(|p| (p - 0.01).abs() < EPS));
+}
+
+#[test]
+fn test_invalid_channels() {
+	let one = Complex::new(1., 0.);
+	let zero = Complex::new(0., 0.);
+	assert!(matches!(
+		Channel::new(
+			&[0],
+			vec![vec![one, zero, zero, one], vec![one, zero, zero, zero]]
+		),
+		Err(NoiseErr::InvalidKrausOperators)
+	));
+	assert!(matches!(
+		Channel::new(&[0], vec![vec![one, zero]]),
+		Err(NoiseErr::InvalidKrausOperators)
+	));
+	assert!(matches!(
+		Channel::depolarizing(&[1, 1], 0.1),
+		Err(NoiseErr::InvalidTargetList(_))
+	));
+	assert!(matches!(
+		Channel::bit_flip(0, 1.2),
+		Err(NoiseErr::InvalidProbability(_))
+	));
+	assert!(matches!(
+		Channel::amplitude_damping(0, -0.1),
+		Err(NoiseErr::InvalidProbability(_))
+	));
+}
+
+#[test]
+fn test_trajectories() {
+	let circuit = test_circuit();
+	let noisy = NoisyCircuit::with_gate_noise(&circuit, test_noise);
+	let channel_count = noisy
+		.operations()
+		.iter()
+		.filter(|o| matches!(o, Operation::Channel(_)))
+		.count();
+	assert_eq!(channel_count, 7 + 11);
+	let mut rho = DensityMatrix::new(3);
+	noisy.update_density_matrix(&mut rho).unwrap();
+	assert!((rho.trace() - 1.).abs() < EPS);
+	assert!(rho.purity() < 0.9);
+	let trajectories = 3000;
+	let mut average = [0.; 8];
+	for _ in 0..trajectories {
+		let mut state = StateVec::new(3);
+		noisy.update_quantum_state(state.as_mut()).unwrap();
+		let norm: f64 = state.as_ref().iter().map(|a| a.norm_sqr()).sum();
+		assert!((norm - 1.).abs() < 1e-8);
+		for (a, s) in average.iter_mut().zip(state.as_ref()) {
+			*a += s.norm_sqr() / trajectories as f64;
+		}
+	}
+	let distance = average
+		.iter()
+		.zip(rho.probabilities())
+		.map(|(a, b)| (a - b).abs())
+		.fold(0., f64::max);
+	assert!(distance < 0.03, "{}", distance);
+	let shots = 20000;
+	let mut frequency = [0.; 8];
+	for outcome in rho.sampling(shots) {
+		frequency[outcome as usize] += 1. / shots as f64;
+	}
+	let distance = frequency
+		.iter()
+		.zip(rho.probabilities())
+		.map(|(a, b)| (a - b).abs())
+		.fold(0., f64::max);
+	assert!(distance < 0.02, "{}", distance);
+}
+
+#[test]
+fn test_trajectory_skips_zero_kraus_operator() {
+	// the second Kraus operator of the amplitude damping is 0, and the
+	// probabilities of an unnormalized state leave r unused half the time
+	let channel = Channel::amplitude_damping(0, 0.).unwrap();
+	for _ in 0..50 {
+		let mut state = [Complex::new(0.5f64.sqrt(), 0.), Complex::new(0., 0.)];
+		channel.update_quantum_state(&mut state);
+		assert!((state[0] - Complex::new(1., 0.)).norm() < EPS);
+		assert_eq!(state[1], Complex::new(0., 0.));
+	}
+}
+
+#[test]
+#[should_panic(expected = "power of 2")]
+fn test_density_matrix_rejects_empty_state() {
+	DensityMatrix::from_state(&[]);
+}
@@ -0,0 +1,271 @@
+use qurs::circuit::{Circuit, Gate};
+use qurs::noise::{Channel, DensityMatrix, Operation};
+use qurs::noise_model::{NoiseModel, NoiseModelErr};
+use qurs::{Complex, StateVec};
+
+const EPS: f64 = 1e-10;
+
+const CALIBRATION: &str = r#"{
+	"qubits": [
+		{"t1": 80.0, "t2": 60.0, "readout_fidelity": 0.98},
+		{"t1": 70.0, "t2": 90.0, "prob_meas1_prep0": 0.01, "prob_meas0_prep1": 0.03},
+		{}
+	],
+	"gates": [
+		{"gate": "SqrtX", "error": 3e-4, "time": 0.035},
+		{"gate": "Rz", "time": 0},
+		{"gate": "X", "error": 3e-4, "time": 0.035},
+		{"gate": "Cnot", "error": 0.01, "time": 0.4},
+		{"gate": "Cnot", "qubits": [0, 1], "error": 0.008, "time": 0.3},
+		{"gate": "Sdag", "qubits": [2], "error": 0.001}
+	]
+}"#;
+
+fn pauli_error(channels: &[Channel]) -> f64 {
+	assert_eq!(channels.len(), 1);
+	1. - channels[0].pauli_probabilities().unwrap()[0]
+}
+
+#[test]
+fn test_parse() {
+	let model = NoiseModel::parse(CALIBRATION).unwrap();
+	assert_eq!(model.qubit_count(), 3);
+	assert_eq!(model.gate_time(&Gate::Cnot(0, 1)), 0.3);
+	assert_eq!(model.gate_time(&Gate::Cnot(1, 0)), 0.4);
+	assert_eq!(model.gate_time(&Gate::SqrtX(2)), 0.035);
+	assert_eq!(model.gate_time(&Gate::H(2)), 0.);
+	// depolarizing probability r (d + 1)/d for the average infidelity r
+	assert!((pauli_error(&model.gate_noise(&Gate::SqrtX(1))) - 3e-4 * 1.5).abs() < EPS);
+	assert!((pauli_error(&model.gate_noise(&Gate::Sdag(2))) - 1e-3 * 1.5).abs() < EPS);
+	assert!(model.gate_noise(&Gate::Sdag(1)).is_empty());
+	assert!(model.gate_noise(&Gate::Rz(0, 0.3)).is_empty());
+	// the error for specific qubits replaces the generic one
+	let noise = model.gate_noise(&Gate::Cnot(0, 1));
+	assert_eq!(noise[0].targets(), &[0, 1]);
+	assert!((pauli_error(&noise) - 0.008 * 1.25).abs() < EPS);
+	let noise = model.gate_noise(&Gate::Cnot(2, 1));
+	assert_eq!(noise.len(), 1);
+	assert_eq!(noise[0].targets(), &[2, 1]);
+	assert!((pauli_error(&noise) - 0.01 * 1.25).abs() < EPS);
+	let readout = model.readout().matrix();
+	// P(measure 0b011 | prepare 0b000)
+	assert!((readout[0b011 * 8] - 0.02 * 0.01).abs() < EPS);
+	assert!((readout[0b010] - 0.98 * 0.03).abs() < EPS);
+}
+
+#[test]
+fn test_read() {
+	let path = std::env::temp_dir().join(format!("qurs_calibration_{}.json", std::process::id()));
+	std::fs::write(&path, CALIBRATION).unwrap();
+	let model = NoiseModel::read(&path).unwrap();
+	std::fs::remove_file(&path).unwrap();
+	assert_eq!(model, NoiseModel::parse(CALIBRATION).unwrap());
+	assert!(matches!(NoiseModel::read(&path), Err(NoiseModelErr::Io(_))));
+}
+
+#[test]
+fn test_invalid_calibration() {
+	assert!(matches!(
+		NoiseModel::parse("{\"qubits\": [}"),
+		Err(NoiseModelErr::InvalidJson(12))
+	));
+	assert!(matches!(
+		NoiseModel::parse("{} x"),
+		Err(NoiseModelErr::InvalidJson(3))
+	));
+	assert!(matches!(
+		NoiseModel::parse("{}"),
+		Err(NoiseModelErr::InvalidCalibration(_))
+	));
+	assert!(matches!(
+		NoiseModel::parse(r#"{"qubits": [{"t1": 1}]}"#),
+		Err(NoiseModelErr::InvalidCalibration(_))
+	));
+	assert!(matches!(
+		NoiseModel::parse(r#"{"qubits": [{"t1": 1, "t2": 3}]}"#),
+		Err(NoiseModelErr::Noise(_))
+	));
+	assert!(matches!(
+		NoiseModel::parse(r#"{"qubits": [{"readout_fidelity": "high"}]}"#),
+		Err(NoiseModelErr::InvalidCalibration(_))
+	));
+	assert!(matches!(
+		NoiseModel::parse(
+			r#"{"qubits": [{}], "gates": [{"gate": "Cnot", "qubits": [0, 1], "error": 0.1}]}"#
+		),
+		Err(NoiseModelErr::InvalidQubit(1))
+	));
+	assert!(matches!(
+		NoiseModel::parse(r#"{"qubits": [{}], "gates": [{"gate": "DenseMatrix", "error": 0.1}]}"#),
+		Err(NoiseModelErr::InvalidCalibration(_))
+	));
+}
+
+#[test]
+fn test_json_grammar() {
+	let with_fidelity = |x: &str| format!(r#"{{"qubits": [{{"readout_fidelity": {}}}]}}"#, x);
+	let offset = with_fidelity("").find('}').unwrap();
+	for valid in ["0.98", "98e-2", "0.98E+0", "9.8e-1"] {
+		assert!(
+			NoiseModel::parse(&with_fidelity(valid)).is_ok(),
+			"{}",
+			valid
+		);
+	}
+	for (invalid, position) in [
+		("+1", 0),
+		("01", 1),
+		("-.5", 1),
+		("1.", 2),
+		("1e", 2),
+		("-", 1),
+	] {
+		assert!(
+			matches!(
+				NoiseModel::parse(&with_fidelity(invalid)),
+				Err(NoiseModelErr::InvalidJson(p)) if p == offset + position
+			),
+			"{}",
+			invalid
+		);
+	}
+
+	// surrogate pairs encode characters outside the basic multilingual plane
+	assert!(NoiseModel::parse(r#"{"qubits": [], "note": "\ud83d\ude00 \u00e9"}"#).is_ok());
+	for invalid in [r#""\ud83d""#, r#""\ude00""#, r#""\ud83dA""#, r#""\u+041""#] {
+		assert!(
+			matches!(
+				NoiseModel::parse(&format!(r#"{{"qubits": [], "note": {}}}"#, invalid)),
+				Err(NoiseModelErr::InvalidJson(_))
+			),
+			"{}",
+			invalid
+		);
+	}
+
+	// arrays and objects nest at most 128 deep
+	let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+	assert!(matches!(
+		NoiseModel::parse(&nested(128)),
+		Err(NoiseModelErr::InvalidCalibration(_))
+	));
+	assert!(matches!(
+		NoiseModel::parse(&nested(100_000)),
+		Err(NoiseModelErr::InvalidJson(128))
+	));
+}
+
+#[test]
+fn test_thermal_relaxation() {
+	let (t1, t2, time) = (50., 30., 12.);
+	let channel = Channel::thermal_relaxation(0, t1, t2, time).unwrap();
+	let mut rho = DensityMatrix::new(1);
+	rho.apply_gate(&Gate::X(0));
+	rho.apply_channel(&channel);
+	assert!((rho.probabilities()[1] - (-time / t1).exp()).abs() < EPS);
+	let mut rho = DensityMatrix::new(1);
+	rho.apply_gate(&Gate::H(0));
+	rho.apply_channel(&channel);
+	assert!((rho.matrix()[1].norm() - 0.5 * (-time / t2).exp()).abs() < EPS);
+	assert!(Channel::thermal_relaxation(0, t1, 2.5 * t1, time).is_err());
+}
+
+#[test]
+fn test_gate_noise_rules() {
+	let mut model = NoiseModel::new(2);
+	model.set_gate_error("H", None, 0.01).unwrap();
+	model.set_gate_error("H", Some(&[1]), 0.02).unwrap();
+	model.set_gate_error("H", Some(&[1]), 0.002).unwrap();
+	model
+		.add_gate_noise("H", Some(&[1]), Channel::amplitude_damping(0, 0.1).unwrap())
+		.unwrap();
+	assert!((pauli_error(&model.gate_noise(&Gate::H(0))) - 0.01 * 1.5).abs() < EPS);
+	let noise = model.gate_noise(&Gate::H(1));
+	assert_eq!(noise.len(), 2);
+	assert!((pauli_error(&noise[..1]) - 0.002 * 1.5).abs() < EPS);
+	assert_eq!(noise[1], Channel::amplitude_damping(1, 0.1).unwrap());
+	assert!(model.set_gate_error("DenseMatrix", None, 0.01).is_err());
+	assert!(model.set_gate_error("X", None, 0.9).is_err());
+}
+
+#[test]
+fn test_idle_noise() {
+	let (t1, t2) = (10., 15.);
+	let mut model = NoiseModel::new(3);
+	model.set_gate_time("X", None, 0.5).unwrap();
+	model.set_gate_time("X", Some(&[0]), 2.).unwrap();
+	model.set_relaxation(1, t1, t2).unwrap();
+	let mut circuit = Circuit::new(3);
+	circuit.add_gate(Gate::X(0));
+	circuit.add_gate(Gate::X(1));
+	circuit.add_gate(Gate::Cnot(1, 2));
+	let noisy = model.noisy_circuit(&circuit);
+	// qubit 1 is idle for 1.5 in the first layer, and the Cnot takes no time
+	let channels: Vec<&Channel> = noisy
+		.operations()
+		.iter()
+		.filter_map(|o| match o {
+			Operation::Channel(c) => Some(c),
+			_ => None,
+		})
+		.collect();
+	assert_eq!(channels.len(), 1);
+	assert_eq!(channels[0].targets(), &[1]);
+	let mut rho = DensityMatrix::new(3);
+	model.update_density_matrix(&circuit, &mut rho).unwrap();
+	let decay = (-1.5 / t1).exp();
+	assert!((rho.probabilities()[0b111] - decay).abs() < EPS);
+	assert!((rho.probabilities()[0b001] - (1. - decay)).abs() < EPS);
+}
+
+#[test]
+fn test_noisy_run() {
+	let model = NoiseModel::parse(CALIBRATION).unwrap();
+	let mut circuit = Circuit::new(3);
+	circuit.add_gate(Gate::SqrtX(0));
+	circuit.add_gate(Gate::Cnot(0, 1));
+	circuit.add_gate(Gate::Cnot(1, 2));
+	circuit.add_gate(Gate::Rz(2, 0.7));
+	circuit.add_gate(Gate::SqrtX(2));
+	circuit.add_gate(Gate::Cnot(2, 0));
+	let mut rho = DensityMatrix::new(3);
+	model.update_density_matrix(&circuit, &mut rho).unwrap();
+	let mut ideal = StateVec::new(3);
+	circuit.update_quantum_state(ideal.as_mut()).unwrap();
+	let fidelity = rho.fidelity(ideal.as_ref()).unwrap();
+	assert!(fidelity < 0.99 && fidelity > 0.9, "{}", fidelity);
+	let trajectories = 2000;
+	let mut average = vec![Complex::new(0., 0.); 64];
+	for _ in 0..trajectories {
+		let mut state = StateVec::new(3);
+		model
+			.update_quantum_state(&circuit, state.as_mut())
+			.unwrap();
+		for (a, x) in average
+			.iter_mut()
+			.zip(DensityMatrix::from_state(state.as_ref()).matrix())
+		{
+			*a += x / trajectories as f64;
+		}
+	}
+	let distance = average
+		.iter()
+		.zip(rho.matrix())
+		.map(|(a, b)| (a - b).norm())
+		.fold(0., f64::max);
+	assert!(distance < 0.03, "{}", distance);
+	// readout errors on top of the noisy state
+	let shots = 40000;
+	let mut frequency = [0.; 8];
+	for outcome in model.sampling(&circuit, shots).unwrap() {
+		frequency[outcome as usize] += 1. / shots as f64;
+	}
+	let matrix = model.readout().matrix();
+	let probabilities = rho.probabilities();
+	for (measured, f) in frequency.iter().enumerate() {
+		let expected: f64 = (0..8)
+			.map(|p| matrix[measured * 8 + p] * probabilities[p])
+			.sum();
+		assert!((f - expected).abs() < 0.01);
+	}
+}
@@ -0,0 +1,117 @@
+use qurs::circuit::{equivalent, Circuit, Gate};
+use qurs::optimizer::{
+	CancelInversePairs, CommuteDiagonal, FuseSingleQubitGates, MergeRotations, Pass, PassManager,
+};
+
+fn circuit_of(qubit_count: usize, gates: &[Gate]) -> Circuit {
+	let mut circuit = Circuit::new(qubit_count);
+	for gate in gates {
+		circuit.add_gate(gate.clone());
+	}
+	circuit
+}
+
+#[test]
+fn cancel_inverse_pairs() {
+	let circuit = circuit_of(
+		2,
+		&[
+			Gate::H(0),
+			Gate::S(1),
+			Gate::X(0),
+			Gate::X(0),
+			Gate::Sdag(1),
+			Gate::H(0),
+			Gate::Cnot(0, 1),
+			Gate::Cnot(1, 0),
+			Gate::Cz(1, 0),
+			Gate::Cz(0, 1),
+		],
+	);
+	let optimized = CancelInversePairs.run(&circuit);
+	assert_eq!(optimized.gates(), [Gate::Cnot(0, 1), Gate::Cnot(1, 0)]);
+	assert!(equivalent(&circuit, &optimized, false));
+}
+
+#[test]
+fn merge_rotations() {
+	let circuit = circuit_of(
+		2,
+		&[
+			Gate::Rz(0, 0.3),
+			Gate::Rx(1, 0.5),
+			Gate::Rz(0, 0.4),
+			Gate::Rx(1, -0.5),
+			Gate::Ry(0, 1.),
+		],
+	);
+	let optimized = MergeRotations.run(&circuit);
+	assert_eq!(optimized.gate_count(), 2);
+	assert!(equivalent(&circuit, &optimized, false));
+}
+
+#[test]
+fn commute_diagonal_through_control() {
+	let circuit = circuit_of(
+		2,
+		&[Gate::T(0), Gate::Cnot(0, 1), Gate::Tdag(0), Gate::H(1)],
+	);
+	let commuted = CommuteDiagonal.run(&circuit);
+	assert!(equivalent(&circuit, &commuted, false));
+	let optimized = CancelInversePairs.run(&commuted);
+	assert_eq!(optimized.gates(), [Gate::Cnot(0, 1), Gate::H(1)]);
+
+	// A diagonal gate on the target does not commute with CNOT.
+	let circuit = circuit_of(2, &[Gate::T(1), Gate::Cnot(0, 1), Gate::Tdag(1)]);
+	assert_eq!(CommuteDiagonal.run(&circuit), circuit);
+}
+
+#[test]
+fn fuse_single_qubit_gates() {
+	let circuit = circuit_of(
+		2,
+		&[
+			Gate::H(0),
+			Gate::S(0),
+			Gate::T(0),
+			Gate::X(1),
+			Gate::Cnot(0, 1),
+			Gate::Ry(1, 0.3),
+			Gate::Rz(1, 0.2),
+		],
+	);
+	let optimized = FuseSingleQubitGates.run(&circuit);
+	assert_eq!(optimized.gate_count(), 4);
+	assert!(equivalent(&circuit, &optimized, false));
+}
+
+#[test]
+fn pass_manager_report() {
+	let circuit = circuit_of(
+		3,
+		&[
+			Gate::H(0),
+			Gate::T(0),
+			Gate::Cnot(0, 1),
+			Gate::Rz(0, 0.1),
+			Gate::Tdag(0),
+			Gate::Rz(0, 0.2),
+			Gate::Cnot(1, 2),
+			Gate::Cnot(1, 2),
+			Gate::H(2),
+			Gate::S(2),
+			Gate::Sdag(2),
+			Gate::H(2),
+		],
+	);
+	let (optimized, reports) = PassManager::with_default_passes().run(&circuit);
+	assert!(equivalent(&circuit, &optimized, false));
+	assert_eq!(reports.len(), 4);
+	assert_eq!(reports[0].gate_count_before, circuit.gate_count());
+	assert_eq!(reports[3].gate_count_after, optimized.gate_count());
+	assert!(optimized.gate_count() < circuit.gate_count());
+	assert!(optimized.depth() < circuit.depth());
+	for pair in reports.windows(2) {
+		assert_eq!(pair[0].gate_count_after, pair[1].gate_count_before);
+	}
+}
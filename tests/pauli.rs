@@ -0,0 +1,150 @@
+use qurs::circuit::Gate;
+use qurs::pauli::{
+	expectation_value_pauli_whole_list, pauli_gate_whole_list, pauli_rotation_gate_whole_list,
+	Pauli, PauliString,
+};
+use qurs::prelude::*;
+use qurs::{Complex, StateVec};
+
+fn assert_close(a: &StateVec, b: &StateVec) {
+	for (x, y) in a.as_ref().iter().zip(b.as_ref()) {
+		assert!((x - y).norm() < 1e-10, "{} != {}", x, y);
+	}
+}
+
+#[test]
+fn test_pauli_type_validation() {
+	assert_eq!(Pauli::from_type(2).unwrap(), Pauli::Y);
+	assert!(matches!(
+		Pauli::from_type(4),
+		Err(StateErr::InvalidPauliType(4))
+	));
+	assert!(matches!(
+		PauliString::from_type_list(&[0, 1], &[1]),
+		Err(StateErr::InconsistentListLength(2, 1))
+	));
+	assert!(matches!(
+		PauliString::from_type_list(&[0, 1], &[1, 7]),
+		Err(StateErr::InvalidPauliType(7))
+	));
+	assert!(matches!(
+		PauliString::new(&[1, 1], &[Pauli::X, Pauli::Z]),
+		Err(StateErr::InvalidTargetList(_))
+	));
+}
+
+#[test]
+fn test_pauli_string_target_out_of_range() {
+	let mut state = StateVec::new(2);
+	let x = PauliString::new(&[2], &[Pauli::X]).unwrap();
+	assert!(matches!(
+		x.apply(&mut state),
+		Err(StateErr::InvalidTargetQubitIndex(2))
+	));
+	assert!(x.expectation_value(&state).is_err());
+}
+
+#[test]
+fn test_pauli_string_apply() {
+	let mut state = StateVec::new(3);
+	state.set_computational_basis(0b000);
+	let p = PauliString::new(&[0, 2], &[Pauli::X, Pauli::Y]).unwrap();
+	p.apply(&mut state).unwrap();
+	// Y|0> = i|1>
+	let amplitude = state.as_ref()[0b101];
+	assert!((amplitude - Complex::new(0., 1.)).norm() < 1e-12);
+	assert_eq!(p.to_string(), "X0 Y2");
+	assert_eq!(PauliString::default().to_string(), "I");
+	assert_eq!(p.pauli_at(1), Pauli::I);
+}
+
+#[test]
+fn test_pauli_string_rotate_matches_gate() {
+	let p = PauliString::new(&[0, 1, 3], &[Pauli::Z, Pauli::X, Pauli::Y]).unwrap();
+	let angle = 0.7;
+
+	let mut expected = StateVec::new(4);
+	expected.set_haar_random_state_with_seed(3);
+	let mut actual = expected.clone();
+
+	p.rotate(angle, &mut actual).unwrap();
+	p.rotation_gate(angle)
+		.update_quantum_state(expected.as_mut());
+	assert_close(&actual, &expected);
+
+	// exp(-iθ/2 P) = cos(θ/2) - i sin(θ/2) P
+	let mut original = StateVec::new(4);
+	original.set_haar_random_state_with_seed(3);
+	let mut applied = original.clone();
+	p.apply(&mut applied).unwrap();
+	let (c, s) = ((angle / 2.).cos(), (angle / 2.).sin());
+	for ((r, o), a) in actual
+		.as_ref()
+		.iter()
+		.zip(original.as_ref())
+		.zip(applied.as_ref())
+	{
+		let e = o * c - Complex::new(0., s) * a;
+		assert!((r - e).norm() < 1e-10);
+	}
+}
+
+#[test]
+fn test_whole_list() {
+	let paulis = [Pauli::X, Pauli::I, Pauli::Z];
+	let whole = PauliString::from_whole_list(&paulis);
+
+	let mut expected = StateVec::new(3);
+	expected.set_haar_random_state_with_seed(5);
+	let mut actual = expected.clone();
+	let ev = whole.expectation_value(&expected).unwrap();
+	assert!((expectation_value_pauli_whole_list(&paulis, &expected).unwrap() - ev).abs() < 1e-10);
+
+	pauli_gate_whole_list(&paulis, &mut actual).unwrap();
+	whole.apply(&mut expected).unwrap();
+	assert_close(&actual, &expected);
+
+	pauli_rotation_gate_whole_list(&paulis, 0.3, &mut actual).unwrap();
+	Gate::PauliRotation {
+		targets: vec![0, 1, 2],
+		paulis: vec![1, 0, 3],
+		angle: 0.3,
+	}
+	.update_quantum_state(expected.as_mut());
+	assert_close(&actual, &expected);
+
+	assert!(matches!(
+		pauli_gate_whole_list(&paulis[..2], &mut actual),
+		Err(StateErr::InconsistentListLength(3, 2))
+	));
+}
+
+#[test]
+#[should_panic]
+fn test_partial_list_rejects_invalid_type() {
+	let mut state = StateVec::new(2);
+	qurs::multi_qubit_pauli_rotation_gate_partial_list(&[0], &[5], 0.1, &mut state);
+}
+
+#[test]
+fn test_pauli_string_product() {
+	let a = PauliString::new(&[2, 0], &[Pauli::X, Pauli::Z]).unwrap();
+	let b = PauliString::new(&[0, 1, 2], &[Pauli::X, Pauli::Z, Pauli::Y]).unwrap();
+	let (phase, product) = a.product(&b);
+	// ZX = iY, XY = iZ
+	assert_eq!(phase, Complex::new(-1., 0.));
+	assert_eq!(
+		product,
+		PauliString::new(&[0, 1, 2], &[Pauli::Y, Pauli::Z, Pauli::Z]).unwrap()
+	);
+	let (phase, product) = a.product(&a);
+	assert_eq!(phase, Complex::new(1., 0.));
+	assert!(product.targets().is_empty());
+}
+
+#[test]
+#[should_panic]
+fn test_partial_list_rejects_repeated_target() {
+	let state = StateVec::new(2);
+	qurs::expectation_value_multi_qubit_pauli_operator_partial_list(&[1, 1], &[3, 3], &state);
+}
@@ -0,0 +1,97 @@
+use num::{Complex, Zero};
+use qurs::qaoa::{diagonal_expectation_value, Qaoa};
+use qurs::{self, expectation_value_multi_qubit_pauli_operator_partial_list, StateVec};
+use std::f64::consts::PI;
+
+const EPS: f64 = 1e-10;
+
+fn ring(n: u32) -> Vec<(u32, u32, f64)> {
+	(0..n).map(|i| (i, (i + 1) % n, 1.)).collect()
+}
+
+#[test]
+fn maxcut_cost_table() {
+	let qaoa = Qaoa::maxcut(4, &ring(4));
+	assert_eq!(qaoa.cost_table()[0b0101], 4.);
+	assert_eq!(qaoa.cost_table()[0b0011], 2.);
+	assert_eq!(qaoa.optimum().1, 4.);
+}
+
+#[test]
+fn maxcut_ring_p1() {
+	// For a ring, <C_edge> = 1/2 + sin(4β)sin(2γ)/4 at p = 1, so the
+	// approximation ratio reaches 3/4 at γ = π/4, β = π/8.
+	let n = 6;
+	let qaoa = Qaoa::maxcut(n, &ring(n as u32));
+	for &(gamma, beta) in &[(PI / 4., PI / 8.), (0.3, 0.2), (1.1, -0.4)] {
+		let mut state = StateVec::new(n);
+		qaoa.circuit(&[(gamma, beta)])
+			.update_quantum_state(state.as_mut())
+			.unwrap();
+		let expected = n as f64 * (0.5 + (4. * beta).sin() * (2. * gamma).sin() / 4.);
+		let actual = qaoa.expectation_value(state.as_ref()).unwrap();
+		assert!(
+			(actual - expected).abs() < EPS,
+			"{} != {}",
+			actual,
+			expected
+		);
+	}
+
+	let mut state = StateVec::new(n);
+	qaoa.circuit(&[(PI / 4., PI / 8.)])
+		.update_quantum_state(state.as_mut())
+		.unwrap();
+	assert!((qaoa.approximation_ratio(state.as_ref()).unwrap() - 0.75).abs() < EPS);
+}
+
+#[test]
+fn approximation_ratio_without_edges() {
+	let qaoa = Qaoa::maxcut(2, &[]);
+	let state = StateVec::new(2);
+	assert!(qaoa.approximation_ratio(state.as_ref()).unwrap().is_nan());
+}
+
+#[test]
+fn cost_fn_matches_maxcut() {
+	let edges = [
+		(0, 1, 0.5),
+		(1, 2, 1.5),
+		(2, 3, 1.),
+		(0, 3, 2.),
+		(0, 2, 0.7),
+	];
+	let maxcut = Qaoa::maxcut(4, &edges);
+	let general = Qaoa::from_cost_fn(4, |z| maxcut.cost_table()[z]);
+	let layers = [(0.4, 0.7), (-0.2, 0.3)];
+
+	let mut a = StateVec::new(4);
+	let mut b = StateVec::new(4);
+	maxcut
+		.circuit(&layers)
+		.update_quantum_state(a.as_mut())
+		.unwrap();
+	general
+		.circuit(&layers)
+		.update_quantum_state(b.as_mut())
+		.unwrap();
+	// The constant term only changes the global phase.
+	let overlap = qurs::inner_product(a.as_ref(), b.as_ref()).unwrap();
+	assert!((overlap.norm() - 1.).abs() < EPS);
+}
+
+#[test]
+fn diagonal_expectation_matches_pauli() {
+	let mut state = [Complex::zero(); 8];
+	qurs::prelude::StateMut::set_haar_random_state_with_seed(&mut state, 3);
+	// C = Z_0 Z_2
+	let diagonal = diagonal_expectation_value(&state, |z| {
+		if (z & 1) ^ ((z >> 2) & 1) == 0 {
+			1.
+		} else {
+			-1.
+		}
+	});
+	let pauli = expectation_value_multi_qubit_pauli_operator_partial_list(&[0, 2], &[3, 3], &state);
+	assert!((diagonal - pauli).abs() < EPS);
+}
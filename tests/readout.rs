@@ -0,0 +1,183 @@
+use qurs::circuit::{Circuit, Gate};
+use qurs::readout::{Calibration, ReadoutErr, ReadoutMitigation, ReadoutNoise};
+use qurs::StateVec;
+
+fn test_state(qubit_count: usize) -> StateVec {
+	let mut circuit = Circuit::new(qubit_count);
+	for q in 0..qubit_count as u32 {
+		circuit.add_gate(Gate::Ry(q, 0.5 + 0.6 * q as f64));
+	}
+	for q in 1..qubit_count as u32 {
+		circuit.add_gate(Gate::Cnot(q - 1, q));
+	}
+	let mut state = StateVec::new(qubit_count);
+	circuit.update_quantum_state(state.as_mut()).unwrap();
+	state
+}
+
+fn test_noise() -> ReadoutNoise {
+	let mut noise = ReadoutNoise::new(3);
+	noise.add_qubit_error(0, 0.05, 0.1).unwrap();
+	// |11> on qubits 2 and 1 decays to |01> or |00>
+	#[rustfmt::skip]
+	let matrix = vec![
+		0.97, 0.05, 0.04, 0.10,
+		0.01, 0.93, 0.00, 0.15,
+		0.02, 0.00, 0.94, 0.05,
+		0.00, 0.02, 0.02, 0.70,
+	];
+	noise.add_correlated_error(&[2, 1], matrix).unwrap();
+	noise
+}
+
+fn probabilities(state: &StateVec) -> Vec<f64> {
+	state.as_ref().iter().map(|a| a.norm_sqr()).collect()
+}
+
+fn max_distance(a: &[f64], b: &[f64]) -> f64 {
+	a.iter()
+		.zip(b)
+		.map(|(x, y)| (x - y).abs())
+		.fold(0., f64::max)
+}
+
+fn calibrate(noise: &ReadoutNoise, calibration: Calibration, shots: u32) -> ReadoutMitigation {
+	let samples: Vec<Vec<u64>> = calibration
+		.circuits(noise.qubit_count())
+		.iter()
+		.map(|circuit| {
+			let mut state = StateVec::new(noise.qubit_count());
+			circuit.update_quantum_state(state.as_mut()).unwrap();
+			noise.sampling(&state, shots)
+		})
+		.collect();
+	ReadoutMitigation::from_calibration(calibration, noise.qubit_count(), &samples).unwrap()
+}
+
+#[test]
+fn test_noise_matrix() {
+	let noise = test_noise();
+	let matrix = noise.matrix();
+	for column in 0..8 {
+		let sum: f64 = (0..8).map(|row| matrix[row * 8 + column]).sum();
+		assert!((sum - 1.).abs() < 1e-12);
+	}
+	// prepared 0b110 is |11> on qubits 2 and 1, and |0> on qubit 0
+	assert!((matrix[0b110] - 0.95 * 0.10).abs() < 1e-12);
+	assert!((matrix[0b101 * 8 + 0b110] - 0.05 * 0.15).abs() < 1e-12);
+	assert!((matrix[0b111 * 8 + 0b111] - 0.9 * 0.70).abs() < 1e-12);
+}
+
+#[test]
+fn test_noise_sampling() {
+	let noise = test_noise();
+	let state = test_state(3);
+	let ideal = probabilities(&state);
+	let matrix = noise.matrix();
+	let expected: Vec<f64> = (0..8)
+		.map(|row| {
+			(0..8)
+				.map(|column| matrix[row * 8 + column] * ideal[column])
+				.sum()
+		})
+		.collect();
+	let shots = 50000;
+	let mut frequency = vec![0.; 8];
+	for outcome in noise.sampling(&state, shots) {
+		frequency[outcome as usize] += 1. / shots as f64;
+	}
+	assert!(max_distance(&frequency, &expected) < 0.01);
+}
+
+#[test]
+fn test_invalid_noise() {
+	let mut noise = ReadoutNoise::new(2);
+	noise.add_qubit_error(1, 0.1, 0.1).unwrap();
+	assert!(matches!(
+		noise.add_qubit_error(2, 0.1, 0.1),
+		Err(ReadoutErr::InvalidQubit(2))
+	));
+	assert!(matches!(
+		noise.add_qubit_error(1, 0.1, 0.1),
+		Err(ReadoutErr::DuplicateQubit(1))
+	));
+	assert!(matches!(
+		noise.add_correlated_error(
+			&[0, 0],
+			vec![1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.]
+		),
+		Err(ReadoutErr::DuplicateQubit(0))
+	));
+	assert!(matches!(
+		noise.add_qubit_error(0, 1.5, 0.1),
+		Err(ReadoutErr::InvalidConfusionMatrix(_))
+	));
+	assert!(matches!(
+		noise.add_correlated_error(&[0], vec![1., 0.]),
+		Err(ReadoutErr::InvalidConfusionMatrix(_))
+	));
+	noise.add_qubit_error(0, 0.5, 0.5).unwrap();
+	assert!(matches!(
+		ReadoutMitigation::from_noise(&noise),
+		Err(ReadoutErr::SingularConfusionMatrix)
+	));
+}
+
+#[test]
+fn test_full_mitigation() {
+	let noise = test_noise();
+	let state = test_state(3);
+	let ideal = probabilities(&state);
+	let mitigation = calibrate(&noise, Calibration::Full, 50000);
+	assert!(max_distance(&mitigation.matrix(), &noise.matrix()) < 0.01);
+	let samples = noise.sampling(&state, 50000);
+	let inverted = mitigation.invert(&samples).unwrap();
+	assert!((inverted.iter().sum::<f64>() - 1.).abs() < 1e-10);
+	assert!(
+		max_distance(&inverted, &ideal) < 0.02,
+		"{:?} {:?}",
+		inverted,
+		ideal
+	);
+	let unfolded = mitigation
+		.iterative_bayesian_unfolding(&samples, 1000, 1e-10)
+		.unwrap();
+	assert!((unfolded.iter().sum::<f64>() - 1.).abs() < 1e-10);
+	assert!(unfolded.iter().all(|&p| p >= 0.));
+	assert!(
+		max_distance(&unfolded, &ideal) < 0.02,
+		"{:?} {:?}",
+		unfolded,
+		ideal
+	);
+	// the tensored calibration misses the correlation
+	let tensored = calibrate(&noise, Calibration::Tensored, 50000);
+	assert!(max_distance(&tensored.invert(&samples).unwrap(), &ideal) > 0.02);
+}
+
+#[test]
+fn test_tensored_mitigation() {
+	let mut noise = ReadoutNoise::new(4);
+	for q in 0..4 {
+		noise
+			.add_qubit_error(q, 0.02 + 0.01 * q as f64, 0.08 - 0.01 * q as f64)
+			.unwrap();
+	}
+	let state = test_state(4);
+	let ideal = probabilities(&state);
+	let mitigation = calibrate(&noise, Calibration::Tensored, 50000);
+	assert_eq!(Calibration::Tensored.circuits(4).len(), 2);
+	assert!(max_distance(&mitigation.matrix(), &noise.matrix()) < 0.01);
+	let samples = noise.sampling(&state, 50000);
+	let inverted = mitigation.invert(&samples).unwrap();
+	assert!(max_distance(&inverted, &ideal) < 0.02);
+	let exact = ReadoutMitigation::from_noise(&noise).unwrap();
+	let unfolded = exact
+		.iterative_bayesian_unfolding(&samples, 1000, 1e-10)
+		.unwrap();
+	assert!(max_distance(&unfolded, &ideal) < 0.02);
+	assert!(matches!(
+		ReadoutMitigation::from_calibration(Calibration::Full, 4, &[vec![0]]),
+		Err(ReadoutErr::InconsistentDataLength(16, 1))
+	));
+}
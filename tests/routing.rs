@@ -0,0 +1,99 @@
+use qurs::circuit::{Circuit, Gate};
+use qurs::permutate_qubit;
+use qurs::prelude::*;
+use qurs::routing::{route, CouplingMap};
+use qurs::StateVec;
+use rand::{Rng, SeedableRng};
+
+fn random_circuit(qubit_count: u32, gate_count: usize, seed: u64) -> Circuit {
+	let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+	let mut circuit = Circuit::new(qubit_count as usize);
+	for _ in 0..gate_count {
+		let a = rng.gen_range(0..qubit_count);
+		let mut b = rng.gen_range(0..qubit_count - 1);
+		if b >= a {
+			b += 1;
+		}
+		let gate = match rng.gen_range(0..5) {
+			0 => Gate::H(a),
+			1 => Gate::Ry(a, rng.gen_range(-3.0..3.0)),
+			2 => Gate::Cnot(a, b),
+			3 => Gate::Cz(a, b),
+			_ => Gate::Swap(a, b),
+		};
+		circuit.add_gate(gate);
+	}
+	circuit
+}
+
+fn inverse(layout: &[u32]) -> Vec<u32> {
+	let mut inverse = vec![0; layout.len()];
+	for (l, &p) in layout.iter().enumerate() {
+		inverse[p as usize] = l as u32;
+	}
+	inverse
+}
+
+fn check_routing(circuit: &Circuit, coupling_map: &CouplingMap, initial_layout: &[u32]) {
+	let routed = route(circuit, coupling_map, initial_layout).unwrap();
+	for gate in routed.circuit().gates() {
+		if let [a, b] = gate.qubits()[..] {
+			assert!(coupling_map.is_adjacent(a, b), "{:?}", gate);
+		}
+	}
+
+	let physical_count = coupling_map.qubit_count();
+	let mut logical_circuit = Circuit::new(physical_count);
+	logical_circuit.merge_circuit(circuit);
+	let mut expected = StateVec::new(physical_count);
+	expected.set_haar_random_state_with_seed(3);
+	let input = expected.clone();
+	logical_circuit
+		.update_quantum_state(expected.as_mut())
+		.unwrap();
+
+	let mut actual = permutate_qubit(input, &inverse(routed.initial_layout())).unwrap();
+	routed
+		.circuit()
+		.update_quantum_state(actual.as_mut())
+		.unwrap();
+	let actual = permutate_qubit(actual, routed.final_layout()).unwrap();
+	for (a, b) in actual.as_ref().iter().zip(expected.as_ref()) {
+		assert!((a - b).norm() < 1e-10);
+	}
+}
+
+#[test]
+fn routed_circuit_matches_original() {
+	for seed in 0..5 {
+		let circuit = random_circuit(5, 40, seed);
+		check_routing(&circuit, &CouplingMap::line(5), &[0, 1, 2, 3, 4]);
+		check_routing(&circuit, &CouplingMap::line(5), &[3, 0, 4, 1, 2]);
+		check_routing(&circuit, &CouplingMap::grid(2, 3), &[5, 1, 0, 2, 4]);
+	}
+}
+
+#[test]
+fn adjacent_gates_need_no_swap() {
+	let mut circuit = Circuit::new(4);
+	circuit.add_gate(Gate::Cnot(0, 1));
+	circuit.add_gate(Gate::Cz(2, 1));
+	circuit.add_gate(Gate::Cnot(3, 2));
+	let routed = route(&circuit, &CouplingMap::line(4), &[0, 1, 2, 3]).unwrap();
+	assert_eq!(routed.swap_count(), 0);
+	assert_eq!(routed.final_layout(), &[0, 1, 2, 3]);
+}
+
+#[test]
+fn routing_errors() {
+	assert!(CouplingMap::new(3, &[(0, 1)]).is_err());
+	assert!(CouplingMap::new(2, &[(0, 2)]).is_err());
+	let mut circuit = Circuit::new(3);
+	circuit.add_gate(Gate::Ccnot(0, 1, 2));
+	let map = CouplingMap::line(3);
+	assert!(route(&circuit, &map, &[0, 1, 2]).is_err());
+	let circuit = Circuit::new(3);
+	assert!(route(&circuit, &map, &[0, 1, 1]).is_err());
+	assert!(route(&circuit, &map, &[0, 1]).is_err());
+	assert!(route(&Circuit::new(4), &map, &[0, 1, 2, 3]).is_err());
+}
@@ -0,0 +1,219 @@
+use qurs::circuit::{Circuit, Gate};
+use qurs::observable::Observable;
+use qurs::pauli::{Pauli, PauliString};
+use qurs::prelude::*;
+use qurs::shadow::{ClassicalShadow, ShadowEnsemble, Snapshot};
+use qurs::{Complex, StateVec};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Seed of the statistical tests, so that they pass or fail the same way on
+/// every run.
+const SEED: u64 = 2020;
+
+fn bell_state(qubit_count: usize) -> StateVec {
+	let mut circuit = Circuit::new(qubit_count);
+	circuit.add_gate(Gate::H(0));
+	circuit.add_gate(Gate::Cnot(0, 1));
+	for q in 2..qubit_count as u32 {
+		circuit.add_gate(Gate::Ry(q, 0.7));
+	}
+	let mut state = StateVec::new(qubit_count);
+	circuit.update_quantum_state(state.as_mut()).unwrap();
+	state
+}
+
+/// Get a random circuit of the Clifford ensemble.
+fn random_clifford_circuit(qubit_count: usize) -> Circuit {
+	let state = StateVec::new(qubit_count);
+	let shadow = ClassicalShadow::sample(state.as_ref(), ShadowEnsemble::Clifford, 1).unwrap();
+	match &shadow.snapshots()[0] {
+		Snapshot::Clifford { circuit, .. } => circuit.clone(),
+		_ => unreachable!(),
+	}
+}
+
+#[test]
+fn test_clifford_snapshot() {
+	let qubit_count = 3;
+	let paulis = [
+		PauliString::new(&[0], &[Pauli::X]).unwrap(),
+		PauliString::new(&[0, 2], &[Pauli::Y, Pauli::Z]).unwrap(),
+		PauliString::new(&[0, 1, 2], &[Pauli::X, Pauli::Y, Pauli::Y]).unwrap(),
+	];
+	for _ in 0..4 {
+		let circuit = random_clifford_circuit(qubit_count);
+		for outcome in 0..1 << qubit_count {
+			let mut state = StateVec::new(qubit_count);
+			state.set_computational_basis(outcome);
+			circuit
+				.inverse()
+				.update_quantum_state(state.as_mut())
+				.unwrap();
+			let snapshot = Snapshot::Clifford {
+				circuit: circuit.clone(),
+				outcome: outcome as u64,
+			};
+			let shadow =
+				ClassicalShadow::from_snapshots(qubit_count, vec![snapshot.clone(), snapshot]);
+			for pauli in &paulis {
+				let expected = 9. * pauli.expectation_value(&state).unwrap();
+				let value = shadow.pauli_expectation_value(pauli, 2).value;
+				assert!((value - expected).abs() < 1e-9, "{} {}", value, expected);
+			}
+		}
+	}
+}
+
+#[test]
+fn test_expectation_value() {
+	let state = bell_state(3);
+	let mut observable = Observable::new(3);
+	observable.add_term(
+		Complex::new(0.5, 0.),
+		PauliString::new(&[0, 1], &[Pauli::X, Pauli::X]).unwrap(),
+	);
+	observable.add_term(
+		Complex::new(-1., 0.),
+		PauliString::new(&[2], &[Pauli::Z]).unwrap(),
+	);
+	observable.add_term(
+		Complex::new(0.3, 0.),
+		PauliString::new(&[1, 2], &[Pauli::Z, Pauli::X]).unwrap(),
+	);
+	let exact = observable.expectation_value(&state).unwrap().re;
+	let zz = PauliString::new(&[0, 1], &[Pauli::Z, Pauli::Z]).unwrap();
+	let mut rng = StdRng::seed_from_u64(SEED);
+	for &ensemble in &[ShadowEnsemble::Pauli, ShadowEnsemble::Clifford] {
+		let shadow =
+			ClassicalShadow::sample_with_rng(state.as_ref(), ensemble, 6000, &mut rng).unwrap();
+		let estimate = shadow.expectation_value(&observable, 10);
+		assert!(
+			(estimate.value - exact).abs() < 0.2,
+			"{:?} {:?} {}",
+			ensemble,
+			estimate,
+			exact
+		);
+		assert!(estimate.error > 0.);
+		assert!((estimate.failure_probability - 2. * (-5f64).exp()).abs() < 1e-12);
+		let estimate = shadow.pauli_expectation_value(&zz, 10);
+		assert!(
+			(estimate.value - 1.).abs() < 0.2,
+			"{:?} {:?}",
+			ensemble,
+			estimate
+		);
+	}
+}
+
+#[test]
+fn test_fidelity() {
+	let state = bell_state(3);
+	let mut other = StateVec::new(3);
+	other.set_computational_basis(0);
+	let overlap = state.as_ref()[0].norm_sqr();
+	let mut rng = StdRng::seed_from_u64(SEED);
+	for &ensemble in &[ShadowEnsemble::Pauli, ShadowEnsemble::Clifford] {
+		let shadow =
+			ClassicalShadow::sample_with_rng(state.as_ref(), ensemble, 6000, &mut rng).unwrap();
+		let estimate = shadow.fidelity(state.as_ref(), 10);
+		assert!(
+			(estimate.value - 1.).abs() < 0.2,
+			"{:?} {:?}",
+			ensemble,
+			estimate
+		);
+		let estimate = shadow.fidelity(other.as_ref(), 10);
+		assert!(
+			(estimate.value - overlap).abs() < 0.2,
+			"{:?} {:?}",
+			ensemble,
+			estimate
+		);
+	}
+}
+
+#[test]
+fn test_purity() {
+	let state = bell_state(3);
+	let mut rng = StdRng::seed_from_u64(SEED);
+	for &ensemble in &[ShadowEnsemble::Pauli, ShadowEnsemble::Clifford] {
+		let shadow =
+			ClassicalShadow::sample_with_rng(state.as_ref(), ensemble, 4000, &mut rng).unwrap();
+		for &(subsystem, expected) in &[(&[0][..], 0.5), (&[0, 1][..], 1.), (&[2][..], 1.)] {
+			let estimate = shadow.purity(subsystem, 8);
+			assert!(
+				(estimate.value - expected).abs() < 0.25,
+				"{:?} {:?} {:?}",
+				ensemble,
+				subsystem,
+				estimate
+			);
+		}
+	}
+}
+
+#[test]
+fn test_purity_of_combined_ensembles() {
+	let state = bell_state(2);
+	let snapshot = |ensemble| {
+		ClassicalShadow::sample(state.as_ref(), ensemble, 1)
+			.unwrap()
+			.snapshots()[0]
+			.clone()
+	};
+	let (pauli, clifford) = (
+		snapshot(ShadowEnsemble::Pauli),
+		snapshot(ShadowEnsemble::Clifford),
+	);
+	let purity = |snapshots: Vec<Snapshot>| {
+		ClassicalShadow::from_snapshots(2, snapshots)
+			.purity(&[0], 2)
+			.value
+	};
+	let pauli_only = purity(vec![pauli.clone(); 4]);
+	let clifford_only = purity(vec![clifford.clone(); 4]);
+	// each batch holds snapshots of one ensemble
+	let mut combined = vec![pauli; 4];
+	combined.extend(vec![clifford; 4]);
+	let value = purity(combined);
+	assert!((value - (pauli_only + clifford_only) / 2.).abs() < 1e-10);
+}
+
+#[test]
+fn test_sample_rejects_invalid_state() {
+	for len in [0, 3] {
+		let state = vec![Complex::new(0., 0.); len];
+		assert!(ClassicalShadow::sample(&state, ShadowEnsemble::Pauli, 1).is_err());
+	}
+}
+
+#[test]
+fn test_median_of_means_uses_every_snapshot() {
+	let snapshot = |outcome| Snapshot::Pauli {
+		bases: vec![Pauli::Z],
+		outcome,
+	};
+	// tr(ρ̂ Z) is 3 for outcome 0 and -3 for outcome 1, so the batches 3, 3 and
+	// 3, -3, -3 have the means 3 and -1
+	let snapshots = [0, 0, 0, 1, 1].iter().map(|&o| snapshot(o)).collect();
+	let shadow = ClassicalShadow::from_snapshots(1, snapshots);
+	let z = PauliString::new(&[0], &[Pauli::Z]).unwrap();
+	assert!((shadow.pauli_expectation_value(&z, 2).value - 1.).abs() < 1e-12);
+}
+
+#[test]
+fn test_seeded_sampling_is_reproducible() {
+	let state = bell_state(2);
+	for &ensemble in &[ShadowEnsemble::Pauli, ShadowEnsemble::Clifford] {
+		let sample = |seed| {
+			let mut rng = StdRng::seed_from_u64(seed);
+			ClassicalShadow::sample_with_rng(state.as_ref(), ensemble, 20, &mut rng)
+				.unwrap()
+				.snapshots()
+				.to_vec()
+		};
+		assert_eq!(sample(1), sample(1));
+	}
+}
@@ -0,0 +1,134 @@
+use qurs::circuit::{equivalent, Circuit, Gate};
+use qurs::inner_product;
+use qurs::prelude::*;
+use qurs::synthesis::{decompose_two_qubit_unitary, kak_decomposition, prepare_state};
+use qurs::{Complex, StateVec};
+use rand::SeedableRng;
+
+mod common;
+use common::haar_random_unitary;
+
+fn fidelity_of_prepared(target: &[Complex<f64>]) -> f64 {
+	let circuit = prepare_state(target);
+	let mut state = StateVec::new(circuit.qubit_count());
+	circuit.update_quantum_state(state.as_mut()).unwrap();
+	inner_product(target, state.as_ref()).unwrap().norm_sqr()
+}
+
+#[test]
+fn prepare_haar_random_states() {
+	for qubit_count in 1..6 {
+		let mut target = StateVec::new(qubit_count);
+		target.set_haar_random_state_with_seed(qubit_count as u32);
+		let fidelity = fidelity_of_prepared(target.as_ref());
+		assert!((fidelity - 1.).abs() < 1e-10, "{}", fidelity);
+	}
+}
+
+#[test]
+fn prepare_sparse_states() {
+	let zero = Complex::new(0., 0.);
+	let mut target = vec![zero; 16];
+	target[5] = Complex::new(0., -1.);
+	assert!((fidelity_of_prepared(&target) - 1.).abs() < 1e-10);
+
+	let h = 0.5;
+	let target = [
+		Complex::new(h, 0.),
+		zero,
+		zero,
+		Complex::new(0., h),
+		zero,
+		Complex::new(-h, 0.),
+		Complex::new(0., -h),
+		zero,
+	];
+	assert!((fidelity_of_prepared(&target) - 1.).abs() < 1e-10);
+}
+
+#[test]
+#[should_panic]
+fn prepare_state_rejects_invalid_length() {
+	prepare_state(&[Complex::new(1., 0.); 3]);
+}
+
+#[test]
+fn kak_decomposition_of_haar_random_unitaries() {
+	let mut rng = rand::rngs::StdRng::seed_from_u64(13);
+	for _ in 0..100 {
+		let matrix = haar_random_unitary(4, &mut rng);
+		let mut expected = Circuit::new(3);
+		expected.add_gate(Gate::DenseMatrix {
+			targets: vec![2, 0],
+			matrix: matrix.clone(),
+		});
+		let gates = decompose_two_qubit_unitary(&[2, 0], &matrix);
+		assert!(gates.iter().filter(|g| matches!(g, Gate::Cnot(..))).count() <= 3);
+		let mut actual = Circuit::new(3);
+		for gate in gates {
+			assert!(matches!(
+				gate,
+				Gate::Cnot(..) | Gate::Rx(..) | Gate::Ry(..) | Gate::Rz(..)
+			));
+			actual.add_gate(gate);
+		}
+		assert!(equivalent(&expected, &actual, true));
+
+		let kak = kak_decomposition(&matrix);
+		let mut reconstructed = Circuit::new(2);
+		for (q, m) in kak.before.iter().enumerate() {
+			reconstructed.add_gate(Gate::DenseMatrix {
+				targets: vec![q as u32],
+				matrix: m.to_vec(),
+			});
+		}
+		let [a, b, c] = kak.interaction;
+		for (paulis, angle) in [([1, 1], a), ([2, 2], b), ([3, 3], c)] {
+			reconstructed.add_gate(Gate::PauliRotation {
+				targets: vec![0, 1],
+				paulis: paulis.to_vec(),
+				angle: -2. * angle,
+			});
+		}
+		for (q, m) in kak.after.iter().enumerate() {
+			reconstructed.add_gate(Gate::DenseMatrix {
+				targets: vec![q as u32],
+				matrix: m.to_vec(),
+			});
+		}
+		let phase = Complex::from_polar(1., kak.global_phase);
+		for (x, y) in reconstructed.to_unitary().iter().zip(&matrix) {
+			assert!((x * phase - y).norm() < 1e-8);
+		}
+	}
+}
+
+#[test]
+fn two_qubit_decomposition_of_special_gates() {
+	let gates = [
+		Gate::Cnot(0, 1),
+		Gate::Cz(1, 0),
+		Gate::Swap(0, 1),
+		Gate::PauliRotation {
+			targets: vec![0, 1],
+			paulis: vec![1, 2],
+			angle: 0.4,
+		},
+	];
+	for gate in &gates {
+		let mut circuit = Circuit::new(2);
+		circuit.add_gate(gate.clone());
+		circuit.add_gate(Gate::H(0));
+		let mut decomposed = Circuit::new(2);
+		for gate in decompose_two_qubit_unitary(&[0, 1], &circuit.to_unitary()) {
+			decomposed.add_gate(gate);
+		}
+		assert!(equivalent(&circuit, &decomposed, true), "{:?}", gate);
+	}
+
+	let mut local = Circuit::new(2);
+	local.add_gate(Gate::H(0));
+	local.add_gate(Gate::Ry(1, 0.3));
+	let gates = decompose_two_qubit_unitary(&[0, 1], &local.to_unitary());
+	assert!(gates.iter().all(|g| g.qubits().len() == 1));
+}
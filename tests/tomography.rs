@@ -0,0 +1,153 @@
+use qurs::circuit::{Circuit, Gate};
+use qurs::tomography::{
+	choi_to_pauli_transfer_matrix, unitary_choi, ProcessTomography, StateTomography,
+};
+use qurs::{Complex, StateVec};
+
+fn test_circuit(qubit_count: usize) -> Circuit {
+	let mut circuit = Circuit::new(qubit_count);
+	for q in 0..qubit_count as u32 {
+		circuit.add_gate(Gate::Ry(q, 0.4 + 0.3 * q as f64));
+		circuit.add_gate(Gate::Rz(q, 1.1 - 0.2 * q as f64));
+	}
+	for q in 1..qubit_count as u32 {
+		circuit.add_gate(Gate::Cnot(q - 1, q));
+	}
+	circuit
+}
+
+fn density_matrix(state: &[Complex<f64>]) -> Vec<Complex<f64>> {
+	let mut rho = Vec::with_capacity(state.len() * state.len());
+	for a in state {
+		for b in state {
+			rho.push(a * b.conj());
+		}
+	}
+	rho
+}
+
+fn max_distance(a: &[Complex<f64>], b: &[Complex<f64>]) -> f64 {
+	assert_eq!(a.len(), b.len());
+	a.iter()
+		.zip(b)
+		.map(|(x, y)| (x - y).norm())
+		.fold(0., f64::max)
+}
+
+fn fidelity(rho: &[Complex<f64>], state: &[Complex<f64>]) -> f64 {
+	let dim = state.len();
+	let mut sum = Complex::new(0., 0.);
+	for r in 0..dim {
+		for c in 0..dim {
+			sum += state[r].conj() * rho[r * dim + c] * state[c];
+		}
+	}
+	sum.re
+}
+
+#[test]
+fn test_state_tomography() {
+	for qubit_count in 1..=3 {
+		let mut state = StateVec::new(qubit_count);
+		test_circuit(qubit_count)
+			.update_quantum_state(state.as_mut())
+			.unwrap();
+		let expected = density_matrix(state.as_ref());
+		let tomography = StateTomography::new(qubit_count);
+		assert_eq!(tomography.bases().len(), 3usize.pow(qubit_count as u32));
+		let samples = tomography.sample(state.as_ref(), 4000).unwrap();
+		let dim = 1 << qubit_count;
+		for rho in [
+			tomography.linear_inversion(&samples).unwrap(),
+			tomography.maximum_likelihood(&samples, 1000, 1e-8).unwrap(),
+		] {
+			let trace: Complex<f64> = (0..dim).map(|i| rho[i * dim + i]).sum();
+			assert!((trace - 1.).norm() < 1e-10);
+			for r in 0..dim {
+				for c in 0..dim {
+					assert!((rho[r * dim + c] - rho[c * dim + r].conj()).norm() < 1e-10);
+				}
+			}
+			assert!(
+				max_distance(&rho, &expected) < 0.05,
+				"{}",
+				max_distance(&rho, &expected)
+			);
+			assert!(fidelity(&rho, state.as_ref()) > 0.95);
+		}
+	}
+}
+
+#[test]
+fn test_maximum_likelihood_is_physical() {
+	// few shots of a pure state make the linear inversion unphysical
+	let mut state = StateVec::new(2);
+	test_circuit(2)
+		.update_quantum_state(state.as_mut())
+		.unwrap();
+	let tomography = StateTomography::new(2);
+	let samples = tomography.sample(state.as_ref(), 20).unwrap();
+	let rho = tomography
+		.maximum_likelihood(&samples, 2000, 1e-10)
+		.unwrap();
+	// <v|ρ|v> >= 0 for random vectors v
+	for seed in 0..20 {
+		let v: Vec<Complex<f64>> = (0..4)
+			.map(|i| {
+				Complex::new(
+					((seed * 7 + i * 3) % 5) as f64 - 2.,
+					((seed + i * 11) % 7) as f64 - 3.,
+				)
+			})
+			.collect();
+		assert!(fidelity(&rho, &v) > -1e-10);
+	}
+}
+
+#[test]
+fn test_process_tomography() {
+	for qubit_count in 1..=2 {
+		let circuit = test_circuit(qubit_count);
+		let tomography = ProcessTomography::new(qubit_count);
+		assert_eq!(
+			tomography.preparations().len(),
+			4usize.pow(qubit_count as u32)
+		);
+		let samples = tomography.sample(&circuit, 4000).unwrap();
+		let choi = tomography.choi(&samples).unwrap();
+		let expected = unitary_choi(qubit_count, &circuit.to_unitary());
+		assert!(
+			max_distance(&choi, &expected) < 0.1,
+			"{}",
+			max_distance(&choi, &expected)
+		);
+		let ptm = tomography.pauli_transfer_matrix(&samples).unwrap();
+		let expected = choi_to_pauli_transfer_matrix(qubit_count, &expected);
+		let distance = ptm
+			.iter()
+			.zip(&expected)
+			.map(|(a, b)| (a - b).abs())
+			.fold(0., f64::max);
+		assert!(distance < 0.1, "{}", distance);
+	}
+}
+
+#[test]
+fn test_pauli_transfer_matrix() {
+	let mut circuit = Circuit::new(2);
+	circuit.add_gate(Gate::Cnot(0, 1));
+	let ptm = choi_to_pauli_transfer_matrix(2, &unitary_choi(2, &circuit.to_unitary()));
+	// CNOT maps X0 to X0 X1, Z1 to Z0 Z1 and Y0 Y1 to -X0 Z1
+	let index = |p0: usize, p1: usize| p0 + 4 * p1;
+	for (input, output, sign) in [
+		(index(1, 0), index(1, 1), 1.),
+		(index(0, 3), index(3, 3), 1.),
+		(index(2, 2), index(1, 3), -1.),
+		(index(3, 0), index(3, 0), 1.),
+	] {
+		for row in 0..16 {
+			let expected = if row == output { sign } else { 0. };
+			assert!((ptm[row * 16 + input] - expected).abs() < 1e-10);
+		}
+	}
+}
@@ -0,0 +1,163 @@
+use qurs::circuit::{equivalent, Circuit, Gate};
+use qurs::gate::ControlValue;
+use qurs::synthesis::{euler_zyz, euler_zyz_matrix};
+use qurs::transpiler::{transpile, Basis};
+use qurs::Complex;
+use rand::{Rng, SeedableRng};
+
+mod common;
+use common::haar_random_unitary;
+
+fn random_unitary(rng: &mut impl Rng) -> [Complex<f64>; 4] {
+	let mut circuit = Circuit::new(1);
+	circuit.add_gate(Gate::Rz(0, rng.gen_range(-3.0..3.0)));
+	circuit.add_gate(Gate::Ry(0, rng.gen_range(-3.0..3.0)));
+	circuit.add_gate(Gate::Rx(0, rng.gen_range(-3.0..3.0)));
+	let u = circuit.to_unitary();
+	let phase = Complex::from_polar(1., rng.gen_range(-3.0..3.0));
+	[u[0] * phase, u[1] * phase, u[2] * phase, u[3] * phase]
+}
+
+#[test]
+fn euler_zyz_roundtrip() {
+	let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+	for _ in 0..50 {
+		let u = random_unitary(&mut rng);
+		let rebuilt = euler_zyz_matrix(&euler_zyz(&u));
+		for (a, b) in u.iter().zip(&rebuilt) {
+			assert!((a - b).norm() < 1e-10);
+		}
+	}
+	let h = Gate::H(0);
+	let mut circuit = Circuit::new(1);
+	circuit.add_gate(h);
+	let u = circuit.to_unitary();
+	let rebuilt = euler_zyz_matrix(&euler_zyz(&[u[0], u[1], u[2], u[3]]));
+	for (a, b) in u.iter().zip(&rebuilt) {
+		assert!((a - b).norm() < 1e-10);
+	}
+}
+
+fn check(circuit: &Circuit) {
+	for basis in [Basis::RzSxXCnot, Basis::RxRyRzCz] {
+		let transpiled = transpile(circuit, basis).unwrap();
+		for gate in transpiled.gates() {
+			assert!(basis.contains(gate), "{:?} is not in {:?}", gate, basis);
+		}
+		assert!(equivalent(circuit, &transpiled, true), "{:?}", basis);
+	}
+}
+
+#[test]
+fn transpile_standard_gates() {
+	let gates = [
+		Gate::H(0),
+		Gate::SqrtY(1),
+		Gate::SqrtYdag(2),
+		Gate::S(0),
+		Gate::T(1),
+		Gate::X(2),
+		Gate::Y(0),
+		Gate::SqrtX(1),
+		Gate::Rx(2, 0.3),
+		Gate::Swap(0, 2),
+		Gate::Ccnot(0, 1, 2),
+		Gate::Ccz(2, 0, 1),
+		Gate::Cz(1, 2),
+		Gate::Cnot(2, 0),
+		Gate::Rxx(0, 2, 0.3),
+		Gate::Rzz(1, 2, -0.8),
+		Gate::ISwap(1, 2),
+		Gate::FSim(0, 1, 0.2, 0.5),
+		Gate::Ecr(2, 0),
+		Gate::Givens(0, 1, 0.4),
+		Gate::Phase(1, 0.6),
+		Gate::U3(2, 0.3, -0.4, 1.2),
+		Gate::Cp(2, 0, 0.7),
+		Gate::MultiControlledPhase {
+			controls: vec![0, 2],
+			target: 1,
+			angle: -0.5,
+		},
+		Gate::H(1).controlled(&[(0, ControlValue::Zero), (2, ControlValue::One)]),
+		Gate::Rx(0, 0.3).controlled(&[(2, ControlValue::Zero)]),
+	];
+	for gate in &gates {
+		let mut circuit = Circuit::new(3);
+		circuit.add_gate(gate.clone());
+		check(&circuit);
+	}
+	let mut circuit = Circuit::new(3);
+	for gate in &gates {
+		circuit.add_gate(gate.clone());
+	}
+	check(&circuit);
+}
+
+#[test]
+fn transpile_multi_controlled_and_pauli_rotation() {
+	let mut rng = rand::rngs::StdRng::seed_from_u64(9);
+	for controls in [vec![1], vec![0, 3], vec![3, 1, 0]] {
+		let mut circuit = Circuit::new(4);
+		circuit.add_gate(Gate::MultiControlled {
+			controls,
+			target: 2,
+			matrix: random_unitary(&mut rng),
+		});
+		check(&circuit);
+	}
+
+	let mut circuit = Circuit::new(4);
+	circuit.add_gate(Gate::PauliRotation {
+		targets: vec![3, 0, 1, 2],
+		paulis: vec![1, 2, 0, 3],
+		angle: 0.7,
+	});
+	check(&circuit);
+}
+
+#[test]
+fn transpile_controlled_diagonal_gates() {
+	let controls = [(0, ControlValue::One), (2, ControlValue::One)];
+	for gate in [Gate::Rz(1, 1.), Gate::Phase(1, 2.8), Gate::T(1)] {
+		let mut circuit = Circuit::new(3);
+		circuit.add_gate(gate.controlled(&controls));
+		check(&circuit);
+	}
+}
+
+#[test]
+fn transpile_controlled_two_qubit_gates() {
+	let gates = [
+		Gate::Swap(1, 2).controlled(&[(0, ControlValue::One)]),
+		Gate::Rxx(0, 2, 0.3).controlled(&[(1, ControlValue::One)]),
+		Gate::FSim(3, 1, 0.2, 0.5).controlled(&[(0, ControlValue::Zero), (2, ControlValue::One)]),
+	];
+	for gate in &gates {
+		let mut circuit = Circuit::new(4);
+		circuit.add_gate(gate.clone());
+		check(&circuit);
+	}
+}
+
+#[test]
+fn transpile_rejects_non_unitary() {
+	let mut circuit = Circuit::new(1);
+	circuit.add_gate(Gate::P0(0));
+	assert!(transpile(&circuit, Basis::RxRyRzCz).is_err());
+}
+
+#[test]
+fn transpile_two_qubit_dense_matrix() {
+	let mut rng = rand::rngs::StdRng::seed_from_u64(21);
+	let mut circuit = Circuit::new(3);
+	circuit.add_gate(Gate::DenseMatrix {
+		targets: vec![1, 2],
+		matrix: haar_random_unitary(4, &mut rng),
+	});
+	circuit.add_gate(Gate::DenseMatrix {
+		targets: vec![0, 1],
+		matrix: haar_random_unitary(4, &mut rng),
+	});
+	check(&circuit);
+}